@@ -0,0 +1,10 @@
+CREATE TABLE processed_events (
+    id uuid NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    event text NOT NULL,
+    key text NOT NULL,
+    created_at timestamp NOT NULL DEFAULT now(),
+    updated_at timestamp NOT NULL DEFAULT now()
+);
+
+SELECT diesel_manage_updated_at('processed_events');
+CREATE UNIQUE INDEX processed_events_event_key ON processed_events (event, key);
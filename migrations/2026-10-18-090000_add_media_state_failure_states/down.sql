@@ -0,0 +1,19 @@
+ALTER TABLE recordings DROP COLUMN state_reason;
+ALTER TABLE recordings DROP COLUMN state_changed_at;
+ALTER TABLE avatars DROP COLUMN state_reason;
+ALTER TABLE avatars DROP COLUMN state_changed_at;
+
+ALTER TABLE recordings ALTER COLUMN state DROP DEFAULT;
+ALTER TABLE avatars ALTER COLUMN state DROP DEFAULT;
+ALTER TYPE media_state RENAME TO media_state_old;
+CREATE TYPE media_state AS ENUM ('created', 'uploaded', 'processed');
+
+UPDATE recordings SET state = 'uploaded' WHERE state NOT IN ('created', 'uploaded', 'processed');
+UPDATE avatars SET state = 'uploaded' WHERE state NOT IN ('created', 'uploaded', 'processed');
+
+ALTER TABLE recordings ALTER COLUMN state TYPE media_state USING state::text::media_state;
+ALTER TABLE avatars ALTER COLUMN state TYPE media_state USING state::text::media_state;
+ALTER TABLE recordings ALTER COLUMN state SET DEFAULT 'created';
+ALTER TABLE avatars ALTER COLUMN state SET DEFAULT 'created';
+
+DROP TYPE media_state_old;
@@ -0,0 +1,9 @@
+ALTER TYPE media_state ADD VALUE 'moderating' AFTER 'uploaded';
+ALTER TYPE media_state ADD VALUE 'transcoding' AFTER 'moderating';
+ALTER TYPE media_state ADD VALUE 'rejected';
+ALTER TYPE media_state ADD VALUE 'failed';
+
+ALTER TABLE recordings ADD COLUMN state_reason text;
+ALTER TABLE recordings ADD COLUMN state_changed_at timestamp NOT NULL DEFAULT now();
+ALTER TABLE avatars ADD COLUMN state_reason text;
+ALTER TABLE avatars ADD COLUMN state_changed_at timestamp NOT NULL DEFAULT now();
@@ -0,0 +1,2 @@
+DROP TABLE processing_jobs;
+DROP TYPE processing_job_kind;
@@ -0,0 +1,17 @@
+CREATE TYPE processing_job_kind AS ENUM ('moderation', 'transcoding');
+
+CREATE TABLE processing_jobs (
+    id uuid NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    recording_id uuid NOT NULL REFERENCES recordings(id) ON DELETE CASCADE,
+    kind processing_job_kind NOT NULL,
+    external_id text NOT NULL,
+    progress integer,
+    started_at timestamp NOT NULL DEFAULT now(),
+    finished_at timestamp,
+    created_at timestamp NOT NULL DEFAULT now(),
+    updated_at timestamp NOT NULL DEFAULT now()
+);
+
+SELECT diesel_manage_updated_at('processing_jobs');
+CREATE UNIQUE INDEX processing_jobs_external_id ON processing_jobs (external_id);
+CREATE INDEX processing_jobs_recording_id ON processing_jobs (recording_id);
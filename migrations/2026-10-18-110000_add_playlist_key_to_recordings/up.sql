@@ -0,0 +1 @@
+ALTER TABLE recordings ADD COLUMN playlist_key text;
@@ -0,0 +1,2 @@
+ALTER TABLE avatars DROP COLUMN moderation_labels;
+ALTER TABLE recordings DROP COLUMN moderation_labels;
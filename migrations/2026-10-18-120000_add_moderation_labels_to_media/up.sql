@@ -0,0 +1,2 @@
+ALTER TABLE recordings ADD COLUMN moderation_labels jsonb;
+ALTER TABLE avatars ADD COLUMN moderation_labels jsonb;
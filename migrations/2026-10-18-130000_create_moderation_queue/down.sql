@@ -0,0 +1,14 @@
+ALTER TABLE recordings ALTER COLUMN state DROP DEFAULT;
+ALTER TABLE avatars ALTER COLUMN state DROP DEFAULT;
+ALTER TYPE media_state RENAME TO media_state_old;
+CREATE TYPE media_state AS ENUM ('created', 'uploaded', 'moderating', 'transcoding', 'processed', 'rejected', 'failed');
+
+UPDATE recordings SET state = 'moderating' WHERE state = 'flagged';
+UPDATE avatars SET state = 'moderating' WHERE state = 'flagged';
+
+ALTER TABLE recordings ALTER COLUMN state TYPE media_state USING state::text::media_state;
+ALTER TABLE avatars ALTER COLUMN state TYPE media_state USING state::text::media_state;
+ALTER TABLE recordings ALTER COLUMN state SET DEFAULT 'created';
+ALTER TABLE avatars ALTER COLUMN state SET DEFAULT 'created';
+
+DROP TYPE media_state_old;
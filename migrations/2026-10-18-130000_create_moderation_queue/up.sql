@@ -0,0 +1 @@
+ALTER TYPE media_state ADD VALUE 'flagged' AFTER 'moderating';
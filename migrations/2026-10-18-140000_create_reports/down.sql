@@ -0,0 +1,6 @@
+ALTER TABLE comments DROP COLUMN hidden_at;
+ALTER TABLE recordings DROP COLUMN hidden_at;
+
+DROP TABLE reports;
+DROP TYPE report_status;
+DROP TYPE report_reason;
@@ -0,0 +1,24 @@
+CREATE TYPE report_reason AS ENUM ('spam', 'harassment', 'hate_speech', 'inappropriate_content', 'cheating', 'other');
+CREATE TYPE report_status AS ENUM ('open', 'dismissed', 'actioned');
+
+CREATE TABLE reports (
+    id uuid NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    reporter_id uuid NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    recording_id uuid REFERENCES recordings(id) ON DELETE CASCADE,
+    comment_id uuid REFERENCES comments(id) ON DELETE CASCADE,
+    reason report_reason NOT NULL,
+    details text NOT NULL DEFAULT '',
+    status report_status NOT NULL DEFAULT 'open',
+    resolved_at timestamp,
+    created_at timestamp NOT NULL DEFAULT now(),
+    updated_at timestamp NOT NULL DEFAULT now(),
+    CHECK ((recording_id IS NULL) <> (comment_id IS NULL))
+);
+
+SELECT diesel_manage_updated_at('reports');
+CREATE INDEX reports_status ON reports (status, created_at);
+CREATE UNIQUE INDEX reports_open_recording_reporter ON reports (recording_id, reporter_id) WHERE status = 'open';
+CREATE UNIQUE INDEX reports_open_comment_reporter ON reports (comment_id, reporter_id) WHERE status = 'open';
+
+ALTER TABLE recordings ADD COLUMN hidden_at timestamp;
+ALTER TABLE comments ADD COLUMN hidden_at timestamp;
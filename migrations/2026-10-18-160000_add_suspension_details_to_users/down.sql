@@ -0,0 +1,4 @@
+ALTER TABLE users DROP COLUMN shadow_banned_at;
+ALTER TABLE users DROP COLUMN suspension_reason;
+ALTER TABLE users DROP COLUMN suspended_until;
+ALTER TABLE users DROP COLUMN suspended_at;
@@ -0,0 +1,4 @@
+ALTER TABLE users ADD COLUMN suspended_at timestamp;
+ALTER TABLE users ADD COLUMN suspended_until timestamp;
+ALTER TABLE users ADD COLUMN suspension_reason text;
+ALTER TABLE users ADD COLUMN shadow_banned_at timestamp;
@@ -0,0 +1,5 @@
+DROP TRIGGER audit_events_append_only ON audit_events;
+DROP FUNCTION prevent_audit_event_changes();
+DROP TABLE audit_events;
+DROP TYPE audit_target_type;
+DROP TYPE audit_action;
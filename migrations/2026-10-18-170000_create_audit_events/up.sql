@@ -0,0 +1,44 @@
+CREATE TYPE audit_action AS ENUM (
+    'login',
+    'login_failed',
+    'password_reset_requested',
+    'password_reset',
+    'recording_deleted',
+    'comment_deleted',
+    'media_approved',
+    'media_rejected',
+    'report_dismissed',
+    'report_actioned',
+    'user_suspended',
+    'user_unsuspended',
+    'user_shadow_banned',
+    'user_unshadow_banned',
+    'user_role_changed'
+);
+
+CREATE TYPE audit_target_type AS ENUM ('user', 'recording', 'comment', 'avatar', 'report');
+
+CREATE TABLE audit_events (
+    id uuid NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    action audit_action NOT NULL,
+    actor_id uuid,
+    target_type audit_target_type,
+    target_id uuid,
+    ip_address text,
+    payload jsonb NOT NULL DEFAULT '{}',
+    created_at timestamp NOT NULL DEFAULT now()
+);
+
+CREATE INDEX audit_events_created_at ON audit_events (created_at);
+CREATE INDEX audit_events_actor_id ON audit_events (actor_id, created_at);
+CREATE INDEX audit_events_target_id ON audit_events (target_id, created_at);
+
+CREATE FUNCTION prevent_audit_event_changes() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_events_append_only
+    BEFORE UPDATE OR DELETE ON audit_events
+    FOR EACH ROW EXECUTE PROCEDURE prevent_audit_event_changes();
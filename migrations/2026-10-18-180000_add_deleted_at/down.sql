@@ -0,0 +1,9 @@
+-- 'recording_restored' and 'comment_restored' stay in audit_action. Postgres
+-- can't drop enum values, and rebuilding the type would mean deleting the
+-- audit events that use them.
+
+DROP INDEX comments_deleted_at;
+DROP INDEX recordings_deleted_at;
+
+ALTER TABLE comments DROP COLUMN deleted_at;
+ALTER TABLE recordings DROP COLUMN deleted_at;
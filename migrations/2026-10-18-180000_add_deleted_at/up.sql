@@ -0,0 +1,8 @@
+ALTER TABLE recordings ADD COLUMN deleted_at timestamp;
+ALTER TABLE comments ADD COLUMN deleted_at timestamp;
+
+CREATE INDEX recordings_deleted_at ON recordings (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX comments_deleted_at ON comments (deleted_at) WHERE deleted_at IS NOT NULL;
+
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'recording_restored';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'comment_restored';
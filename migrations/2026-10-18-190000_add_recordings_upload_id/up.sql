@@ -0,0 +1 @@
+ALTER TABLE recordings ADD COLUMN upload_id text;
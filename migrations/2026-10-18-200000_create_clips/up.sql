@@ -0,0 +1,20 @@
+CREATE TYPE clip_render_state AS ENUM ('pending', 'rendered', 'failed');
+
+CREATE TABLE clips (
+    id uuid NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    recording_id uuid NOT NULL REFERENCES recordings(id) ON DELETE CASCADE,
+    user_id uuid NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    title text NOT NULL,
+    start_timestamp bigint NOT NULL,
+    end_timestamp bigint NOT NULL,
+    render_state clip_render_state,
+    render_job_id text,
+    video_key text,
+    created_at timestamp NOT NULL DEFAULT now(),
+    updated_at timestamp NOT NULL DEFAULT now(),
+    CHECK (start_timestamp >= 0 AND end_timestamp > start_timestamp)
+);
+
+SELECT diesel_manage_updated_at('clips');
+CREATE INDEX clips_recording_id ON clips (recording_id, start_timestamp);
+CREATE INDEX clips_render_job_id ON clips (render_job_id) WHERE render_job_id IS NOT NULL;
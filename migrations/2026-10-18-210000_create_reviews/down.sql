@@ -0,0 +1,2 @@
+DROP TABLE review_scores;
+DROP TABLE reviews;
@@ -0,0 +1,19 @@
+CREATE TABLE reviews (
+    id uuid NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    recording_id uuid NOT NULL REFERENCES recordings(id) ON DELETE CASCADE,
+    reviewer_id uuid NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    body text NOT NULL DEFAULT '',
+    created_at timestamp NOT NULL DEFAULT now(),
+    updated_at timestamp NOT NULL DEFAULT now(),
+    UNIQUE (recording_id, reviewer_id)
+);
+
+SELECT diesel_manage_updated_at('reviews');
+
+CREATE TABLE review_scores (
+    review_id uuid NOT NULL REFERENCES reviews(id) ON DELETE CASCADE,
+    category text NOT NULL,
+    score smallint NOT NULL CHECK (score BETWEEN 1 AND 5),
+    comment text NOT NULL DEFAULT '',
+    PRIMARY KEY (review_id, category)
+);
@@ -0,0 +1,8 @@
+DROP INDEX reviews_accepted_recording;
+
+ALTER TABLE reviews DROP COLUMN helpful_at;
+ALTER TABLE reviews DROP COLUMN accepted_at;
+ALTER TABLE reviews DROP COLUMN focus_area;
+ALTER TABLE reviews DROP COLUMN weaknesses;
+ALTER TABLE reviews DROP COLUMN strengths;
+ALTER TABLE reviews DROP COLUMN rating;
@@ -0,0 +1,8 @@
+ALTER TABLE reviews ADD COLUMN rating smallint CHECK (rating BETWEEN 1 AND 5);
+ALTER TABLE reviews ADD COLUMN strengths text NOT NULL DEFAULT '';
+ALTER TABLE reviews ADD COLUMN weaknesses text NOT NULL DEFAULT '';
+ALTER TABLE reviews ADD COLUMN focus_area text NOT NULL DEFAULT '';
+ALTER TABLE reviews ADD COLUMN accepted_at timestamp;
+ALTER TABLE reviews ADD COLUMN helpful_at timestamp;
+
+CREATE UNIQUE INDEX reviews_accepted_recording ON reviews (recording_id) WHERE accepted_at IS NOT NULL;
@@ -0,0 +1,6 @@
+-- 'game_created', 'game_updated', 'skill_level_created' and
+-- 'skill_level_updated' stay in audit_action. Postgres can't drop enum values,
+-- and rebuilding the type would mean deleting the audit events that use them.
+
+DROP TABLE skill_levels;
+DROP TABLE games;
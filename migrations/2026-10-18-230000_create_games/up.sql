@@ -0,0 +1,93 @@
+CREATE TABLE games (
+    id text NOT NULL PRIMARY KEY,
+    name text NOT NULL,
+    transcoding_profile text NOT NULL DEFAULT 'default',
+    review_categories jsonb NOT NULL DEFAULT '[]',
+    position smallint NOT NULL,
+    created_at timestamp NOT NULL DEFAULT now(),
+    updated_at timestamp NOT NULL DEFAULT now()
+);
+
+SELECT diesel_manage_updated_at('games');
+
+-- `value` is what recordings store and never changes once assigned, while
+-- `position` orders the ranks so new ones can be slotted in between.
+CREATE TABLE skill_levels (
+    game_id text NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    value smallint NOT NULL,
+    name text NOT NULL,
+    position smallint NOT NULL,
+    deprecated_at timestamp,
+    created_at timestamp NOT NULL DEFAULT now(),
+    updated_at timestamp NOT NULL DEFAULT now(),
+    PRIMARY KEY (game_id, value)
+);
+
+SELECT diesel_manage_updated_at('skill_levels');
+
+INSERT INTO games (id, name, transcoding_profile, review_categories, position) VALUES
+    ('overwatch', 'Overwatch', 'default', '[{"key": "positioning", "name": "Positioning"}, {"key": "cooldown_usage", "name": "Cooldown usage"}, {"key": "target_priority", "name": "Target priority"}, {"key": "ultimate_usage", "name": "Ultimate usage"}, {"key": "communication", "name": "Communication"}]', 0),
+    ('apex', 'Apex Legends', 'default', '[{"key": "positioning", "name": "Positioning"}, {"key": "aim", "name": "Aim"}, {"key": "rotations", "name": "Rotations"}, {"key": "ability_usage", "name": "Ability usage"}, {"key": "decision_making", "name": "Decision making"}]', 1),
+    ('cs2', 'Counter-Strike 2', 'high_framerate', '[{"key": "positioning", "name": "Positioning"}, {"key": "aim", "name": "Aim"}, {"key": "utility_usage", "name": "Utility usage"}, {"key": "economy", "name": "Economy"}, {"key": "communication", "name": "Communication"}]', 2),
+    ('dota2', 'Dota 2', 'default', '[{"key": "laning", "name": "Laning"}, {"key": "farming", "name": "Farming"}, {"key": "itemization", "name": "Itemization"}, {"key": "map_awareness", "name": "Map awareness"}, {"key": "teamfighting", "name": "Teamfighting"}]', 3),
+    ('lol', 'League of Legends', 'default', '[{"key": "laning", "name": "Laning"}, {"key": "warding", "name": "Warding"}, {"key": "objective_control", "name": "Objective control"}, {"key": "map_awareness", "name": "Map awareness"}, {"key": "teamfighting", "name": "Teamfighting"}]', 4),
+    ('valorant', 'Valorant', 'high_framerate', '[{"key": "positioning", "name": "Positioning"}, {"key": "aim", "name": "Aim"}, {"key": "utility_usage", "name": "Utility usage"}, {"key": "economy", "name": "Economy"}, {"key": "communication", "name": "Communication"}]', 5);
+
+INSERT INTO skill_levels (game_id, value, name, position) VALUES
+    ('overwatch', 0, 'Bronze', 0),
+    ('overwatch', 1, 'Silver', 1),
+    ('overwatch', 2, 'Gold', 2),
+    ('overwatch', 3, 'Platinum', 3),
+    ('overwatch', 4, 'Diamond', 4),
+    ('overwatch', 5, 'Masters', 5),
+    ('overwatch', 6, 'Grandmaster', 6),
+    ('apex', 0, 'Bronze', 0),
+    ('apex', 1, 'Silver', 1),
+    ('apex', 2, 'Gold', 2),
+    ('apex', 3, 'Platinum', 3),
+    ('apex', 4, 'Diamond', 4),
+    ('apex', 5, 'Master', 5),
+    ('apex', 6, 'Predator', 6),
+    ('cs2', 0, 'Silver', 0),
+    ('cs2', 1, 'Silver Elite', 1),
+    ('cs2', 2, 'Silver Elite Master', 2),
+    ('cs2', 3, 'Gold Nova', 3),
+    ('cs2', 4, 'Gold Nova Master', 4),
+    ('cs2', 5, 'Master Guardian', 5),
+    ('cs2', 6, 'Master Guardian Elite', 6),
+    ('cs2', 7, 'Distinguished Master Guardian', 7),
+    ('cs2', 8, 'Legendary Eagle', 8),
+    ('cs2', 9, 'Legendary Eagle Master', 9),
+    ('cs2', 10, 'Supreme Master First Class', 10),
+    ('cs2', 11, 'Global Elite', 11),
+    ('dota2', 0, 'Herald', 0),
+    ('dota2', 1, 'Guardian', 1),
+    ('dota2', 2, 'Crusader', 2),
+    ('dota2', 3, 'Archon', 3),
+    ('dota2', 4, 'Legend', 4),
+    ('dota2', 5, 'Ancient', 5),
+    ('dota2', 6, 'Divine', 6),
+    ('dota2', 7, 'Immortal', 7),
+    ('lol', 0, 'Iron', 0),
+    ('lol', 1, 'Bronze', 1),
+    ('lol', 2, 'Silver', 2),
+    ('lol', 3, 'Gold', 3),
+    ('lol', 4, 'Platinum', 4),
+    ('lol', 5, 'Emerald', 5),
+    ('lol', 6, 'Diamond', 6),
+    ('lol', 7, 'Master', 7),
+    ('lol', 8, 'Grandmaster', 8),
+    ('lol', 9, 'Challenger', 9),
+    ('valorant', 0, 'Iron', 0),
+    ('valorant', 1, 'Bronze', 1),
+    ('valorant', 2, 'Silver', 2),
+    ('valorant', 3, 'Gold', 3),
+    ('valorant', 4, 'Platinum', 4),
+    ('valorant', 5, 'Diamond', 5),
+    ('valorant', 6, 'Immortal', 6),
+    ('valorant', 7, 'Radiant', 7);
+
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'game_created';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'game_updated';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'skill_level_created';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'skill_level_updated';
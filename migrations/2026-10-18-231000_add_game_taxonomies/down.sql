@@ -0,0 +1,14 @@
+-- 'taxonomy_entry_created' and 'taxonomy_entry_updated' stay in audit_action.
+-- Postgres can't drop enum values, and rebuilding the type would mean deleting
+-- the audit events that use them.
+
+ALTER TABLE followings DROP COLUMN hero;
+ALTER TABLE followings DROP COLUMN role;
+ALTER TABLE followings DROP COLUMN map;
+
+ALTER TABLE recordings DROP COLUMN hero;
+ALTER TABLE recordings DROP COLUMN role;
+ALTER TABLE recordings DROP COLUMN map;
+
+DROP TABLE taxonomy_entries;
+DROP TYPE taxonomy_kind;
@@ -0,0 +1,211 @@
+CREATE TYPE taxonomy_kind AS ENUM ('map', 'role', 'hero');
+
+-- Maps, roles and heroes (or agents, champions, legends) a recording can be
+-- tagged with. Recordings and followings store `key`, which never changes.
+CREATE TABLE taxonomy_entries (
+    game_id text NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    kind taxonomy_kind NOT NULL,
+    key text NOT NULL,
+    name text NOT NULL,
+    position smallint NOT NULL,
+    deprecated_at timestamp,
+    created_at timestamp NOT NULL DEFAULT now(),
+    updated_at timestamp NOT NULL DEFAULT now(),
+    PRIMARY KEY (game_id, kind, key)
+);
+
+SELECT diesel_manage_updated_at('taxonomy_entries');
+
+-- Dota 2 and League of Legends rosters are left to be added through the admin
+-- API, and neither has more than one competitive map.
+INSERT INTO taxonomy_entries (game_id, kind, key, name, position) VALUES
+    ('overwatch', 'map', 'antarctic_peninsula', 'Antarctic Peninsula', 0),
+    ('overwatch', 'map', 'busan', 'Busan', 1),
+    ('overwatch', 'map', 'ilios', 'Ilios', 2),
+    ('overwatch', 'map', 'lijiang_tower', 'Lijiang Tower', 3),
+    ('overwatch', 'map', 'nepal', 'Nepal', 4),
+    ('overwatch', 'map', 'oasis', 'Oasis', 5),
+    ('overwatch', 'map', 'samoa', 'Samoa', 6),
+    ('overwatch', 'map', 'circuit_royal', 'Circuit Royal', 7),
+    ('overwatch', 'map', 'dorado', 'Dorado', 8),
+    ('overwatch', 'map', 'havana', 'Havana', 9),
+    ('overwatch', 'map', 'junkertown', 'Junkertown', 10),
+    ('overwatch', 'map', 'rialto', 'Rialto', 11),
+    ('overwatch', 'map', 'route_66', 'Route 66', 12),
+    ('overwatch', 'map', 'shambali_monastery', 'Shambali Monastery', 13),
+    ('overwatch', 'map', 'watchpoint_gibraltar', 'Watchpoint: Gibraltar', 14),
+    ('overwatch', 'map', 'blizzard_world', 'Blizzard World', 15),
+    ('overwatch', 'map', 'eichenwalde', 'Eichenwalde', 16),
+    ('overwatch', 'map', 'hollywood', 'Hollywood', 17),
+    ('overwatch', 'map', 'kings_row', 'King''s Row', 18),
+    ('overwatch', 'map', 'midtown', 'Midtown', 19),
+    ('overwatch', 'map', 'numbani', 'Numbani', 20),
+    ('overwatch', 'map', 'paraiso', 'Paraíso', 21),
+    ('overwatch', 'map', 'colosseo', 'Colosseo', 22),
+    ('overwatch', 'map', 'esperanca', 'Esperança', 23),
+    ('overwatch', 'map', 'new_queen_street', 'New Queen Street', 24),
+    ('overwatch', 'map', 'runasapi', 'Runasapi', 25),
+    ('overwatch', 'map', 'new_junk_city', 'New Junk City', 26),
+    ('overwatch', 'map', 'suravasa', 'Suravasa', 27),
+    ('overwatch', 'role', 'tank', 'Tank', 0),
+    ('overwatch', 'role', 'damage', 'Damage', 1),
+    ('overwatch', 'role', 'support', 'Support', 2),
+    ('overwatch', 'hero', 'ana', 'Ana', 0),
+    ('overwatch', 'hero', 'ashe', 'Ashe', 1),
+    ('overwatch', 'hero', 'baptiste', 'Baptiste', 2),
+    ('overwatch', 'hero', 'bastion', 'Bastion', 3),
+    ('overwatch', 'hero', 'brigitte', 'Brigitte', 4),
+    ('overwatch', 'hero', 'cassidy', 'Cassidy', 5),
+    ('overwatch', 'hero', 'dva', 'D.Va', 6),
+    ('overwatch', 'hero', 'doomfist', 'Doomfist', 7),
+    ('overwatch', 'hero', 'echo', 'Echo', 8),
+    ('overwatch', 'hero', 'freja', 'Freja', 9),
+    ('overwatch', 'hero', 'genji', 'Genji', 10),
+    ('overwatch', 'hero', 'hanzo', 'Hanzo', 11),
+    ('overwatch', 'hero', 'hazard', 'Hazard', 12),
+    ('overwatch', 'hero', 'illari', 'Illari', 13),
+    ('overwatch', 'hero', 'junker_queen', 'Junker Queen', 14),
+    ('overwatch', 'hero', 'junkrat', 'Junkrat', 15),
+    ('overwatch', 'hero', 'juno', 'Juno', 16),
+    ('overwatch', 'hero', 'kiriko', 'Kiriko', 17),
+    ('overwatch', 'hero', 'lifeweaver', 'Lifeweaver', 18),
+    ('overwatch', 'hero', 'lucio', 'Lúcio', 19),
+    ('overwatch', 'hero', 'mauga', 'Mauga', 20),
+    ('overwatch', 'hero', 'mei', 'Mei', 21),
+    ('overwatch', 'hero', 'mercy', 'Mercy', 22),
+    ('overwatch', 'hero', 'moira', 'Moira', 23),
+    ('overwatch', 'hero', 'orisa', 'Orisa', 24),
+    ('overwatch', 'hero', 'pharah', 'Pharah', 25),
+    ('overwatch', 'hero', 'ramattra', 'Ramattra', 26),
+    ('overwatch', 'hero', 'reaper', 'Reaper', 27),
+    ('overwatch', 'hero', 'reinhardt', 'Reinhardt', 28),
+    ('overwatch', 'hero', 'roadhog', 'Roadhog', 29),
+    ('overwatch', 'hero', 'sigma', 'Sigma', 30),
+    ('overwatch', 'hero', 'sojourn', 'Sojourn', 31),
+    ('overwatch', 'hero', 'soldier_76', 'Soldier: 76', 32),
+    ('overwatch', 'hero', 'sombra', 'Sombra', 33),
+    ('overwatch', 'hero', 'symmetra', 'Symmetra', 34),
+    ('overwatch', 'hero', 'torbjorn', 'Torbjörn', 35),
+    ('overwatch', 'hero', 'tracer', 'Tracer', 36),
+    ('overwatch', 'hero', 'venture', 'Venture', 37),
+    ('overwatch', 'hero', 'widowmaker', 'Widowmaker', 38),
+    ('overwatch', 'hero', 'winston', 'Winston', 39),
+    ('overwatch', 'hero', 'wrecking_ball', 'Wrecking Ball', 40),
+    ('overwatch', 'hero', 'zarya', 'Zarya', 41),
+    ('overwatch', 'hero', 'zenyatta', 'Zenyatta', 42),
+    ('apex', 'map', 'kings_canyon', 'Kings Canyon', 0),
+    ('apex', 'map', 'worlds_edge', 'World''s Edge', 1),
+    ('apex', 'map', 'olympus', 'Olympus', 2),
+    ('apex', 'map', 'storm_point', 'Storm Point', 3),
+    ('apex', 'map', 'broken_moon', 'Broken Moon', 4),
+    ('apex', 'map', 'e_district', 'E-District', 5),
+    ('apex', 'role', 'assault', 'Assault', 0),
+    ('apex', 'role', 'skirmisher', 'Skirmisher', 1),
+    ('apex', 'role', 'recon', 'Recon', 2),
+    ('apex', 'role', 'controller', 'Controller', 3),
+    ('apex', 'role', 'support', 'Support', 4),
+    ('apex', 'hero', 'alter', 'Alter', 0),
+    ('apex', 'hero', 'ash', 'Ash', 1),
+    ('apex', 'hero', 'ballistic', 'Ballistic', 2),
+    ('apex', 'hero', 'bangalore', 'Bangalore', 3),
+    ('apex', 'hero', 'bloodhound', 'Bloodhound', 4),
+    ('apex', 'hero', 'catalyst', 'Catalyst', 5),
+    ('apex', 'hero', 'caustic', 'Caustic', 6),
+    ('apex', 'hero', 'conduit', 'Conduit', 7),
+    ('apex', 'hero', 'crypto', 'Crypto', 8),
+    ('apex', 'hero', 'fuse', 'Fuse', 9),
+    ('apex', 'hero', 'gibraltar', 'Gibraltar', 10),
+    ('apex', 'hero', 'horizon', 'Horizon', 11),
+    ('apex', 'hero', 'lifeline', 'Lifeline', 12),
+    ('apex', 'hero', 'loba', 'Loba', 13),
+    ('apex', 'hero', 'mad_maggie', 'Mad Maggie', 14),
+    ('apex', 'hero', 'mirage', 'Mirage', 15),
+    ('apex', 'hero', 'newcastle', 'Newcastle', 16),
+    ('apex', 'hero', 'octane', 'Octane', 17),
+    ('apex', 'hero', 'pathfinder', 'Pathfinder', 18),
+    ('apex', 'hero', 'rampart', 'Rampart', 19),
+    ('apex', 'hero', 'revenant', 'Revenant', 20),
+    ('apex', 'hero', 'seer', 'Seer', 21),
+    ('apex', 'hero', 'sparrow', 'Sparrow', 22),
+    ('apex', 'hero', 'valkyrie', 'Valkyrie', 23),
+    ('apex', 'hero', 'vantage', 'Vantage', 24),
+    ('apex', 'hero', 'wattson', 'Wattson', 25),
+    ('apex', 'hero', 'wraith', 'Wraith', 26),
+    ('cs2', 'map', 'ancient', 'Ancient', 0),
+    ('cs2', 'map', 'anubis', 'Anubis', 1),
+    ('cs2', 'map', 'dust_ii', 'Dust II', 2),
+    ('cs2', 'map', 'inferno', 'Inferno', 3),
+    ('cs2', 'map', 'mirage', 'Mirage', 4),
+    ('cs2', 'map', 'nuke', 'Nuke', 5),
+    ('cs2', 'map', 'overpass', 'Overpass', 6),
+    ('cs2', 'map', 'train', 'Train', 7),
+    ('cs2', 'map', 'vertigo', 'Vertigo', 8),
+    ('cs2', 'role', 'entry', 'Entry', 0),
+    ('cs2', 'role', 'awper', 'AWPer', 1),
+    ('cs2', 'role', 'igl', 'In-game leader', 2),
+    ('cs2', 'role', 'support', 'Support', 3),
+    ('cs2', 'role', 'lurker', 'Lurker', 4),
+    ('dota2', 'role', 'carry', 'Carry', 0),
+    ('dota2', 'role', 'mid', 'Mid', 1),
+    ('dota2', 'role', 'offlane', 'Offlane', 2),
+    ('dota2', 'role', 'soft_support', 'Soft support', 3),
+    ('dota2', 'role', 'hard_support', 'Hard support', 4),
+    ('lol', 'role', 'top', 'Top', 0),
+    ('lol', 'role', 'jungle', 'Jungle', 1),
+    ('lol', 'role', 'mid', 'Mid', 2),
+    ('lol', 'role', 'bot', 'Bot', 3),
+    ('lol', 'role', 'support', 'Support', 4),
+    ('valorant', 'map', 'abyss', 'Abyss', 0),
+    ('valorant', 'map', 'ascent', 'Ascent', 1),
+    ('valorant', 'map', 'bind', 'Bind', 2),
+    ('valorant', 'map', 'breeze', 'Breeze', 3),
+    ('valorant', 'map', 'corrode', 'Corrode', 4),
+    ('valorant', 'map', 'fracture', 'Fracture', 5),
+    ('valorant', 'map', 'haven', 'Haven', 6),
+    ('valorant', 'map', 'icebox', 'Icebox', 7),
+    ('valorant', 'map', 'lotus', 'Lotus', 8),
+    ('valorant', 'map', 'pearl', 'Pearl', 9),
+    ('valorant', 'map', 'split', 'Split', 10),
+    ('valorant', 'map', 'sunset', 'Sunset', 11),
+    ('valorant', 'role', 'duelist', 'Duelist', 0),
+    ('valorant', 'role', 'initiator', 'Initiator', 1),
+    ('valorant', 'role', 'controller', 'Controller', 2),
+    ('valorant', 'role', 'sentinel', 'Sentinel', 3),
+    ('valorant', 'hero', 'astra', 'Astra', 0),
+    ('valorant', 'hero', 'breach', 'Breach', 1),
+    ('valorant', 'hero', 'brimstone', 'Brimstone', 2),
+    ('valorant', 'hero', 'chamber', 'Chamber', 3),
+    ('valorant', 'hero', 'clove', 'Clove', 4),
+    ('valorant', 'hero', 'cypher', 'Cypher', 5),
+    ('valorant', 'hero', 'deadlock', 'Deadlock', 6),
+    ('valorant', 'hero', 'fade', 'Fade', 7),
+    ('valorant', 'hero', 'gekko', 'Gekko', 8),
+    ('valorant', 'hero', 'harbor', 'Harbor', 9),
+    ('valorant', 'hero', 'iso', 'Iso', 10),
+    ('valorant', 'hero', 'jett', 'Jett', 11),
+    ('valorant', 'hero', 'kayo', 'KAY/O', 12),
+    ('valorant', 'hero', 'killjoy', 'Killjoy', 13),
+    ('valorant', 'hero', 'neon', 'Neon', 14),
+    ('valorant', 'hero', 'omen', 'Omen', 15),
+    ('valorant', 'hero', 'phoenix', 'Phoenix', 16),
+    ('valorant', 'hero', 'raze', 'Raze', 17),
+    ('valorant', 'hero', 'reyna', 'Reyna', 18),
+    ('valorant', 'hero', 'sage', 'Sage', 19),
+    ('valorant', 'hero', 'skye', 'Skye', 20),
+    ('valorant', 'hero', 'sova', 'Sova', 21),
+    ('valorant', 'hero', 'tejo', 'Tejo', 22),
+    ('valorant', 'hero', 'viper', 'Viper', 23),
+    ('valorant', 'hero', 'vyse', 'Vyse', 24),
+    ('valorant', 'hero', 'waylay', 'Waylay', 25),
+    ('valorant', 'hero', 'yoru', 'Yoru', 26);
+
+ALTER TABLE recordings ADD COLUMN map text;
+ALTER TABLE recordings ADD COLUMN role text;
+ALTER TABLE recordings ADD COLUMN hero text;
+
+ALTER TABLE followings ADD COLUMN map text;
+ALTER TABLE followings ADD COLUMN role text;
+ALTER TABLE followings ADD COLUMN hero text;
+
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'taxonomy_entry_created';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'taxonomy_entry_updated';
@@ -0,0 +1,2 @@
+ALTER TABLE followings DROP COLUMN max_skill_level;
+ALTER TABLE followings DROP COLUMN min_skill_level;
@@ -0,0 +1,4 @@
+-- Both bounds are skill level values, compared by the ranks' positions so
+-- that ranks added later fall inside the range they were slotted into.
+ALTER TABLE followings ADD COLUMN min_skill_level smallint;
+ALTER TABLE followings ADD COLUMN max_skill_level smallint;
@@ -0,0 +1,13 @@
+ALTER TABLE users ADD COLUMN emails_enabled boolean NOT NULL DEFAULT true;
+
+UPDATE users SET emails_enabled = false
+WHERE id IN (
+    SELECT user_id FROM notification_preferences
+    WHERE frequency = 'off'
+    GROUP BY user_id
+    HAVING count(*) = cardinality(enum_range(NULL::notification_category))
+);
+
+DROP TABLE notification_preferences;
+DROP TYPE notification_frequency;
+DROP TYPE notification_category;
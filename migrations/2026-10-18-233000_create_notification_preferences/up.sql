@@ -0,0 +1,30 @@
+CREATE TYPE notification_category AS ENUM (
+    'comments_on_recordings',
+    'comment_replies',
+    'digest',
+    'processing_complete',
+    'product_news'
+);
+
+CREATE TYPE notification_frequency AS ENUM ('immediate', 'hourly', 'daily', 'weekly', 'off');
+
+-- Rows only exist for categories a user has changed or been emailed about;
+-- every other category uses its default frequency.
+CREATE TABLE notification_preferences (
+    user_id uuid NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    category notification_category NOT NULL,
+    frequency notification_frequency NOT NULL,
+    last_sent_at timestamp,
+    created_at timestamp NOT NULL DEFAULT now(),
+    updated_at timestamp NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_id, category)
+);
+
+SELECT diesel_manage_updated_at('notification_preferences');
+
+INSERT INTO notification_preferences (user_id, category, frequency)
+SELECT users.id, category, 'off'
+FROM users, unnest(enum_range(NULL::notification_category)) AS category
+WHERE NOT users.emails_enabled;
+
+ALTER TABLE users DROP COLUMN emails_enabled;
@@ -0,0 +1,20 @@
+use crate::data_types::AuditAction;
+use crate::guards::ClientIp;
+use crate::models::AuditEventChangeset;
+use crate::schema::audit_events;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+pub fn event(action: AuditAction, actor_id: Option<Uuid>, ip: &ClientIp) -> AuditEventChangeset {
+  AuditEventChangeset::default()
+    .action(action)
+    .actor_id(actor_id)
+    .ip_address(ip.0.clone())
+}
+
+pub fn record(conn: &mut PgConnection, event: AuditEventChangeset) -> QueryResult<()> {
+  diesel::insert_into(audit_events::table)
+    .values(event)
+    .execute(conn)
+    .map(|_| ())
+}
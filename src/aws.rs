@@ -1,4 +1,5 @@
 pub mod media_convert;
+pub mod s3;
 use crate::config::Config;
 use rusoto_core::credential::{AwsCredentials, CredentialsError, ProvideAwsCredentials};
 
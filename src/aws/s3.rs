@@ -0,0 +1,282 @@
+use crate::config::Config;
+use crate::models::Recording;
+use crate::transcoding::{CLIP_NAME_MODIFIER, HLS_NAME_MODIFIER};
+use anyhow::Result;
+use rocket::tokio::io::AsyncReadExt;
+use rusoto_core::Region;
+use rusoto_credential::AwsCredentials;
+use rusoto_s3::util::{PreSignedRequest, PreSignedRequestOption};
+use rusoto_s3::{
+  AbortMultipartUploadRequest, Delete, DeleteObjectsRequest, GetObjectRequest,
+  ListObjectsV2Request, ListPartsRequest, ObjectIdentifier, Part, PutObjectAclRequest, S3Client,
+  UploadPartRequest, S3,
+};
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+const MAX_DELETE_OBJECTS: usize = 1000;
+/// SigV4 presigned URLs can't be valid for longer than a week.
+const MAX_PRESIGN_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+/// Metadata attached to every upload so the uploads queue can route events
+/// back to the instance that issued the upload.
+pub fn upload_metadata(config: &Config) -> HashMap<String, String> {
+  let mut metadata = HashMap::new();
+
+  if let Some(instance_id) = config.instance_id.as_ref() {
+    metadata.insert("instance-id".to_string(), instance_id.to_string());
+  }
+
+  metadata
+}
+
+/// Maps any processed output key back to the key of the upload it was
+/// generated from.
+pub fn original_key(key: &str) -> String {
+  let original_key = key.replace("/processed", "/originals");
+  let original_key = original_key.split('_').next().unwrap_or_default();
+  let original_key = original_key.split('.').next().unwrap_or_default();
+
+  original_key.to_string()
+}
+
+pub async fn list_object_keys(
+  client: &S3Client,
+  bucket: &str,
+  prefix: &str,
+) -> Result<Vec<String>> {
+  let mut keys = vec![];
+  let mut continuation_token = None;
+
+  loop {
+    let response = client
+      .list_objects_v2(ListObjectsV2Request {
+        bucket: bucket.to_owned(),
+        prefix: Some(prefix.to_owned()),
+        continuation_token: continuation_token.clone(),
+        ..Default::default()
+      })
+      .await?;
+
+    keys.extend(
+      response
+        .contents
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|object| object.key),
+    );
+
+    continuation_token = response.next_continuation_token;
+
+    if continuation_token.is_none() {
+      break;
+    }
+  }
+
+  Ok(keys)
+}
+
+pub async fn delete_object_keys(client: &S3Client, bucket: &str, keys: Vec<String>) -> Result<()> {
+  for keys in keys.chunks(MAX_DELETE_OBJECTS) {
+    client
+      .delete_objects(DeleteObjectsRequest {
+        bucket: bucket.to_owned(),
+        delete: Delete {
+          objects: keys
+            .iter()
+            .map(|key| ObjectIdentifier {
+              key: key.clone(),
+              ..Default::default()
+            })
+            .collect(),
+          ..Default::default()
+        },
+        ..Default::default()
+      })
+      .await?;
+  }
+
+  Ok(())
+}
+
+pub async fn delete_recording_objects(
+  client: &S3Client,
+  bucket: &str,
+  recording: &Recording,
+) -> Result<()> {
+  let mut objects_to_delete = vec![];
+
+  if let Some(video_key) = &recording.video_key {
+    let clip_prefix = format!(
+      "{}{}",
+      video_key.replace("/originals/", "/processed/"),
+      CLIP_NAME_MODIFIER
+    );
+
+    objects_to_delete.push(video_key.clone());
+    objects_to_delete.extend(list_object_keys(client, bucket, &clip_prefix).await?);
+  }
+
+  if let Some(thumbnail_key) = &recording.thumbnail_key {
+    objects_to_delete.push(thumbnail_key.clone());
+  }
+
+  if let Some(processed_video_key) = &recording.processed_video_key {
+    objects_to_delete.push(processed_video_key.clone());
+  }
+
+  if let Some(playlist_key) = &recording.playlist_key {
+    let prefix = format!(
+      "{}{}",
+      playlist_key.trim_end_matches(".m3u8"),
+      HLS_NAME_MODIFIER
+    );
+
+    objects_to_delete.push(playlist_key.clone());
+    objects_to_delete.extend(list_object_keys(client, bucket, &prefix).await?);
+  }
+
+  delete_object_keys(client, bucket, objects_to_delete).await
+}
+
+/// Sets every object under the prefix back to the bucket's default private
+/// ACL, returning how many were updated. Media used to be uploaded as
+/// `public-read`.
+pub async fn make_objects_private(client: &S3Client, bucket: &str, prefix: &str) -> Result<usize> {
+  let keys = list_object_keys(client, bucket, prefix).await?;
+
+  for key in &keys {
+    client
+      .put_object_acl(PutObjectAclRequest {
+        bucket: bucket.to_owned(),
+        key: key.clone(),
+        acl: Some("private".to_owned()),
+        ..Default::default()
+      })
+      .await?;
+  }
+
+  Ok(keys.len())
+}
+
+pub async fn list_upload_parts(
+  client: &S3Client,
+  bucket: &str,
+  key: &str,
+  upload_id: &str,
+) -> Result<Vec<Part>> {
+  let mut parts = vec![];
+  let mut part_number_marker = None;
+
+  loop {
+    let response = client
+      .list_parts(ListPartsRequest {
+        bucket: bucket.to_owned(),
+        key: key.to_owned(),
+        upload_id: upload_id.to_owned(),
+        part_number_marker,
+        ..Default::default()
+      })
+      .await?;
+
+    parts.extend(response.parts.unwrap_or_default());
+
+    if !response.is_truncated.unwrap_or(false) {
+      break;
+    }
+
+    part_number_marker = response.next_part_number_marker;
+  }
+
+  Ok(parts)
+}
+
+pub async fn abort_upload(
+  client: &S3Client,
+  bucket: &str,
+  key: &str,
+  upload_id: &str,
+) -> Result<()> {
+  client
+    .abort_multipart_upload(AbortMultipartUploadRequest {
+      bucket: bucket.to_owned(),
+      key: key.to_owned(),
+      upload_id: upload_id.to_owned(),
+      ..Default::default()
+    })
+    .await?;
+
+  Ok(())
+}
+
+pub fn presign_upload_part(
+  config: &Config,
+  key: &str,
+  upload_id: &str,
+  part_number: i64,
+) -> String {
+  let req = UploadPartRequest {
+    bucket: config.uploads_bucket.to_owned(),
+    key: key.to_owned(),
+    upload_id: upload_id.to_owned(),
+    part_number,
+    ..Default::default()
+  };
+
+  req.get_presigned_url(
+    &Region::EuWest2,
+    &AwsCredentials::new(
+      &config.aws_access_key_id,
+      &config.aws_secret_key,
+      None,
+      None,
+    ),
+    &Default::default(),
+  )
+}
+
+/// Signs a short-lived URL for a private object in the uploads bucket.
+pub fn presign_download(config: &Config, key: &str) -> String {
+  presign_download_for(config, key, config.download_url_ttl)
+}
+
+/// Signs a URL for a private object that stays valid for `ttl` seconds.
+pub fn presign_download_for(config: &Config, key: &str, ttl: u64) -> String {
+  let req = GetObjectRequest {
+    bucket: config.uploads_bucket.to_owned(),
+    key: key.to_owned(),
+    ..Default::default()
+  };
+
+  req.get_presigned_url(
+    &Region::EuWest2,
+    &AwsCredentials::new(
+      &config.aws_access_key_id,
+      &config.aws_secret_key,
+      None,
+      None,
+    ),
+    &PreSignedRequestOption {
+      expires_in: Duration::from_secs(ttl.min(MAX_PRESIGN_SECONDS)),
+    },
+  )
+}
+
+pub async fn get_object_text(client: &S3Client, bucket: &str, key: &str) -> Result<String> {
+  let object = client
+    .get_object(GetObjectRequest {
+      bucket: bucket.to_owned(),
+      key: key.to_owned(),
+      ..Default::default()
+    })
+    .await?;
+
+  let mut body = String::new();
+
+  if let Some(stream) = object.body {
+    stream.into_async_read().read_to_string(&mut body).await?;
+  }
+
+  Ok(body)
+}
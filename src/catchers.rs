@@ -0,0 +1,28 @@
+use crate::guards::auth::Suspension;
+use rocket::serde::json::Json;
+use rocket::Request;
+use serde_json::{json, Value};
+
+/// Same shape as Rocket's default JSON error, with the suspension details
+/// added when the request was rejected because the user is suspended.
+#[catch(403)]
+pub fn forbidden(request: &Request) -> Json<Value> {
+  match request.local_cache(|| None::<Suspension>) {
+    Some(suspension) => Json(json!({
+      "error": {
+        "code": 403,
+        "reason": "Suspended",
+        "description": "Your account is suspended.",
+        "suspended_until": suspension.until,
+        "suspension_reason": suspension.reason,
+      }
+    })),
+    None => Json(json!({
+      "error": {
+        "code": 403,
+        "reason": "Forbidden",
+        "description": "The server refused to authorize the request.",
+      }
+    })),
+  }
+}
@@ -1,4 +1,23 @@
+use crate::garbage_collection::GarbageCollectionConfig;
+use crate::moderation::ModerationConfig;
+use crate::transcoding::{
+  default_profiles, deserialize_profiles, TranscoderKind, TranscodingProfile,
+};
+use crate::upload_limits::UploadLimitsConfig;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+fn default_deletion_retention_days() -> i64 {
+  30
+}
+
+fn default_download_url_ttl() -> u64 {
+  3600
+}
+
+fn default_report_hide_threshold() -> i64 {
+  3
+}
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Config {
@@ -6,16 +25,52 @@ pub struct Config {
   pub avatar_processor_lambda_arn: String,
   pub aws_access_key_id: String,
   pub aws_secret_key: String,
+  /// Number of days a deleted recording or comment can be restored before it
+  /// is purged.
+  #[serde(default = "default_deletion_retention_days")]
+  pub deletion_retention_days: i64,
+  /// Number of seconds signed download URLs stay valid for.
+  #[serde(default = "default_download_url_ttl")]
+  pub download_url_ttl: u64,
+  /// Path to the ffmpeg binary used by the ffmpeg transcoder, looked up in
+  /// `PATH` when unset.
+  pub ffmpeg_path: Option<String>,
+  /// When abandoned uploads and stuck media are cleaned up.
+  #[serde(default)]
+  pub garbage_collection: GarbageCollectionConfig,
   pub host: String,
   pub instance_id: Option<String>,
   pub intercom_access_token: Option<String>,
   pub intercom_verification_secret: Option<String>,
+  /// Queue of MediaConvert job state changes, used to track transcoding
+  /// progress and failures.
+  pub media_convert_events_queue_url: Option<String>,
   pub media_convert_queue_arn: String,
   pub media_convert_role_arn: String,
+  /// Which provider checks uploads for inappropriate content, and which of its
+  /// labels flag an upload for review.
+  #[serde(default)]
+  pub moderation: ModerationConfig,
   pub rekognition_queue_url: String,
   pub rekognition_role_arn: String,
   pub rekognition_topic_arn: String,
+  /// Number of open reports from distinct users after which content is hidden
+  /// pending review.
+  #[serde(default = "default_report_hide_threshold")]
+  pub report_hide_threshold: i64,
   pub sentry_dsn: Option<String>,
+  /// Backend that transcodes uploaded recordings.
+  #[serde(default)]
+  pub transcoder: TranscoderKind,
+  /// Output settings by profile name, which games refer to.
+  #[serde(
+    default = "default_profiles",
+    deserialize_with = "deserialize_profiles"
+  )]
+  pub transcoding_profiles: HashMap<String, TranscodingProfile>,
+  /// Size, duration and content type limits for uploads.
+  #[serde(default)]
+  pub upload_limits: UploadLimitsConfig,
   pub uploads_bucket: String,
   pub uploads_queue_url: String,
   pub web_host: String,
@@ -1,2 +1,22 @@
+mod audit_action;
+mod audit_target_type;
+mod clip_render_state;
 mod media_state;
-pub use media_state::MediaState;
+mod notification_category;
+mod notification_frequency;
+mod processing_job_kind;
+mod report_reason;
+mod report_status;
+mod taxonomy_kind;
+mod user_role;
+pub use audit_action::AuditAction;
+pub use audit_target_type::AuditTargetType;
+pub use clip_render_state::ClipRenderState;
+pub use media_state::{MediaState, MediaStateTransitionError};
+pub use notification_category::NotificationCategory;
+pub use notification_frequency::NotificationFrequency;
+pub use processing_job_kind::ProcessingJobKind;
+pub use report_reason::ReportReason;
+pub use report_status::ReportStatus;
+pub use taxonomy_kind::TaxonomyKind;
+pub use user_role::UserRole;
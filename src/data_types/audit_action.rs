@@ -0,0 +1,34 @@
+use diesel_derive_enum::DbEnum;
+use rocket_okapi::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(
+  DbEnum, Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize, JsonSchema, FromFormField,
+)]
+#[ExistingTypePath = "crate::schema::sql_types::AuditAction"]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+  Login,
+  LoginFailed,
+  PasswordResetRequested,
+  PasswordReset,
+  RecordingDeleted,
+  CommentDeleted,
+  MediaApproved,
+  MediaRejected,
+  ReportDismissed,
+  ReportActioned,
+  UserSuspended,
+  UserUnsuspended,
+  UserShadowBanned,
+  UserUnshadowBanned,
+  UserRoleChanged,
+  RecordingRestored,
+  CommentRestored,
+  GameCreated,
+  GameUpdated,
+  SkillLevelCreated,
+  SkillLevelUpdated,
+  TaxonomyEntryCreated,
+  TaxonomyEntryUpdated,
+}
@@ -0,0 +1,16 @@
+use diesel_derive_enum::DbEnum;
+use rocket_okapi::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(
+  DbEnum, Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize, JsonSchema, FromFormField,
+)]
+#[ExistingTypePath = "crate::schema::sql_types::AuditTargetType"]
+#[serde(rename_all = "snake_case")]
+pub enum AuditTargetType {
+  User,
+  Recording,
+  Comment,
+  Avatar,
+  Report,
+}
@@ -1,11 +1,127 @@
 use diesel_derive_enum::DbEnum;
 use rocket_okapi::JsonSchema;
 use serde::Serialize;
+use thiserror::Error;
 
 #[derive(DbEnum, Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, JsonSchema)]
 #[ExistingTypePath = "crate::schema::sql_types::MediaState"]
 pub enum MediaState {
   Created,
   Uploaded,
+  Moderating,
+  Flagged,
+  Transcoding,
   Processed,
+  Rejected,
+  Failed,
+}
+
+#[derive(Error, Debug)]
+#[error("invalid media state transition from {from:?} to {to:?}")]
+pub struct MediaStateTransitionError {
+  pub from: MediaState,
+  pub to: MediaState,
+}
+
+impl MediaState {
+  pub fn can_transition_to(&self, to: MediaState) -> bool {
+    use MediaState::*;
+
+    matches!(
+      (self, to),
+      (Created, Uploaded)
+        | (Created, Failed)
+        | (Uploaded, Moderating)
+        | (Uploaded, Rejected)
+        | (Uploaded, Failed)
+        | (Moderating, Transcoding)
+        | (Moderating, Rejected)
+        | (Moderating, Flagged)
+        | (Moderating, Failed)
+        | (Flagged, Transcoding)
+        | (Flagged, Rejected)
+        | (Flagged, Failed)
+        | (Transcoding, Processed)
+        | (Transcoding, Failed)
+    )
+  }
+
+  pub fn transition_to(&self, to: MediaState) -> Result<MediaState, MediaStateTransitionError> {
+    if !self.can_transition_to(to) {
+      return Err(MediaStateTransitionError { from: *self, to });
+    }
+
+    Ok(to)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::MediaState::*;
+  use super::*;
+
+  const ALL: [MediaState; 8] = [
+    Created,
+    Uploaded,
+    Moderating,
+    Flagged,
+    Transcoding,
+    Processed,
+    Rejected,
+    Failed,
+  ];
+
+  #[test]
+  fn allows_the_processing_pipeline() {
+    for (from, to) in [
+      (Created, Uploaded),
+      (Uploaded, Moderating),
+      (Moderating, Transcoding),
+      (Moderating, Flagged),
+      (Flagged, Transcoding),
+      (Transcoding, Processed),
+    ] {
+      assert_eq!(from.transition_to(to).unwrap(), to);
+    }
+  }
+
+  #[test]
+  fn allows_failing_until_processed() {
+    for from in [Created, Uploaded, Moderating, Flagged, Transcoding] {
+      assert!(from.can_transition_to(Failed), "{:?}", from);
+    }
+  }
+
+  #[test]
+  fn forbids_skipping_or_going_back() {
+    for (from, to) in [
+      (Created, Transcoding),
+      (Created, Processed),
+      (Uploaded, Transcoding),
+      (Moderating, Uploaded),
+      (Flagged, Moderating),
+      (Transcoding, Moderating),
+      (Transcoding, Rejected),
+    ] {
+      let error = from.transition_to(to).unwrap_err();
+
+      assert_eq!((error.from, error.to), (from, to));
+    }
+  }
+
+  #[test]
+  fn forbids_leaving_terminal_states() {
+    for from in [Processed, Rejected, Failed] {
+      for to in ALL {
+        assert!(!from.can_transition_to(to), "{:?} -> {:?}", from, to);
+      }
+    }
+  }
+
+  #[test]
+  fn forbids_self_transitions() {
+    for state in ALL {
+      assert!(!state.can_transition_to(state), "{:?}", state);
+    }
+  }
 }
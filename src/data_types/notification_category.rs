@@ -0,0 +1,38 @@
+use super::NotificationFrequency;
+use diesel_derive_enum::DbEnum;
+use rocket_okapi::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(DbEnum, Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[ExistingTypePath = "crate::schema::sql_types::NotificationCategory"]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationCategory {
+  /// Comments left on the user's own recordings.
+  CommentsOnRecordings,
+  /// Comments left after the user's on recordings they've commented on.
+  CommentReplies,
+  Digest,
+  ProcessingComplete,
+  ProductNews,
+}
+
+impl NotificationCategory {
+  pub const ALL: [NotificationCategory; 5] = [
+    NotificationCategory::CommentsOnRecordings,
+    NotificationCategory::CommentReplies,
+    NotificationCategory::Digest,
+    NotificationCategory::ProcessingComplete,
+    NotificationCategory::ProductNews,
+  ];
+
+  /// Frequency used until the user picks one.
+  pub fn default_frequency(&self) -> NotificationFrequency {
+    match self {
+      NotificationCategory::CommentsOnRecordings => NotificationFrequency::Immediate,
+      NotificationCategory::CommentReplies => NotificationFrequency::Immediate,
+      NotificationCategory::Digest => NotificationFrequency::Daily,
+      NotificationCategory::ProcessingComplete => NotificationFrequency::Immediate,
+      NotificationCategory::ProductNews => NotificationFrequency::Weekly,
+    }
+  }
+}
@@ -0,0 +1,27 @@
+use chrono::Duration;
+use diesel_derive_enum::DbEnum;
+use rocket_okapi::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(DbEnum, Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[ExistingTypePath = "crate::schema::sql_types::NotificationFrequency"]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationFrequency {
+  Immediate,
+  Hourly,
+  Daily,
+  Weekly,
+  Off,
+}
+
+impl NotificationFrequency {
+  /// Minimum time between two emails, or `None` if there's no minimum.
+  pub fn interval(&self) -> Option<Duration> {
+    match self {
+      NotificationFrequency::Immediate | NotificationFrequency::Off => None,
+      NotificationFrequency::Hourly => Some(Duration::hours(1)),
+      NotificationFrequency::Daily => Some(Duration::days(1)),
+      NotificationFrequency::Weekly => Some(Duration::weeks(1)),
+    }
+  }
+}
@@ -0,0 +1,10 @@
+use diesel_derive_enum::DbEnum;
+use rocket_okapi::JsonSchema;
+use serde::Serialize;
+
+#[derive(DbEnum, Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, JsonSchema)]
+#[ExistingTypePath = "crate::schema::sql_types::ProcessingJobKind"]
+pub enum ProcessingJobKind {
+  Moderation,
+  Transcoding,
+}
@@ -0,0 +1,37 @@
+use diesel_derive_enum::DbEnum;
+use rocket_okapi::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Ordered from least to most privileged.
+#[derive(
+  DbEnum,
+  Debug,
+  PartialEq,
+  Eq,
+  PartialOrd,
+  Ord,
+  Hash,
+  Clone,
+  Copy,
+  Serialize,
+  Deserialize,
+  JsonSchema,
+)]
+#[ExistingTypePath = "crate::schema::sql_types::UserRole"]
+#[serde(rename_all = "snake_case")]
+pub enum UserRole {
+  User,
+  Moderator,
+  Admin,
+}
+
+impl UserRole {
+  pub fn can_moderate(&self) -> bool {
+    matches!(self, UserRole::Moderator | UserRole::Admin)
+  }
+
+  /// Staff can only act against users with a lower role than their own.
+  pub fn outranks(&self, other: UserRole) -> bool {
+    *self > other
+  }
+}
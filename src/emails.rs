@@ -1,17 +1,30 @@
 use crate::config::Config;
+use crate::data_types::NotificationCategory;
+use crate::notifications::unsubscribe_token;
 use crate::{aws::ConfigCredentialsProvider, PROFILE};
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use handlebars::Handlebars;
 use hyper_tls::HttpsConnector;
-use rusoto_core::{HttpClient, Region, RusotoError};
+use rusoto_core::{HttpClient, Region};
 use rusoto_sesv2::{
-  BulkEmailContent, BulkEmailEntry, Destination, ReplacementEmailContent, ReplacementTemplate,
-  SendBulkEmailError, SendBulkEmailRequest, SesV2, SesV2Client, Template,
+  BulkEmailContent, BulkEmailEntry, Destination, EmailContent, EmailTemplateContent,
+  GetEmailTemplateRequest, RawMessage, ReplacementEmailContent, ReplacementTemplate,
+  SendBulkEmailRequest, SendEmailRequest, SesV2, SesV2Client, Template,
 };
 use serde::Serialize;
+use serde_json::json;
+use uuid::Uuid;
+
+const FROM_EMAIL_ADDRESS: &str = "Ranklab <noreply@ranklab.gg>";
 
 #[derive(Serialize)]
 pub struct Recipient {
   email: String,
   template_data: serde_json::Value,
+  #[serde(skip)]
+  list_unsubscribe_url: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -28,8 +41,27 @@ impl Recipient {
     Recipient {
       email,
       template_data,
+      list_unsubscribe_url: None,
     }
   }
+
+  /// Adds unsubscribe links for a notification category. `unsubscribe_url`
+  /// in the template data opens a confirmation page, while the
+  /// `List-Unsubscribe` header accepts the RFC 8058 one-click POST.
+  pub fn unsubscribable(
+    mut self,
+    config: &Config,
+    user_id: Uuid,
+    category: NotificationCategory,
+  ) -> Self {
+    let token = unsubscribe_token(config, user_id, Some(category));
+
+    self.template_data["unsubscribe_url"] =
+      json!(format!("{}/unsubscribe?token={}", config.web_host, token));
+    self.list_unsubscribe_url = Some(format!("{}/unsubscribe/{}", config.host, token));
+
+    self
+  }
 }
 
 impl Email {
@@ -53,13 +85,56 @@ impl Email {
     }
   }
 
-  pub async fn deliver(self) -> Result<(), RusotoError<SendBulkEmailError>> {
+  pub async fn deliver(self) -> Result<()> {
     if *PROFILE == "test" || self.recipients.is_empty() {
       return Ok(());
     }
 
+    // SES templates can't set headers, so emails that need `List-Unsubscribe`
+    // are rendered here and sent as raw messages.
+    let (unsubscribable, recipients): (Vec<_>, Vec<_>) = self
+      .recipients
+      .into_iter()
+      .partition(|recipient| recipient.list_unsubscribe_url.is_some());
+
+    if !unsubscribable.is_empty() {
+      let template = self
+        .client
+        .get_email_template(GetEmailTemplateRequest {
+          template_name: self.template_name.clone(),
+        })
+        .await?
+        .template_content;
+
+      for recipient in unsubscribable {
+        let message = render_message(&template, &self.template_data, &recipient)?;
+
+        self
+          .client
+          .send_email(SendEmailRequest {
+            from_email_address: Some(FROM_EMAIL_ADDRESS.to_owned()),
+            destination: Some(Destination {
+              to_addresses: Some(vec![recipient.email.clone()]),
+              ..Default::default()
+            }),
+            content: EmailContent {
+              raw: Some(RawMessage {
+                data: message.into_bytes().into(),
+              }),
+              ..Default::default()
+            },
+            ..Default::default()
+          })
+          .await?;
+      }
+    }
+
+    if recipients.is_empty() {
+      return Ok(());
+    }
+
     let email_request = SendBulkEmailRequest {
-      from_email_address: Some("Ranklab <noreply@ranklab.gg>".to_string()),
+      from_email_address: Some(FROM_EMAIL_ADDRESS.to_owned()),
       default_content: BulkEmailContent {
         template: Some(Template {
           template_name: Some(self.template_name),
@@ -67,8 +142,7 @@ impl Email {
           ..Default::default()
         }),
       },
-      bulk_email_entries: self
-        .recipients
+      bulk_email_entries: recipients
         .iter()
         .map(|recipient| BulkEmailEntry {
           destination: Destination {
@@ -91,3 +165,142 @@ impl Email {
     Ok(())
   }
 }
+
+/// Renders an SES template the way a bulk send would, with the recipient's
+/// data layered over the shared data, into a MIME message carrying the
+/// one-click unsubscribe headers.
+fn render_message(
+  template: &EmailTemplateContent,
+  template_data: &serde_json::Value,
+  recipient: &Recipient,
+) -> Result<String> {
+  let mut data = template_data.clone();
+
+  if let (Some(data), Some(replacements)) =
+    (data.as_object_mut(), recipient.template_data.as_object())
+  {
+    data.extend(replacements.clone());
+  }
+
+  let mut plain = Handlebars::new();
+  plain.register_escape_fn(handlebars::no_escape);
+  let html = Handlebars::new();
+
+  let render = |handlebars: &Handlebars, part: &Option<String>| {
+    part
+      .as_ref()
+      .map(|part| handlebars.render_template(part, &data))
+      .transpose()
+  };
+
+  let subject = render(&plain, &template.subject)?.unwrap_or_default();
+  let text = render(&plain, &template.text)?;
+  let html = render(&html, &template.html)?;
+
+  let list_unsubscribe_url = recipient
+    .list_unsubscribe_url
+    .as_ref()
+    .ok_or_else(|| anyhow!("Recipient has no unsubscribe URL"))?;
+
+  let boundary = format!("ranklab-{}", Uuid::new_v4().simple());
+
+  let mut message = vec![
+    format!("From: {}", FROM_EMAIL_ADDRESS),
+    format!("To: {}", recipient.email),
+    format!("Subject: =?UTF-8?B?{}?=", BASE64.encode(subject)),
+    "MIME-Version: 1.0".to_owned(),
+    format!("List-Unsubscribe: <{}>", list_unsubscribe_url),
+    "List-Unsubscribe-Post: List-Unsubscribe=One-Click".to_owned(),
+    format!(
+      "Content-Type: multipart/alternative; boundary=\"{}\"",
+      boundary
+    ),
+    "".to_owned(),
+  ];
+
+  for (content_type, body) in [("text/plain", text), ("text/html", html)] {
+    let body = match body {
+      Some(body) => BASE64.encode(body),
+      None => continue,
+    };
+
+    message.push(format!("--{}", boundary));
+    message.push(format!("Content-Type: {}; charset=UTF-8", content_type));
+    message.push("Content-Transfer-Encoding: base64".to_owned());
+    message.push("".to_owned());
+
+    // Base64 bodies have to be wrapped at 76 characters.
+    message.extend(
+      body
+        .as_bytes()
+        .chunks(76)
+        .map(|line| String::from_utf8_lossy(line).into_owned()),
+    );
+  }
+
+  message.push(format!("--{}--", boundary));
+  message.push("".to_owned());
+
+  Ok(message.join("\r\n"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn decode_part(message: &str, content_type: &str) -> String {
+    let part = message
+      .split(&format!(
+        "Content-Type: {}; charset=UTF-8\r\n",
+        content_type
+      ))
+      .nth(1)
+      .unwrap();
+
+    let body = part
+      .split("\r\n\r\n")
+      .nth(1)
+      .unwrap()
+      .split("\r\n--")
+      .next()
+      .unwrap()
+      .replace("\r\n", "");
+
+    String::from_utf8(BASE64.decode(body).unwrap()).unwrap()
+  }
+
+  #[test]
+  fn renders_unsubscribe_headers_and_template_parts() {
+    let template = EmailTemplateContent {
+      subject: Some("{{subject}}".to_owned()),
+      text: Some("Hi {{name}}, {{body}}".to_owned()),
+      html: Some("<p>Hi {{name}}, {{body}}</p>".to_owned()),
+    };
+
+    let recipient = Recipient {
+      email: "player@example.com".to_owned(),
+      template_data: json!({ "name": "Ana & Bo" }),
+      list_unsubscribe_url: Some("https://api.example.com/unsubscribe/token".to_owned()),
+    };
+
+    let message = render_message(
+      &template,
+      &json!({ "subject": "Your VOD is ready", "body": "enjoy" }),
+      &recipient,
+    )
+    .unwrap();
+
+    assert!(message.contains("To: player@example.com\r\n"));
+    assert!(message.contains("List-Unsubscribe: <https://api.example.com/unsubscribe/token>\r\n"));
+    assert!(message.contains("List-Unsubscribe-Post: List-Unsubscribe=One-Click\r\n"));
+    assert!(message.contains(&format!(
+      "Subject: =?UTF-8?B?{}?=\r\n",
+      BASE64.encode("Your VOD is ready")
+    )));
+    assert_eq!(decode_part(&message, "text/plain"), "Hi Ana & Bo, enjoy");
+    assert_eq!(
+      decode_part(&message, "text/html"),
+      "<p>Hi Ana &amp; Bo, enjoy</p>"
+    );
+  }
+}
@@ -1,39 +1,45 @@
+use crate::aws::s3::delete_recording_objects;
+use crate::aws::ConfigCredentialsProvider;
 use crate::config::Config;
+use crate::data_types::{NotificationCategory, NotificationFrequency};
 use crate::emails::{Email, Recipient};
 use crate::games;
+use crate::garbage_collection;
 use crate::guards::DbConn;
-use crate::models::{Comment, Digest, DigestChangeset, Following, Recording, User};
+use crate::models::{
+  Comment, Digest, DigestChangeset, Following, NotificationPreference, Recording, User,
+};
+use crate::notifications;
 use crate::schema::{comments, digests};
-use chrono::Duration;
+use chrono::{Duration, NaiveDateTime, Utc};
 use clokwerk::{Scheduler, TimeUnits};
-use diesel::dsl::now;
 use diesel::prelude::*;
+use hyper_tls::HttpsConnector;
 use itertools::Itertools;
 use pluralizer::pluralize;
 use rocket::fairing::{Fairing, Info, Kind};
 use rocket::{tokio, Orbit, Rocket};
+use rusoto_core::{HttpClient, Region};
+use rusoto_s3::S3Client;
 use serde::Serialize;
 use serde_json::json;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::sync::Arc;
 use tokio::time::sleep;
+use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct CronFairing;
 
 async fn process_comments(db_conn: &DbConn, config: &Config) -> Result<(), anyhow::Error> {
   let comments_query = Comment::filter_unnotified();
+  let now = Utc::now().naive_utc();
 
   let joined_comments = db_conn
     .run(move |conn| comments_query.load::<(Comment, User, Recording)>(conn))
     .await?;
 
-  let comments = joined_comments
-    .clone()
-    .into_iter()
-    .map(|(comment, _, _)| comment)
-    .collect::<Vec<_>>();
-
   if joined_comments.is_empty() {
     return Ok(());
   }
@@ -45,9 +51,37 @@ async fn process_comments(db_conn: &DbConn, config: &Config) -> Result<(), anyho
     .unique_by(|user| user.id)
     .collect::<Vec<_>>();
 
+  let settings = notifications::settings(
+    db_conn,
+    NotificationCategory::CommentsOnRecordings,
+    users.iter().map(|user| user.id).collect(),
+  )
+  .await?;
+
   let mut recipients: Vec<Recipient> = vec![];
+  let mut notified_user_ids = HashSet::new();
+  let mut comment_ids = vec![];
 
   for user in &users {
+    let setting = settings[&user.id];
+
+    // Comments for users who opted out are marked as notified so they don't
+    // arrive all at once if they opt back in. Those not yet due stay queued.
+    if setting.frequency != NotificationFrequency::Off && !setting.is_due(now) {
+      continue;
+    }
+
+    comment_ids.extend(
+      joined_comments
+        .iter()
+        .filter(|(_, _, recording)| recording.user_id == user.id)
+        .map(|(comment, _, _)| comment.id),
+    );
+
+    if setting.frequency == NotificationFrequency::Off {
+      continue;
+    }
+
     let recordings = joined_comments
       .clone()
       .into_iter()
@@ -57,10 +91,9 @@ async fn process_comments(db_conn: &DbConn, config: &Config) -> Result<(), anyho
       .collect::<Vec<_>>();
 
     for recording in &recordings {
-      let comments = comments
-        .clone()
-        .into_iter()
-        .filter(|comment| comment.recording_id == recording.id)
+      let comments = joined_comments
+        .iter()
+        .filter(|(comment, _, _)| comment.recording_id == recording.id)
         .collect::<Vec<_>>();
 
       if comments.is_empty() {
@@ -81,9 +114,11 @@ async fn process_comments(db_conn: &DbConn, config: &Config) -> Result<(), anyho
           }),
           "cta_url" : format!("{}/recordings/{}", config.web_host, recording_id),
         }),
-      );
+      )
+      .unsubscribable(config, user.id, NotificationCategory::CommentsOnRecordings);
 
       recipients.push(recipient);
+      notified_user_ids.insert(user.id);
     }
   }
 
@@ -102,23 +137,265 @@ async fn process_comments(db_conn: &DbConn, config: &Config) -> Result<(), anyho
     .await
     .map_err(|e| anyhow::anyhow!("Failed to send comments notification email: {}", e))?;
 
-  let comment_ids = comments
-    .clone()
-    .into_iter()
-    .map(|comment| comment.id)
-    .collect::<Vec<_>>();
+  notifications::mark_sent(
+    db_conn,
+    NotificationCategory::CommentsOnRecordings,
+    notified_user_ids,
+    now,
+  )
+  .await?;
 
   db_conn
     .run(move |conn| {
       diesel::update(comments::table.filter(comments::id.eq_any(comment_ids)))
         .set(comments::notified_at.eq(now))
-        .get_result::<Comment>(conn)
+        .execute(conn)
     })
     .await?;
 
   Ok(())
 }
 
+async fn process_replies(db_conn: &DbConn, config: &Config) -> Result<(), anyhow::Error> {
+  let now = Utc::now().naive_utc();
+  // No setting looks further back than a week.
+  let since = now - Duration::weeks(1);
+
+  let replies = db_conn
+    .run(move |conn| Comment::filter_posted_between(since, now).load::<(Comment, Recording)>(conn))
+    .await?;
+
+  if replies.is_empty() {
+    return Ok(());
+  }
+
+  let recordings = replies
+    .iter()
+    .map(|(_, recording)| (recording.id, recording.clone()))
+    .collect::<HashMap<_, _>>();
+
+  let recording_ids = recordings.keys().cloned().collect::<Vec<_>>();
+
+  let thread_comments = db_conn
+    .run(move |conn| Comment::filter_by_recording_ids(recording_ids).load::<Comment>(conn))
+    .await?;
+
+  // The first comment of each user on recordings they don't own. Owners are
+  // notified of every comment through their own category.
+  let mut first_comments: HashMap<(Uuid, Uuid), NaiveDateTime> = HashMap::new();
+
+  for comment in &thread_comments {
+    if recordings[&comment.recording_id].user_id == comment.user_id {
+      continue;
+    }
+
+    first_comments
+      .entry((comment.user_id, comment.recording_id))
+      .and_modify(|created_at| *created_at = (*created_at).min(comment.created_at))
+      .or_insert(comment.created_at);
+  }
+
+  let user_ids = first_comments
+    .keys()
+    .map(|(user_id, _)| *user_id)
+    .unique()
+    .collect::<Vec<_>>();
+
+  let settings = notifications::settings(
+    db_conn,
+    NotificationCategory::CommentReplies,
+    user_ids.clone(),
+  )
+  .await?;
+
+  let users = db_conn
+    .run(move |conn| User::filter_by_ids(user_ids).load::<User>(conn))
+    .await?;
+
+  let mut recipients: Vec<Recipient> = vec![];
+  let mut notified_user_ids = HashSet::new();
+
+  for user in &users {
+    let setting = settings[&user.id];
+
+    if !setting.is_due(now) {
+      continue;
+    }
+
+    let window_start = setting.window_start(now);
+
+    for recording in recordings.values() {
+      let first_comment_at = match first_comments.get(&(user.id, recording.id)) {
+        Some(created_at) => *created_at,
+        None => continue,
+      };
+
+      let count = replies
+        .iter()
+        .filter(|(comment, _)| {
+          comment.recording_id == recording.id
+            && comment.user_id != user.id
+            && comment.created_at > first_comment_at.max(window_start)
+        })
+        .count();
+
+      if count == 0 {
+        continue;
+      }
+
+      let recipient = Recipient::new(
+        user.email.clone(),
+        json!({
+          "name": user.name,
+          "title": format!("There {} {} new {} after yours on the VOD \"{}\"", match count {
+            1 => "is",
+            _ => "are",
+          }, count, pluralize("comments", count.try_into()?, false), recording.title),
+          "body": format!("You can follow the link below to view {}.", match count {
+            1 => "it",
+            _ => "them",
+          }),
+          "cta_url" : format!("{}/recordings/{}", config.web_host, recording.id),
+        }),
+      )
+      .unsubscribable(config, user.id, NotificationCategory::CommentReplies);
+
+      recipients.push(recipient);
+      notified_user_ids.insert(user.id);
+    }
+  }
+
+  let email = Email::new(
+    config,
+    "notification".to_owned(),
+    json!({
+      "subject": "There are new comments on a VOD you commented on!",
+      "cta" : "View comments",
+    }),
+    recipients,
+  );
+
+  email
+    .deliver()
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to send comment replies email: {}", e))?;
+
+  notifications::mark_sent(
+    db_conn,
+    NotificationCategory::CommentReplies,
+    notified_user_ids,
+    now,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Sends the processed VODs of users who asked for them to be batched. Those
+/// who didn't are emailed as soon as each VOD is processed.
+async fn process_processed_recordings(
+  db_conn: &DbConn,
+  config: &Config,
+) -> Result<(), anyhow::Error> {
+  let now = Utc::now().naive_utc();
+
+  let preferences = db_conn
+    .run(move |conn| {
+      NotificationPreference::filter_batched(NotificationCategory::ProcessingComplete)
+        .load::<NotificationPreference>(conn)
+    })
+    .await?;
+
+  let settings = preferences
+    .iter()
+    .map(|preference| (preference.user_id, notifications::Setting::from(preference)))
+    .filter(|(_, setting)| setting.is_due(now))
+    .collect::<HashMap<_, _>>();
+
+  let since = match settings
+    .values()
+    .map(|setting| setting.window_start(now))
+    .min()
+  {
+    Some(since) => since,
+    None => return Ok(()),
+  };
+
+  let user_ids = settings.keys().cloned().collect::<Vec<_>>();
+  let recordings_user_ids = user_ids.clone();
+
+  let users = db_conn
+    .run(move |conn| User::filter_by_ids(user_ids).load::<User>(conn))
+    .await?;
+
+  let recordings = db_conn
+    .run(move |conn| {
+      Recording::filter_processed_since(recordings_user_ids, since).load::<Recording>(conn)
+    })
+    .await?;
+
+  let mut recipients: Vec<Recipient> = vec![];
+
+  for user in &users {
+    let window_start = settings[&user.id].window_start(now);
+
+    let count = recordings
+      .iter()
+      .filter(|recording| recording.user_id == user.id && recording.state_changed_at > window_start)
+      .count();
+
+    if count == 0 {
+      continue;
+    }
+
+    let recipient = Recipient::new(
+      user.email.clone(),
+      json!({
+        "name": user.name,
+        "title": format!("{} been processed and {} now ready to be reviewed.", match count {
+          1 => "Your VOD has".to_owned(),
+          _ => format!("{} of your VODs have", count),
+        }, match count {
+          1 => "is",
+          _ => "are",
+        }),
+      }),
+    )
+    .unsubscribable(config, user.id, NotificationCategory::ProcessingComplete);
+
+    recipients.push(recipient);
+  }
+
+  let email = Email::new(
+    config,
+    "notification".to_owned(),
+    json!({
+      "subject": "Your VODs are ready!",
+      "body": "You can follow the link below to view them.",
+      "cta" : "View VODs",
+      "cta_url" : format!("{}/recordings", config.web_host),
+    }),
+    recipients,
+  );
+
+  email
+    .deliver()
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to send processed VODs email: {}", e))?;
+
+  // Every due user is marked, even without new VODs, so batches keep to
+  // their schedule.
+  notifications::mark_sent(
+    db_conn,
+    NotificationCategory::ProcessingComplete,
+    settings.keys().cloned().collect(),
+    now,
+  )
+  .await?;
+
+  Ok(())
+}
+
 #[derive(Serialize)]
 struct DigestEmailGame {
   name: String,
@@ -127,42 +404,82 @@ struct DigestEmailGame {
   url: String,
 }
 
-async fn process_digests(db_conn: &DbConn, config: &Config) -> Result<(), anyhow::Error> {
-  let last_digest = db_conn
-    .run(move |conn| Digest::last().first::<Digest>(conn).optional())
-    .await?;
+/// Links to the game's directory with the following's filters applied.
+fn directory_url(config: &Config, following: &Following) -> String {
+  let filters = [
+    ("map", following.map.clone()),
+    ("role", following.role.clone()),
+    ("hero", following.hero.clone()),
+    (
+      "min_skill_level",
+      following.min_skill_level.map(|value| value.to_string()),
+    ),
+    (
+      "max_skill_level",
+      following.max_skill_level.map(|value| value.to_string()),
+    ),
+  ];
+
+  let params = filters
+    .iter()
+    .filter_map(|(name, value)| value.as_ref().map(|value| format!("{}={}", name, value)))
+    .collect::<Vec<_>>();
 
-  let recordings = db_conn
-    .run(move |conn| Recording::filter_for_digest(last_digest).load::<Recording>(conn))
-    .await?;
+  let url = format!("{}/directory/{}", config.web_host, following.game_id);
 
-  if recordings.is_empty() {
-    db_conn
-      .run(move |conn| {
-        diesel::insert_into(digests::table)
-          .values(DigestChangeset::default().metadata(json!({})))
-          .get_result::<Digest>(conn)
-      })
-      .await?;
-
-    return Ok(());
+  if params.is_empty() {
+    url
+  } else {
+    format!("{}?{}", url, params.join("&"))
   }
+}
+
+async fn process_digests(db_conn: &DbConn, config: &Config) -> Result<(), anyhow::Error> {
+  let now = Utc::now().naive_utc();
 
   let users = db_conn
     .run(move |conn| User::filter_for_digest().load::<User>(conn))
     .await?;
 
+  let settings = notifications::settings(
+    db_conn,
+    NotificationCategory::Digest,
+    users.iter().map(|user| user.id).collect(),
+  )
+  .await?;
+
+  let users = users
+    .into_iter()
+    .filter(|user| settings[&user.id].is_due(now))
+    .collect::<Vec<_>>();
+
+  let since = match users
+    .iter()
+    .map(|user| settings[&user.id].window_start(now))
+    .min()
+  {
+    Some(since) => since,
+    None => return Ok(()),
+  };
+
+  let recordings = db_conn
+    .run(move |conn| Recording::filter_for_digest(since).load::<Recording>(conn))
+    .await?;
+
   let followings_users = users.clone();
 
   let followings = db_conn
     .run(move |conn| Following::filter_for_digest(followings_users).load::<Following>(conn))
     .await?;
 
+  let catalogue = games::all(db_conn).await?;
+
   let mut recipients: Vec<Recipient> = vec![];
 
   for user in &users {
     let email = user.email.clone();
     let name = user.name.clone();
+    let window_start = settings[&user.id].window_start(now);
 
     let followings = followings
       .clone()
@@ -173,14 +490,18 @@ async fn process_digests(db_conn: &DbConn, config: &Config) -> Result<(), anyhow
     let games = followings
       .clone()
       .into_iter()
-      .map(|following| {
-        let game = games::find(&following.game_id).unwrap();
+      .filter_map(|following| {
+        let game = catalogue.iter().find(|game| game.id == following.game_id)?;
 
+        Some((following, game))
+      })
+      .map(|(following, game)| {
         let count = recordings
-          .clone()
-          .into_iter()
+          .iter()
           .filter(|recording| {
-            recording.game_id == game.id.to_string() && recording.user_id != user.id
+            following.matches(game, recording)
+              && recording.user_id != user.id
+              && recording.updated_at > window_start
           })
           .count() as isize;
 
@@ -194,7 +515,7 @@ async fn process_digests(db_conn: &DbConn, config: &Config) -> Result<(), anyhow
             }
           ),
           count,
-          url: format!("{}/directory/{}", config.web_host, game.id.to_string()),
+          url: directory_url(config, &following),
         }
       })
       .filter(|game| game.count > 0)
@@ -230,7 +551,8 @@ async fn process_digests(db_conn: &DbConn, config: &Config) -> Result<(), anyhow
         }, vods_label),
         "vods_label": vods_label,
       }),
-    );
+    )
+    .unsubscribable(config, user.id, NotificationCategory::Digest);
 
     recipients.push(recipient);
   }
@@ -251,6 +573,8 @@ async fn process_digests(db_conn: &DbConn, config: &Config) -> Result<(), anyhow
     .await
     .map_err(|e| anyhow::anyhow!("Failed to send digest email: {}", e))?;
 
+  let digest_user_ids = users.iter().map(|user| user.id).collect();
+
   db_conn
     .run(move |conn| {
       diesel::insert_into(digests::table)
@@ -259,6 +583,64 @@ async fn process_digests(db_conn: &DbConn, config: &Config) -> Result<(), anyhow
     })
     .await?;
 
+  // Every due user is marked, even without new VODs, so digests keep to
+  // their schedule.
+  notifications::mark_sent(db_conn, NotificationCategory::Digest, digest_user_ids, now).await?;
+
+  Ok(())
+}
+
+fn s3_client(config: &Config) -> S3Client {
+  S3Client::new_with(
+    HttpClient::from_connector(HttpsConnector::new()),
+    ConfigCredentialsProvider::new(config.clone()),
+    Region::EuWest2,
+  )
+}
+
+async fn collect_garbage(db_conn: &DbConn, config: &Config) -> Result<(), anyhow::Error> {
+  let report = garbage_collection::collect(
+    db_conn,
+    config,
+    &s3_client(config),
+    config.garbage_collection.dry_run,
+  )
+  .await?;
+
+  info!(
+    "[cron] garbage collection{}: {} expired recordings, {} expired avatars, {} missing recordings, {} missing avatars, {} orphaned objects",
+    if report.dry_run { " (dry run)" } else { "" },
+    report.expired_recording_ids.len(),
+    report.expired_avatar_ids.len(),
+    report.missing_recording_ids.len(),
+    report.missing_avatar_ids.len(),
+    report.orphaned_keys.len(),
+  );
+
+  Ok(())
+}
+
+async fn purge_deleted(db_conn: &DbConn, config: &Config) -> Result<(), anyhow::Error> {
+  let cutoff = Utc::now().naive_utc() - Duration::days(config.deletion_retention_days);
+
+  let recordings = db_conn
+    .run(move |conn| Recording::filter_deleted_before(cutoff).load::<Recording>(conn))
+    .await?;
+
+  let client = s3_client(config);
+
+  for recording in recordings {
+    delete_recording_objects(&client, &config.uploads_bucket, &recording).await?;
+
+    db_conn
+      .run(move |conn| diesel::delete(&recording).execute(conn))
+      .await?;
+  }
+
+  db_conn
+    .run(move |conn| diesel::delete(Comment::filter_deleted_before(cutoff)).execute(conn))
+    .await?;
+
   Ok(())
 }
 
@@ -291,7 +673,41 @@ impl CronFairing {
         }
       });
 
-      scheduler.every(1.day()).run({
+      scheduler.every(30.minutes()).run({
+        let db_conn = Arc::clone(&db_conn);
+        let config = Arc::clone(&config);
+
+        move || {
+          let db_conn = Arc::clone(&db_conn);
+          let config = Arc::clone(&config);
+
+          tokio::spawn(async move {
+            if let Err(e) = process_replies(&db_conn, &config).await {
+              error!("[cron] {:?}", e);
+              sentry::capture_error(e.root_cause());
+            }
+          });
+        }
+      });
+
+      scheduler.every(1.hour()).run({
+        let db_conn = Arc::clone(&db_conn);
+        let config = Arc::clone(&config);
+
+        move || {
+          let db_conn = Arc::clone(&db_conn);
+          let config = Arc::clone(&config);
+
+          tokio::spawn(async move {
+            if let Err(e) = process_processed_recordings(&db_conn, &config).await {
+              error!("[cron] {:?}", e);
+              sentry::capture_error(e.root_cause());
+            }
+          });
+        }
+      });
+
+      scheduler.every(1.hour()).run({
         let db_conn = Arc::clone(&db_conn);
         let config = Arc::clone(&config);
 
@@ -308,6 +724,40 @@ impl CronFairing {
         }
       });
 
+      scheduler.every(1.hour()).run({
+        let db_conn = Arc::clone(&db_conn);
+        let config = Arc::clone(&config);
+
+        move || {
+          let db_conn = Arc::clone(&db_conn);
+          let config = Arc::clone(&config);
+
+          tokio::spawn(async move {
+            if let Err(e) = purge_deleted(&db_conn, &config).await {
+              error!("[cron] {:?}", e);
+              sentry::capture_error(e.root_cause());
+            }
+          });
+        }
+      });
+
+      scheduler.every(1.day()).run({
+        let db_conn = Arc::clone(&db_conn);
+        let config = Arc::clone(&config);
+
+        move || {
+          let db_conn = Arc::clone(&db_conn);
+          let config = Arc::clone(&config);
+
+          tokio::spawn(async move {
+            if let Err(e) = collect_garbage(&db_conn, &config).await {
+              error!("[cron] {:?}", e);
+              sentry::capture_error(e.root_cause());
+            }
+          });
+        }
+      });
+
       loop {
         scheduler.run_pending();
         sleep(Duration::seconds(1).to_std().unwrap()).await;
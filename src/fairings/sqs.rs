@@ -1,7 +1,9 @@
 use crate::aws::ConfigCredentialsProvider;
 use crate::config::Config;
 use crate::guards::DbConn;
-use crate::queue_handlers::{RekognitionHandler, UploadsHandler};
+use crate::moderation::ModerationProviderKind;
+use crate::queue_handlers::{MediaConvertHandler, RekognitionHandler, UploadsHandler};
+use crate::transcoding::TranscoderKind;
 use anyhow::{anyhow, Result};
 use hyper_tls::HttpsConnector;
 use rocket::fairing::{Fairing, Info, Kind};
@@ -32,7 +34,18 @@ impl SqsFairing {
 
   async fn init(&self, rocket: &Rocket<Orbit>) {
     self.start::<UploadsHandler>(rocket).await;
-    self.start::<RekognitionHandler>(rocket).await;
+
+    let config = rocket.state::<Config>().unwrap();
+
+    if config.moderation.provider == ModerationProviderKind::Rekognition {
+      self.start::<RekognitionHandler>(rocket).await;
+    }
+
+    if config.transcoder == TranscoderKind::MediaConvert
+      && config.media_convert_events_queue_url.is_some()
+    {
+      self.start::<MediaConvertHandler>(rocket).await;
+    }
   }
 
   async fn start<T: QueueHandler>(&self, rocket: &Rocket<Orbit>) {
@@ -1,48 +1,167 @@
-pub mod apex;
-pub mod cs2;
-pub mod dota2;
-pub mod lol;
-pub mod overwatch;
-pub mod valorant;
-use crate::models::Game;
+use crate::data_types::TaxonomyKind;
+use crate::guards::DbConn;
+use crate::models::{Game, ReviewCategory, SkillLevel, TaxonomyEntry};
+use diesel::prelude::*;
 use lazy_static::lazy_static;
-use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
-#[derive(
-  Debug, PartialEq, Deserialize, Serialize, Eq, Hash, Clone, JsonSchema, Copy, FromFormField,
-)]
-#[serde(rename_all = "snake_case")]
-pub enum GameId {
-  Overwatch,
-  Apex,
-  Cs2,
-  Dota2,
-  Lol,
-  Valorant,
+/// How long a loaded catalogue is served before it's read again, so changes
+/// made through another instance show up eventually.
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// A game together with its ranks and review rubric, as served from the cache.
+#[derive(Clone)]
+pub struct CatalogueGame {
+  pub id: String,
+  pub name: String,
+  pub transcoding_profile: String,
+  /// Ordered by position, including deprecated ranks.
+  pub skill_levels: Vec<SkillLevel>,
+  pub review_categories: Vec<ReviewCategory>,
+  /// Maps, roles and heroes, ordered by position within each kind.
+  pub taxonomy: Vec<TaxonomyEntry>,
 }
 
-impl ToString for GameId {
-  fn to_string(&self) -> String {
-    serde_plain::to_string(&self).unwrap()
+impl CatalogueGame {
+  pub fn skill_level(&self, value: i16) -> Option<&SkillLevel> {
+    self
+      .skill_levels
+      .iter()
+      .find(|skill_level| skill_level.value == value)
+  }
+
+  /// Whether new recordings can be created with the given skill level.
+  pub fn accepts_skill_level(&self, value: i16) -> bool {
+    self
+      .skill_level(value)
+      .map_or(false, |skill_level| !skill_level.is_deprecated())
+  }
+
+  /// Values of the ranks between two bounds, inclusive and by position,
+  /// including deprecated ones. An unset or unknown bound leaves that end of
+  /// the range open.
+  pub fn skill_levels_between(&self, min: Option<i16>, max: Option<i16>) -> Vec<i16> {
+    let position = |value: Option<i16>| {
+      value
+        .and_then(|value| self.skill_level(value))
+        .map(|skill_level| skill_level.position)
+    };
+
+    let min_position = position(min).unwrap_or(i16::MIN);
+    let max_position = position(max).unwrap_or(i16::MAX);
+
+    self
+      .skill_levels
+      .iter()
+      .filter(|skill_level| (min_position..=max_position).contains(&skill_level.position))
+      .map(|skill_level| skill_level.value)
+      .collect()
   }
+
+  pub fn taxonomy_entries(&self, kind: TaxonomyKind) -> impl Iterator<Item = &TaxonomyEntry> {
+    self.taxonomy.iter().filter(move |entry| entry.kind == kind)
+  }
+
+  pub fn taxonomy_entry(&self, kind: TaxonomyKind, key: &str) -> Option<&TaxonomyEntry> {
+    self.taxonomy_entries(kind).find(|entry| entry.key == key)
+  }
+
+  /// Whether new recordings can be tagged with the given entry. A missing key
+  /// is always accepted, since the fields are optional.
+  pub fn accepts_taxonomy_entry(&self, kind: TaxonomyKind, key: Option<&str>) -> bool {
+    key.map_or(true, |key| {
+      self
+        .taxonomy_entry(kind, key)
+        .map_or(false, |entry| !entry.is_deprecated())
+    })
+  }
+}
+
+struct Cache {
+  games: Arc<Vec<CatalogueGame>>,
+  loaded_at: Instant,
 }
 
 lazy_static! {
-  static ref GAMES: Vec<Game> = vec![
-    overwatch::overwatch(),
-    apex::apex(),
-    cs2::cs2(),
-    dota2::dota2(),
-    lol::lol(),
-    valorant::valorant(),
-  ];
+  static ref CACHE: RwLock<Option<Cache>> = RwLock::new(None);
 }
 
-pub fn all() -> &'static Vec<Game> {
-  &GAMES
+/// Bumped by every invalidation, so a load that started before a change
+/// doesn't put the old catalogue back into the cache.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+fn load(conn: &mut PgConnection) -> QueryResult<Vec<CatalogueGame>> {
+  let games = Game::all().load::<Game>(conn)?;
+  let skill_levels = SkillLevel::all().load::<SkillLevel>(conn)?;
+  let taxonomy = TaxonomyEntry::all().load::<TaxonomyEntry>(conn)?;
+
+  Ok(
+    games
+      .into_iter()
+      .map(|game| CatalogueGame {
+        skill_levels: skill_levels
+          .iter()
+          .filter(|skill_level| skill_level.game_id == game.id)
+          .cloned()
+          .collect(),
+        taxonomy: taxonomy
+          .iter()
+          .filter(|entry| entry.game_id == game.id)
+          .cloned()
+          .collect(),
+        review_categories: game.review_categories(),
+        id: game.id,
+        name: game.name,
+        transcoding_profile: game.transcoding_profile,
+      })
+      .collect(),
+  )
+}
+
+fn cached() -> Option<Arc<Vec<CatalogueGame>>> {
+  let cache = CACHE.read().unwrap();
+
+  cache
+    .as_ref()
+    .filter(|cache| cache.loaded_at.elapsed() < CACHE_TTL)
+    .map(|cache| cache.games.clone())
 }
 
-pub fn find(id: &str) -> Option<&'static Game> {
-  all().iter().find(|g| g.id.to_string() == id)
+pub async fn all(db_conn: &DbConn) -> QueryResult<Arc<Vec<CatalogueGame>>> {
+  if let Some(games) = cached() {
+    return Ok(games);
+  }
+
+  let generation = GENERATION.load(Ordering::SeqCst);
+  let games = Arc::new(db_conn.run(load).await?);
+  let mut cache = CACHE.write().unwrap();
+
+  if GENERATION.load(Ordering::SeqCst) == generation {
+    *cache = Some(Cache {
+      games: games.clone(),
+      loaded_at: Instant::now(),
+    });
+  }
+
+  Ok(games)
+}
+
+/// Looks up a game by id, failing with `NotFound` for unknown games.
+pub async fn find(db_conn: &DbConn, id: &str) -> QueryResult<CatalogueGame> {
+  all(db_conn)
+    .await?
+    .iter()
+    .find(|game| game.id == id)
+    .cloned()
+    .ok_or(diesel::result::Error::NotFound)
+}
+
+/// Drops the cached catalogue after it has been changed.
+pub fn invalidate() {
+  let mut cache = CACHE.write().unwrap();
+
+  GENERATION.fetch_add(1, Ordering::SeqCst);
+  *cache = None;
 }
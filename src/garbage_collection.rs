@@ -0,0 +1,403 @@
+use crate::aws::s3::{abort_upload, delete_object_keys, list_object_keys, original_key};
+use crate::config::Config;
+use crate::data_types::MediaState;
+use crate::guards::DbConn;
+use crate::models::{Avatar, Recording};
+use crate::schema::{avatars, recordings};
+use anyhow::Result;
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use rusoto_s3::S3Client;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+const PREFIXES: [&str; 2] = ["recordings/", "avatars/"];
+const PAGE_SIZE: i64 = 500;
+
+fn default_stale_after_hours() -> i64 {
+  24
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct GarbageCollectionConfig {
+  /// Report what would be cleaned up without touching the bucket or the
+  /// database.
+  #[serde(default)]
+  pub dry_run: bool,
+  /// How long a recording or avatar may sit in `Created` or `Uploaded`
+  /// before it is expired.
+  #[serde(default = "default_stale_after_hours")]
+  pub stale_after_hours: i64,
+}
+
+impl Default for GarbageCollectionConfig {
+  fn default() -> Self {
+    Self {
+      dry_run: false,
+      stale_after_hours: default_stale_after_hours(),
+    }
+  }
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct GarbageCollectionReport {
+  pub dry_run: bool,
+  /// Rows that never finished uploading or never started processing.
+  pub expired_recording_ids: Vec<Uuid>,
+  pub expired_avatar_ids: Vec<Uuid>,
+  /// Rows still being processed whose original upload is gone.
+  pub missing_recording_ids: Vec<Uuid>,
+  pub missing_avatar_ids: Vec<Uuid>,
+  /// Bucket objects that belong to no recording or avatar.
+  pub orphaned_keys: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Cleanup {
+  Expired,
+  Missing,
+}
+
+impl Cleanup {
+  fn reason(&self) -> &'static str {
+    match self {
+      Cleanup::Expired => "Upload expired",
+      Cleanup::Missing => "Original upload missing",
+    }
+  }
+}
+
+fn cleanup(
+  state: MediaState,
+  state_changed_at: NaiveDateTime,
+  key_exists: bool,
+  stale_before: NaiveDateTime,
+) -> Option<Cleanup> {
+  match state {
+    MediaState::Created | MediaState::Uploaded if state_changed_at < stale_before => {
+      Some(Cleanup::Expired)
+    }
+    MediaState::Uploaded
+    | MediaState::Moderating
+    | MediaState::Flagged
+    | MediaState::Transcoding
+      if !key_exists =>
+    {
+      Some(Cleanup::Missing)
+    }
+    _ => None,
+  }
+}
+
+/// Marks the recording as failed, then removes what it left in the bucket.
+/// Returns false if the recording moved on since it was loaded.
+async fn fail_recording(
+  db_conn: &DbConn,
+  config: &Config,
+  client: &S3Client,
+  recording: &Recording,
+  cleanup: &Cleanup,
+  key_exists: bool,
+) -> Result<bool> {
+  let changeset = recording
+    .transition_to(MediaState::Failed, Some(cleanup.reason().to_owned()))?
+    .upload_id(None);
+  let (id, state) = (recording.id, recording.state);
+
+  let updated = db_conn
+    .run(move |conn| {
+      diesel::update(recordings::table.find(id))
+        .filter(recordings::state.eq(state))
+        .set(changeset)
+        .execute(conn)
+    })
+    .await?;
+
+  if updated == 0 {
+    return Ok(false);
+  }
+
+  if *cleanup == Cleanup::Expired {
+    if let Some(video_key) = &recording.video_key {
+      if let Some(upload_id) = &recording.upload_id {
+        abort_upload(client, &config.uploads_bucket, video_key, upload_id).await?;
+      }
+
+      if key_exists {
+        delete_object_keys(client, &config.uploads_bucket, vec![video_key.clone()]).await?;
+      }
+    }
+  }
+
+  Ok(true)
+}
+
+/// Marks the avatar as failed, then removes its image from the bucket.
+/// Returns false if the avatar moved on since it was loaded.
+async fn fail_avatar(
+  db_conn: &DbConn,
+  config: &Config,
+  client: &S3Client,
+  avatar: &Avatar,
+  cleanup: &Cleanup,
+  key_exists: bool,
+) -> Result<bool> {
+  let changeset = avatar.transition_to(MediaState::Failed, Some(cleanup.reason().to_owned()))?;
+  let (id, state) = (avatar.id, avatar.state);
+
+  let updated = db_conn
+    .run(move |conn| {
+      diesel::update(avatars::table.find(id))
+        .filter(avatars::state.eq(state))
+        .set(changeset)
+        .execute(conn)
+    })
+    .await?;
+
+  if updated == 0 {
+    return Ok(false);
+  }
+
+  if *cleanup == Cleanup::Expired && key_exists {
+    delete_object_keys(
+      client,
+      &config.uploads_bucket,
+      vec![avatar.image_key.clone()],
+    )
+    .await?;
+  }
+
+  Ok(true)
+}
+
+/// Rows are cleaned up one at a time, database first, so a failure only
+/// skips that row and never leaves a row pointing at a deleted object.
+pub async fn collect(
+  db_conn: &DbConn,
+  config: &Config,
+  client: &S3Client,
+  dry_run: bool,
+) -> Result<GarbageCollectionReport> {
+  let stale_before =
+    Utc::now().naive_utc() - Duration::hours(config.garbage_collection.stale_after_hours);
+
+  // The bucket is listed before rows are loaded so that an upload racing
+  // with the job always has its row visible.
+  let mut keys = HashSet::new();
+
+  for prefix in PREFIXES.iter() {
+    keys.extend(list_object_keys(client, &config.uploads_bucket, prefix).await?);
+  }
+
+  let mut report = GarbageCollectionReport {
+    dry_run,
+    ..Default::default()
+  };
+
+  let mut known_keys = HashSet::new();
+  let mut after = Uuid::nil();
+
+  loop {
+    let recordings = db_conn
+      .run(move |conn| {
+        recordings::table
+          .filter(recordings::id.gt(after))
+          .order(recordings::id.asc())
+          .limit(PAGE_SIZE)
+          .load::<Recording>(conn)
+      })
+      .await?;
+
+    after = match recordings.last() {
+      Some(recording) => recording.id,
+      None => break,
+    };
+
+    for recording in recordings {
+      let video_key = match &recording.video_key {
+        Some(video_key) => video_key.clone(),
+        None => continue,
+      };
+
+      let key_exists = keys.contains(&video_key);
+      known_keys.insert(video_key);
+
+      if recording.deleted_at.is_some() {
+        continue;
+      }
+
+      let cleanup = match cleanup(
+        recording.state,
+        recording.state_changed_at,
+        key_exists,
+        stale_before,
+      ) {
+        Some(cleanup) => cleanup,
+        None => continue,
+      };
+
+      if !dry_run {
+        match fail_recording(db_conn, config, client, &recording, &cleanup, key_exists).await {
+          Ok(true) => (),
+          Ok(false) => continue,
+          Err(e) => {
+            error!(
+              "[garbage_collection] Failed to clean up recording {}: {}",
+              recording.id, e
+            );
+            continue;
+          }
+        }
+      }
+
+      match cleanup {
+        Cleanup::Expired => report.expired_recording_ids.push(recording.id),
+        Cleanup::Missing => report.missing_recording_ids.push(recording.id),
+      }
+    }
+  }
+
+  let mut after = Uuid::nil();
+
+  loop {
+    let avatars = db_conn
+      .run(move |conn| {
+        avatars::table
+          .filter(avatars::id.gt(after))
+          .order(avatars::id.asc())
+          .limit(PAGE_SIZE)
+          .load::<Avatar>(conn)
+      })
+      .await?;
+
+    after = match avatars.last() {
+      Some(avatar) => avatar.id,
+      None => break,
+    };
+
+    for avatar in avatars {
+      let key_exists = keys.contains(&avatar.image_key);
+      known_keys.insert(avatar.image_key.clone());
+
+      let cleanup = match cleanup(
+        avatar.state,
+        avatar.state_changed_at,
+        key_exists,
+        stale_before,
+      ) {
+        Some(cleanup) => cleanup,
+        None => continue,
+      };
+
+      if !dry_run {
+        match fail_avatar(db_conn, config, client, &avatar, &cleanup, key_exists).await {
+          Ok(true) => (),
+          Ok(false) => continue,
+          Err(e) => {
+            error!(
+              "[garbage_collection] Failed to clean up avatar {}: {}",
+              avatar.id, e
+            );
+            continue;
+          }
+        }
+      }
+
+      match cleanup {
+        Cleanup::Expired => report.expired_avatar_ids.push(avatar.id),
+        Cleanup::Missing => report.missing_avatar_ids.push(avatar.id),
+      }
+    }
+  }
+
+  report.orphaned_keys = keys
+    .into_iter()
+    .filter(|key| !known_keys.contains(&original_key(key)))
+    .collect();
+
+  report.orphaned_keys.sort();
+
+  if dry_run {
+    return Ok(report);
+  }
+
+  if let Err(e) =
+    delete_object_keys(client, &config.uploads_bucket, report.orphaned_keys.clone()).await
+  {
+    error!("[garbage_collection] Failed to delete orphaned keys: {}", e);
+  }
+
+  Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn stale_before() -> NaiveDateTime {
+    NaiveDateTime::parse_from_str("2024-01-02 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap()
+  }
+
+  fn stale() -> NaiveDateTime {
+    stale_before() - Duration::hours(1)
+  }
+
+  fn fresh() -> NaiveDateTime {
+    stale_before() + Duration::hours(1)
+  }
+
+  #[test]
+  fn expires_stale_uploads() {
+    for state in [MediaState::Created, MediaState::Uploaded] {
+      assert_eq!(
+        cleanup(state, stale(), true, stale_before()),
+        Some(Cleanup::Expired)
+      );
+      assert_eq!(
+        cleanup(state, stale(), false, stale_before()),
+        Some(Cleanup::Expired)
+      );
+    }
+  }
+
+  #[test]
+  fn keeps_fresh_uploads() {
+    assert_eq!(
+      cleanup(MediaState::Created, fresh(), false, stale_before()),
+      None
+    );
+    assert_eq!(
+      cleanup(MediaState::Uploaded, fresh(), true, stale_before()),
+      None
+    );
+  }
+
+  #[test]
+  fn flags_processing_media_whose_original_is_missing() {
+    for state in [
+      MediaState::Uploaded,
+      MediaState::Moderating,
+      MediaState::Flagged,
+      MediaState::Transcoding,
+    ] {
+      assert_eq!(
+        cleanup(state, fresh(), false, stale_before()),
+        Some(Cleanup::Missing)
+      );
+      assert_eq!(cleanup(state, fresh(), true, stale_before()), None);
+    }
+  }
+
+  #[test]
+  fn ignores_settled_media() {
+    for state in [
+      MediaState::Processed,
+      MediaState::Rejected,
+      MediaState::Failed,
+    ] {
+      assert_eq!(cleanup(state, stale(), false, stale_before()), None);
+    }
+  }
+}
@@ -1,7 +1,9 @@
 pub mod auth;
+mod client_ip;
 mod db_conn;
 mod s3;
-pub use auth::{Auth, Jwt};
+pub use auth::{Admin, Auth, Jwt, Moderator};
+pub use client_ip::ClientIp;
 pub use db_conn::DbConn;
 pub use s3::S3;
 
@@ -2,13 +2,18 @@ use okapi::openapi3::*;
 use rocket::request::{FromRequest, Outcome, Request};
 use rocket_okapi::gen::OpenApiGenerator;
 use rocket_okapi::request::{OpenApiFromRequest, RequestHeaderInput};
+mod admin;
 mod jwt;
+mod moderator;
 mod ott;
 use self::ott::ToScope;
 pub use self::ott::{Ott, ResetPassword};
+pub use admin::Admin;
 pub use jwt::Jwt;
+pub use moderator::Moderator;
 pub use ott::OneTimeTokenParams;
 use rocket::http::Status;
+use serde::Serialize;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -19,6 +24,26 @@ pub enum AuthError {
   Invalid(String),
   #[error("user not found")]
   NotFound,
+  #[error("insufficient permissions")]
+  Forbidden,
+  #[error("user is suspended")]
+  Suspended(Suspension),
+}
+
+/// Kept in the request-local cache so the 403 catcher can tell a suspended
+/// user apart from one who merely lacks permissions.
+#[derive(Clone, Debug, Serialize)]
+pub struct Suspension {
+  pub until: Option<chrono::NaiveDateTime>,
+  pub reason: Option<String>,
+}
+
+fn suspended<T>(request: &Request<'_>, e: AuthError) -> Outcome<T, AuthError> {
+  if let AuthError::Suspended(suspension) = &e {
+    request.local_cache(|| Some(suspension.clone()));
+  }
+
+  Outcome::Failure((Status::Forbidden, e))
 }
 
 #[async_trait]
@@ -48,6 +73,8 @@ impl<'r, T: AuthFromRequest> FromRequest<'r> for Auth<T> {
         AuthError::Missing => Outcome::Failure((Status::Unauthorized, e)),
         AuthError::Invalid(_) => Outcome::Failure((Status::BadRequest, e)),
         AuthError::NotFound => Outcome::Failure((Status::NotFound, e)),
+        AuthError::Forbidden => Outcome::Failure((Status::Forbidden, e)),
+        AuthError::Suspended(_) => suspended(request, e),
       },
     }
   }
@@ -64,52 +91,42 @@ impl<'r, T: AuthFromRequest> FromRequest<'r> for Auth<Option<T>> {
         AuthError::Missing => Outcome::Success(Auth(None)),
         AuthError::Invalid(_) => Outcome::Failure((Status::BadRequest, e)),
         AuthError::NotFound => Outcome::Failure((Status::NotFound, e)),
+        AuthError::Forbidden => Outcome::Failure((Status::Forbidden, e)),
+        AuthError::Suspended(_) => suspended(request, e),
       },
     }
   }
 }
 
-impl<'a> OpenApiFromRequest<'a> for Auth<Jwt> {
-  fn from_request_input(
-    _gen: &mut OpenApiGenerator,
-    _name: String,
-    _required: bool,
-  ) -> rocket_okapi::Result<RequestHeaderInput> {
-    Ok(RequestHeaderInput::Security(
-      "jwt".to_owned(),
-      SecurityScheme {
-        description: None,
-        data: SecuritySchemeData::Http {
-          scheme: "bearer".to_owned(),
-          bearer_format: Some("jwt".to_owned()),
-        },
-        extensions: Object::default(),
-      },
-      SecurityRequirement::default(),
-    ))
-  }
+/// Documents guards that authenticate with the bearer JWT.
+macro_rules! impl_jwt_openapi {
+  ($($guard:ty),+ $(,)?) => {
+    $(
+      impl<'a> OpenApiFromRequest<'a> for Auth<$guard> {
+        fn from_request_input(
+          _gen: &mut OpenApiGenerator,
+          _name: String,
+          _required: bool,
+        ) -> rocket_okapi::Result<RequestHeaderInput> {
+          Ok(RequestHeaderInput::Security(
+            "jwt".to_owned(),
+            SecurityScheme {
+              description: None,
+              data: SecuritySchemeData::Http {
+                scheme: "bearer".to_owned(),
+                bearer_format: Some("jwt".to_owned()),
+              },
+              extensions: Object::default(),
+            },
+            SecurityRequirement::default(),
+          ))
+        }
+      }
+    )+
+  };
 }
 
-impl<'a> OpenApiFromRequest<'a> for Auth<Option<Jwt>> {
-  fn from_request_input(
-    _gen: &mut OpenApiGenerator,
-    _name: String,
-    _required: bool,
-  ) -> rocket_okapi::Result<RequestHeaderInput> {
-    Ok(RequestHeaderInput::Security(
-      "jwt".to_owned(),
-      SecurityScheme {
-        description: None,
-        data: SecuritySchemeData::Http {
-          scheme: "bearer".to_owned(),
-          bearer_format: Some("jwt".to_owned()),
-        },
-        extensions: Object::default(),
-      },
-      SecurityRequirement::default(),
-    ))
-  }
-}
+impl_jwt_openapi!(Jwt, Option<Jwt>, Admin, Moderator);
 
 impl<'a, T: ToScope> OpenApiFromRequest<'a> for Auth<Ott<T>> {
   fn from_request_input(
@@ -0,0 +1,31 @@
+use super::{Auth, AuthError, AuthFromRequest, Jwt};
+use crate::data_types::UserRole;
+use crate::models::User;
+use rocket::Request;
+
+pub struct Admin(User);
+
+impl Admin {
+  pub fn into_user(self) -> User {
+    self.0
+  }
+}
+
+impl Auth<Admin> {
+  pub fn into_user(self) -> User {
+    self.0.into_user()
+  }
+}
+
+#[async_trait]
+impl AuthFromRequest for Admin {
+  async fn from_request(req: &Request<'_>) -> Result<Self, AuthError> {
+    let user = Jwt::from_request(req).await?.into_user();
+
+    if user.role != UserRole::Admin {
+      return Err(AuthError::Forbidden);
+    }
+
+    Ok(Self(user))
+  }
+}
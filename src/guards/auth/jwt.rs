@@ -1,4 +1,4 @@
-use super::{Auth, AuthError};
+use super::{Auth, AuthError, Suspension};
 use crate::guards::DbConn;
 use crate::models::User;
 use crate::oidc::OidcCache;
@@ -90,10 +90,17 @@ impl User {
     let uuid = Uuid::parse_str(&jwt.sub).map_err(|e| AuthError::Invalid(e.to_string()))?;
 
     let user = db_conn
-      .run(move |conn| User::find_by_id(&uuid).first(conn))
+      .run(move |conn| User::find_by_id(&uuid).first::<User>(conn))
       .await
       .map_err(|_| AuthError::NotFound)?;
 
+    if user.is_suspended() {
+      return Err(AuthError::Suspended(Suspension {
+        until: user.suspended_until,
+        reason: user.suspension_reason,
+      }));
+    }
+
     Ok(user)
   }
 }
@@ -0,0 +1,30 @@
+use super::{Auth, AuthError, AuthFromRequest, Jwt};
+use crate::models::User;
+use rocket::Request;
+
+pub struct Moderator(User);
+
+impl Moderator {
+  pub fn into_user(self) -> User {
+    self.0
+  }
+}
+
+impl Auth<Moderator> {
+  pub fn into_user(self) -> User {
+    self.0.into_user()
+  }
+}
+
+#[async_trait]
+impl AuthFromRequest for Moderator {
+  async fn from_request(req: &Request<'_>) -> Result<Self, AuthError> {
+    let user = Jwt::from_request(req).await?.into_user();
+
+    if !user.role.can_moderate() {
+      return Err(AuthError::Forbidden);
+    }
+
+    Ok(Self(user))
+  }
+}
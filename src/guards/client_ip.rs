@@ -0,0 +1,31 @@
+use rocket::request::{FromRequest, Outcome};
+use rocket::Request;
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::request::{OpenApiFromRequest, RequestHeaderInput};
+
+pub struct ClientIp(pub Option<String>);
+
+#[async_trait]
+impl<'r> FromRequest<'r> for ClientIp {
+  type Error = ();
+
+  async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+    Outcome::Success(ClientIp(request.client_ip().map(|ip| ip.to_string())))
+  }
+}
+
+impl<'a> OpenApiFromRequest<'a> for ClientIp {
+  fn from_request_input(
+    _gen: &mut OpenApiGenerator,
+    _name: String,
+    _required: bool,
+  ) -> rocket_okapi::Result<RequestHeaderInput> {
+    Ok(RequestHeaderInput::None)
+  }
+}
+
+impl ClientIp {
+  pub fn into_inner(self) -> Option<String> {
+    self.0
+  }
+}
@@ -5,22 +5,31 @@ use std::env;
 #[macro_use]
 extern crate rocket;
 
+pub mod audit;
 pub mod auth;
 pub mod aws;
+pub mod catchers;
 pub mod config;
 pub mod data_types;
 pub mod emails;
 pub mod fairings;
 pub mod games;
+pub mod garbage_collection;
 pub mod guards;
 pub mod intercom;
 pub mod models;
+pub mod moderation;
+pub mod notifications;
 pub mod oidc;
 pub mod pagination;
 pub mod queue_handlers;
+pub mod reports;
 pub mod response;
 pub mod routes;
 pub mod schema;
+pub mod svg;
+pub mod transcoding;
+pub mod upload_limits;
 pub mod views;
 
 pub const TEST_PROFILE: Profile = Profile::const_new("test");
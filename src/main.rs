@@ -7,7 +7,7 @@ use diesel_migrations::*;
 use ranklab_api::config::Config;
 use ranklab_api::guards::DbConn;
 use ranklab_api::routes::*;
-use ranklab_api::{fairings, oidc, PROFILE};
+use ranklab_api::{catchers, fairings, oidc, transcoding, PROFILE};
 use rocket::fairing::AdHoc;
 use rocket::figment::providers::{Env, Format, Toml};
 use rocket::http::Accept;
@@ -63,30 +63,86 @@ async fn rocket() -> Rocket<Build> {
       Box::pin(async move { req.replace_header(Accept::JSON) })
     }))
     .attach(AdHoc::config::<Config>())
+    .attach(AdHoc::on_liftoff("Ffmpeg", |rocket| {
+      Box::pin(transcoding::ffmpeg::init(rocket))
+    }))
     .manage(oidc::init_cache(&web_host).await.unwrap())
     .mount(
       "/",
       openapi_get_routes![
+        admin::collect_garbage,
+        admin::create_game,
+        admin::create_skill_level,
+        admin::create_taxonomy_entry,
+        admin::delete_comment,
+        admin::delete_recording,
+        admin::get_recording_processing,
+        admin::get_user,
+        admin::list_audit_events,
+        admin::list_users,
+        admin::reset_media_acls,
+        admin::shadow_ban_user,
+        admin::suspend_user,
+        admin::unshadow_ban_user,
+        admin::unsuspend_user,
+        admin::update_game,
+        admin::update_skill_level,
+        admin::update_taxonomy_entry,
+        admin::update_user_role,
         avatars::create,
         avatars::delete,
         avatars::get,
+        clips::create,
+        clips::delete,
+        clips::get,
+        clips::list,
+        clips::render,
         comments::create,
         comments::delete,
         comments::list,
+        comments::restore,
         comments::update,
         games::list,
         games::update,
         index::get,
+        moderation::approve_avatar,
+        moderation::approve_recording,
+        moderation::dismiss_report,
+        moderation::hide_report,
+        moderation::list_avatars,
+        moderation::list_recordings,
+        moderation::list_reports,
+        moderation::reject_avatar,
+        moderation::reject_recording,
+        moderation::suspend_report_author,
+        notification_preferences::list,
+        notification_preferences::unsubscribe,
+        notification_preferences::update,
         passwords::create,
         passwords::update,
+        recording_uploads::abort,
+        recording_uploads::complete,
+        recording_uploads::create,
+        recording_uploads::get,
+        recording_uploads::presign_parts,
         recordings::create,
         recordings::delete,
         recordings::get,
+        recordings::get_playlist,
+        recordings::get_processing,
         recordings::list,
+        recordings::restore,
+        reports::create,
+        reviews::create,
+        reviews::delete,
+        reviews::list,
+        reviews::update,
+        reviews::update_feedback,
         sessions::create,
         users::create,
         users::get,
         users::update,
       ],
     )
+    .register("/", catchers![catchers::forbidden])
 }
@@ -1,19 +1,38 @@
+mod audit_event;
 mod avatar;
+mod clip;
 mod comment;
 mod digest;
 mod following;
 mod game;
+mod notification_preference;
 mod one_time_token;
+mod processed_event;
+mod processing_job;
 mod recording;
+mod report;
+mod review;
 mod session;
 mod user;
 
+pub use audit_event::{AuditEvent, AuditEventChangeset};
 pub use avatar::{Avatar, AvatarChangeset};
-pub use comment::{Comment, CommentChangeset, CommentMetadata};
+pub use clip::{Clip, ClipChangeset};
+pub use comment::{Comment, CommentChangeset, CommentMetadata, Keyframe};
 pub use digest::{Digest, DigestChangeset};
 pub use following::{Following, FollowingChangeset};
-pub use game::{Game, SkillLevel};
+pub use game::{
+  Game, GameChangeset, ReviewCategory, SkillLevel, SkillLevelChangeset, TaxonomyEntry,
+  TaxonomyEntryChangeset,
+};
+pub use notification_preference::{NotificationPreference, NotificationPreferenceChangeset};
 pub use one_time_token::{OneTimeToken, OneTimeTokenChangeset};
+pub use processed_event::{ProcessedEvent, ProcessedEventChangeset};
+pub use processing_job::{ProcessingJob, ProcessingJobChangeset};
 pub use recording::{Recording, RecordingChangeset, RecordingWithCommentCount};
+pub use report::{Report, ReportChangeset, ReportTarget};
+pub use review::{
+  Review, ReviewChangeset, ReviewScore, ReviewScoreChangeset, MAX_REVIEW_SCORE, MIN_REVIEW_SCORE,
+};
 pub use session::Session;
 pub use user::{User, UserChangeset};
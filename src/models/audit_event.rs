@@ -0,0 +1,58 @@
+use crate::data_types::{AuditAction, AuditTargetType};
+use crate::schema::audit_events;
+use derive_builder::Builder;
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+#[derive(Builder, Queryable, Identifiable, Clone)]
+#[builder(
+  derive(AsChangeset, Insertable),
+  pattern = "owned",
+  name = "AuditEventChangeset"
+)]
+#[builder_struct_attr(diesel(table_name = audit_events))]
+pub struct AuditEvent {
+  pub id: Uuid,
+  pub action: AuditAction,
+  pub actor_id: Option<Uuid>,
+  pub target_type: Option<AuditTargetType>,
+  pub target_id: Option<Uuid>,
+  pub ip_address: Option<String>,
+  pub payload: serde_json::Value,
+  pub created_at: chrono::NaiveDateTime,
+}
+
+impl AuditEventChangeset {
+  pub fn target(self, target_type: AuditTargetType, target_id: Uuid) -> Self {
+    self
+      .target_type(Some(target_type))
+      .target_id(Some(target_id))
+  }
+}
+
+impl AuditEvent {
+  pub fn filter_by(
+    actor_id: Option<Uuid>,
+    target_id: Option<Uuid>,
+    action: Option<AuditAction>,
+  ) -> audit_events::BoxedQuery<'static, Pg> {
+    let mut query = audit_events::table
+      .order(audit_events::created_at.desc())
+      .into_boxed();
+
+    if let Some(actor_id) = actor_id {
+      query = query.filter(audit_events::actor_id.eq(actor_id));
+    }
+
+    if let Some(target_id) = target_id {
+      query = query.filter(audit_events::target_id.eq(target_id));
+    }
+
+    if let Some(action) = action {
+      query = query.filter(audit_events::action.eq(action));
+    }
+
+    query
+  }
+}
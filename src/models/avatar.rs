@@ -1,7 +1,8 @@
-use crate::data_types::MediaState;
+use crate::data_types::{MediaState, MediaStateTransitionError};
 use crate::schema::avatars;
 use derive_builder::Builder;
 use diesel::dsl::{And, Eq, Filter, FindBy};
+use diesel::helper_types::{Asc, Order};
 use diesel::prelude::*;
 use schemars::JsonSchema;
 use serde::Serialize;
@@ -22,9 +23,25 @@ pub struct Avatar {
   pub updated_at: chrono::NaiveDateTime,
   pub state: MediaState,
   pub user_id: Uuid,
+  pub state_reason: Option<String>,
+  pub state_changed_at: chrono::NaiveDateTime,
+  pub moderation_labels: Option<serde_json::Value>,
 }
 
 impl Avatar {
+  pub fn transition_to(
+    &self,
+    state: MediaState,
+    reason: Option<String>,
+  ) -> Result<AvatarChangeset, MediaStateTransitionError> {
+    Ok(
+      AvatarChangeset::default()
+        .state(self.state.transition_to(state)?)
+        .state_reason(reason)
+        .state_changed_at(chrono::Utc::now().naive_utc()),
+    )
+  }
+
   pub fn find_by_image_key(image_key: &str) -> FindBy<avatars::table, avatars::image_key, String> {
     avatars::table.filter(avatars::image_key.eq(image_key.to_string()))
   }
@@ -33,6 +50,13 @@ impl Avatar {
     avatars::table.filter(avatars::id.eq(*id))
   }
 
+  pub fn filter_flagged(
+  ) -> Order<FindBy<avatars::table, avatars::state, MediaState>, Asc<avatars::state_changed_at>> {
+    avatars::table
+      .filter(avatars::state.eq(MediaState::Flagged))
+      .order(avatars::state_changed_at.asc())
+  }
+
   #[allow(clippy::type_complexity)]
   pub fn find_by_id_for_user(
     id: &Uuid,
@@ -0,0 +1,56 @@
+use crate::data_types::ClipRenderState;
+use crate::schema::clips;
+use derive_builder::Builder;
+use diesel::dsl::{And, Eq, Filter, FindBy};
+use diesel::helper_types::{Asc, Order};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+#[derive(Builder, Queryable, Identifiable, Clone)]
+#[builder(
+  derive(AsChangeset, Insertable),
+  pattern = "owned",
+  name = "ClipChangeset"
+)]
+#[builder_struct_attr(diesel(table_name = clips))]
+pub struct Clip {
+  pub id: Uuid,
+  pub recording_id: Uuid,
+  pub user_id: Uuid,
+  pub title: String,
+  pub start_timestamp: i64,
+  pub end_timestamp: i64,
+  pub render_state: Option<ClipRenderState>,
+  pub render_job_id: Option<String>,
+  pub video_key: Option<String>,
+  pub created_at: chrono::NaiveDateTime,
+  pub updated_at: chrono::NaiveDateTime,
+}
+
+#[allow(clippy::type_complexity)]
+impl Clip {
+  pub fn find_by_id(id: &Uuid) -> FindBy<clips::table, clips::id, Uuid> {
+    clips::table.filter(clips::id.eq(*id))
+  }
+
+  pub fn find_for_user(
+    user_id: &Uuid,
+    id: &Uuid,
+  ) -> Filter<clips::table, And<Eq<clips::id, Uuid>, Eq<clips::user_id, Uuid>>> {
+    clips::table.filter(clips::id.eq(*id).and(clips::user_id.eq(*user_id)))
+  }
+
+  pub fn find_by_render_job_id(
+    render_job_id: &str,
+  ) -> FindBy<clips::table, clips::render_job_id, String> {
+    clips::table.filter(clips::render_job_id.eq(render_job_id.to_string()))
+  }
+
+  pub fn filter_by_recording_id(
+    recording_id: &Uuid,
+  ) -> Order<FindBy<clips::table, clips::recording_id, Uuid>, Asc<clips::start_timestamp>> {
+    clips::table
+      .filter(clips::recording_id.eq(*recording_id))
+      .order(clips::start_timestamp.asc())
+  }
+}
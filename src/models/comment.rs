@@ -1,7 +1,9 @@
 use crate::schema::{comments, recordings, users};
+use crate::svg;
+use chrono::NaiveDateTime;
 use derive_builder::Builder;
 use diesel::dsl::{And, Eq, Filter};
-use diesel::helper_types::{InnerJoin, IsNull, NotEq, On, Select};
+use diesel::helper_types::{EqAny, Gt, InnerJoin, IsNull, Lt, LtEq, NotEq, On, Select};
 use diesel::prelude::*;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -10,7 +12,58 @@ use uuid::Uuid;
 #[derive(Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case")]
 pub enum CommentMetadata {
-  Video { timestamp: i64, drawing: String },
+  /// Feedback on the recording as a whole.
+  General,
+  Video {
+    timestamp: i64,
+    drawing: String,
+  },
+  /// Points at a sequence of the recording rather than a single moment.
+  Range {
+    start_timestamp: i64,
+    end_timestamp: i64,
+    drawing: String,
+  },
+  /// A drawing that changes over time, e.g. to follow a moving player.
+  Keyframes {
+    keyframes: Vec<Keyframe>,
+  },
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+pub struct Keyframe {
+  pub timestamp: i64,
+  pub drawing: String,
+}
+
+impl CommentMetadata {
+  pub fn sanitized(&self) -> Self {
+    match self {
+      CommentMetadata::General => CommentMetadata::General,
+      CommentMetadata::Video { timestamp, drawing } => CommentMetadata::Video {
+        timestamp: *timestamp,
+        drawing: svg::sanitize(drawing),
+      },
+      CommentMetadata::Range {
+        start_timestamp,
+        end_timestamp,
+        drawing,
+      } => CommentMetadata::Range {
+        start_timestamp: *start_timestamp,
+        end_timestamp: *end_timestamp,
+        drawing: svg::sanitize(drawing),
+      },
+      CommentMetadata::Keyframes { keyframes } => CommentMetadata::Keyframes {
+        keyframes: keyframes
+          .iter()
+          .map(|keyframe| Keyframe {
+            timestamp: keyframe.timestamp,
+            drawing: svg::sanitize(&keyframe.drawing),
+          })
+          .collect(),
+      },
+    }
+  }
 }
 
 #[derive(Builder, Queryable, Identifiable, Clone)]
@@ -29,6 +82,8 @@ pub struct Comment {
   pub user_id: Uuid,
   pub recording_id: Uuid,
   pub notified_at: Option<chrono::NaiveDateTime>,
+  pub hidden_at: Option<chrono::NaiveDateTime>,
+  pub deleted_at: Option<chrono::NaiveDateTime>,
 }
 
 #[allow(clippy::type_complexity)]
@@ -36,14 +91,58 @@ impl Comment {
   pub fn find_for_user(
     user_id: &Uuid,
     id: &Uuid,
-  ) -> Filter<comments::table, And<Eq<comments::id, Uuid>, Eq<comments::user_id, Uuid>>> {
-    comments::table.filter(comments::id.eq(*id).and(comments::user_id.eq(*user_id)))
+  ) -> Filter<
+    comments::table,
+    And<And<Eq<comments::id, Uuid>, Eq<comments::user_id, Uuid>>, IsNull<comments::deleted_at>>,
+  > {
+    comments::table.filter(
+      comments::id
+        .eq(*id)
+        .and(comments::user_id.eq(*user_id))
+        .and(comments::deleted_at.is_null()),
+    )
+  }
+
+  pub fn find_deleted_for_user(
+    user_id: &Uuid,
+    id: &Uuid,
+    deleted_since: chrono::NaiveDateTime,
+  ) -> Filter<
+    comments::table,
+    And<
+      And<Eq<comments::id, Uuid>, Eq<comments::user_id, Uuid>>,
+      Gt<comments::deleted_at, chrono::NaiveDateTime>,
+    >,
+  > {
+    comments::table.filter(
+      comments::id
+        .eq(*id)
+        .and(comments::user_id.eq(*user_id))
+        .and(comments::deleted_at.gt(deleted_since)),
+    )
+  }
+
+  pub fn filter_deleted_before(
+    cutoff: chrono::NaiveDateTime,
+  ) -> Filter<comments::table, Lt<comments::deleted_at, chrono::NaiveDateTime>> {
+    comments::table.filter(comments::deleted_at.lt(cutoff))
   }
 
   pub fn filter_by_recording_id(
     recording_id: &Uuid,
-  ) -> Filter<comments::table, Eq<comments::recording_id, Uuid>> {
-    comments::table.filter(comments::recording_id.eq(*recording_id))
+  ) -> Filter<
+    comments::table,
+    And<
+      And<Eq<comments::recording_id, Uuid>, IsNull<comments::hidden_at>>,
+      IsNull<comments::deleted_at>,
+    >,
+  > {
+    comments::table.filter(
+      comments::recording_id
+        .eq(*recording_id)
+        .and(comments::hidden_at.is_null())
+        .and(comments::deleted_at.is_null()),
+    )
   }
 
   pub fn filter_unnotified() -> Select<
@@ -56,8 +155,11 @@ impl Comment {
         On<users::table, Eq<recordings::user_id, users::id>>,
       >,
       And<
-        And<Eq<users::emails_enabled, bool>, IsNull<comments::notified_at>>,
-        NotEq<comments::user_id, recordings::user_id>,
+        And<
+          And<IsNull<comments::notified_at>, NotEq<comments::user_id, recordings::user_id>>,
+          IsNull<comments::deleted_at>,
+        >,
+        IsNull<recordings::deleted_at>,
       >,
     >,
     (
@@ -70,10 +172,11 @@ impl Comment {
       .inner_join(recordings::table.on(comments::recording_id.eq(recordings::id)))
       .inner_join(users::table.on(recordings::user_id.eq(users::id)))
       .filter(
-        users::emails_enabled
-          .eq(true)
-          .and(comments::notified_at.is_null())
-          .and(comments::user_id.ne(recordings::user_id)),
+        comments::notified_at
+          .is_null()
+          .and(comments::user_id.ne(recordings::user_id))
+          .and(comments::deleted_at.is_null())
+          .and(recordings::deleted_at.is_null()),
       )
       .select((
         comments::all_columns,
@@ -81,4 +184,54 @@ impl Comment {
         recordings::all_columns,
       ))
   }
+
+  /// Comments posted in a period on recordings that are still around, used to
+  /// notify earlier commenters of replies.
+  pub fn filter_posted_between(
+    since: NaiveDateTime,
+    until: NaiveDateTime,
+  ) -> Select<
+    Filter<
+      InnerJoin<comments::table, On<recordings::table, Eq<comments::recording_id, recordings::id>>>,
+      And<
+        And<
+          And<
+            And<Gt<comments::created_at, NaiveDateTime>, LtEq<comments::created_at, NaiveDateTime>>,
+            IsNull<comments::hidden_at>,
+          >,
+          IsNull<comments::deleted_at>,
+        >,
+        IsNull<recordings::deleted_at>,
+      >,
+    >,
+    (
+      <comments::table as diesel::Table>::AllColumns,
+      <recordings::table as diesel::Table>::AllColumns,
+    ),
+  > {
+    comments::table
+      .inner_join(recordings::table.on(comments::recording_id.eq(recordings::id)))
+      .filter(
+        comments::created_at
+          .gt(since)
+          .and(comments::created_at.le(until))
+          .and(comments::hidden_at.is_null())
+          .and(comments::deleted_at.is_null())
+          .and(recordings::deleted_at.is_null()),
+      )
+      .select((comments::all_columns, recordings::all_columns))
+  }
+
+  pub fn filter_by_recording_ids(
+    recording_ids: Vec<Uuid>,
+  ) -> Filter<
+    comments::table,
+    And<EqAny<comments::recording_id, Vec<Uuid>>, IsNull<comments::deleted_at>>,
+  > {
+    comments::table.filter(
+      comments::recording_id
+        .eq_any(recording_ids)
+        .and(comments::deleted_at.is_null()),
+    )
+  }
 }
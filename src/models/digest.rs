@@ -1,7 +1,5 @@
 use crate::schema::digests;
 use derive_builder::Builder;
-use diesel::dsl::{Limit, Order};
-use diesel::helper_types::Desc;
 use diesel::prelude::*;
 use uuid::Uuid;
 
@@ -18,10 +16,3 @@ pub struct Digest {
   pub updated_at: chrono::NaiveDateTime,
   pub metadata: serde_json::Value,
 }
-
-#[allow(clippy::type_complexity)]
-impl Digest {
-  pub fn last() -> Limit<Order<digests::table, Desc<digests::created_at>>> {
-    digests::table.order(digests::created_at.desc()).limit(1)
-  }
-}
@@ -1,3 +1,4 @@
+use crate::games::CatalogueGame;
 use crate::schema::followings;
 use derive_builder::Builder;
 use diesel::dsl::{Eq, Filter};
@@ -5,7 +6,7 @@ use diesel::helper_types::EqAny;
 use diesel::prelude::*;
 use uuid::Uuid;
 
-use super::User;
+use super::{Recording, User};
 
 #[derive(Builder, Queryable, Identifiable, Clone)]
 #[builder(
@@ -20,6 +21,13 @@ pub struct Following {
   pub game_id: String,
   pub created_at: chrono::NaiveDateTime,
   pub updated_at: chrono::NaiveDateTime,
+  /// Optional taxonomy keys narrowing which recordings count towards the
+  /// digest.
+  pub map: Option<String>,
+  pub role: Option<String>,
+  pub hero: Option<String>,
+  pub min_skill_level: Option<i16>,
+  pub max_skill_level: Option<i16>,
 }
 
 #[allow(clippy::type_complexity)]
@@ -48,4 +56,24 @@ impl Following {
     followings::table
       .filter(followings::user_id.eq_any(users.iter().map(|u| u.id).collect::<Vec<Uuid>>()))
   }
+
+  pub fn has_skill_level_range(&self) -> bool {
+    self.min_skill_level.is_some() || self.max_skill_level.is_some()
+  }
+
+  /// Whether a recording of the followed game falls under this following's
+  /// filters.
+  pub fn matches(&self, game: &CatalogueGame, recording: &Recording) -> bool {
+    let matches =
+      |filter: &Option<String>, value: &Option<String>| filter.is_none() || filter == value;
+
+    recording.game_id == self.game_id
+      && (!self.has_skill_level_range()
+        || game
+          .skill_levels_between(self.min_skill_level, self.max_skill_level)
+          .contains(&recording.skill_level))
+      && matches(&self.map, &recording.map)
+      && matches(&self.role, &recording.role)
+      && matches(&self.hero, &recording.hero)
+  }
 }
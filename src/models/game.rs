@@ -1,28 +1,156 @@
-use crate::games::GameId;
+use crate::data_types::TaxonomyKind;
+use crate::schema::{games, skill_levels, taxonomy_entries};
+use derive_builder::Builder;
+use diesel::dsl::{And, Eq, Filter, FindBy};
+use diesel::helper_types::{Asc, Order};
+use diesel::prelude::*;
 use schemars::JsonSchema;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, JsonSchema, Clone)]
+#[derive(Builder, Queryable, Identifiable, Clone)]
+#[builder(
+  derive(AsChangeset, Insertable),
+  pattern = "owned",
+  name = "GameChangeset"
+)]
+#[builder_struct_attr(diesel(table_name = games))]
+pub struct Game {
+  pub id: String,
+  pub name: String,
+  pub transcoding_profile: String,
+  /// A list of `ReviewCategory`.
+  pub review_categories: serde_json::Value,
+  pub position: i16,
+  pub created_at: chrono::NaiveDateTime,
+  pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Builder, Queryable, Clone)]
+#[builder(
+  derive(AsChangeset, Insertable),
+  pattern = "owned",
+  name = "SkillLevelChangeset"
+)]
+#[builder_struct_attr(diesel(table_name = skill_levels))]
 pub struct SkillLevel {
+  pub game_id: String,
+  /// Stored on recordings, so it's never reused or renumbered.
+  pub value: i16,
+  pub name: String,
+  pub position: i16,
+  /// Deprecated ranks can't be picked for new recordings but still describe
+  /// the ones already using them.
+  pub deprecated_at: Option<chrono::NaiveDateTime>,
+  pub created_at: chrono::NaiveDateTime,
+  pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Builder, Queryable, Clone)]
+#[builder(
+  derive(AsChangeset, Insertable),
+  pattern = "owned",
+  name = "TaxonomyEntryChangeset"
+)]
+#[builder_struct_attr(diesel(table_name = taxonomy_entries))]
+pub struct TaxonomyEntry {
+  pub game_id: String,
+  pub kind: TaxonomyKind,
+  pub key: String,
+  pub name: String,
+  pub position: i16,
+  pub deprecated_at: Option<chrono::NaiveDateTime>,
+  pub created_at: chrono::NaiveDateTime,
+  pub updated_at: chrono::NaiveDateTime,
+}
+
+/// A rubric category that reviewers score a recording on.
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+pub struct ReviewCategory {
+  pub key: String,
   pub name: String,
-  pub value: u8,
 }
 
+#[allow(clippy::type_complexity)]
+impl Game {
+  pub fn all() -> Order<games::table, Asc<games::position>> {
+    games::table.order(games::position.asc())
+  }
+
+  pub fn find_by_id(id: &str) -> FindBy<games::table, games::id, String> {
+    games::table.filter(games::id.eq(id.to_owned()))
+  }
+
+  pub fn review_categories(&self) -> Vec<ReviewCategory> {
+    serde_json::from_value(self.review_categories.clone()).unwrap_or_default()
+  }
+}
+
+#[allow(clippy::type_complexity)]
 impl SkillLevel {
-  pub fn new_vec(skill_levels: Vec<&str>) -> Vec<Self> {
-    skill_levels
-      .iter()
-      .enumerate()
-      .map(|(value, &name)| Self {
-        name: name.to_owned(),
-        value: value as u8,
-      })
-      .collect()
+  pub fn all() -> Order<skill_levels::table, Asc<skill_levels::position>> {
+    skill_levels::table.order(skill_levels::position.asc())
+  }
+
+  pub fn filter_by_game_id(
+    game_id: &str,
+  ) -> FindBy<skill_levels::table, skill_levels::game_id, String> {
+    skill_levels::table.filter(skill_levels::game_id.eq(game_id.to_owned()))
+  }
+
+  pub fn find(
+    game_id: &str,
+    value: i16,
+  ) -> Filter<
+    skill_levels::table,
+    And<Eq<skill_levels::game_id, String>, Eq<skill_levels::value, i16>>,
+  > {
+    skill_levels::table.filter(
+      skill_levels::game_id
+        .eq(game_id.to_owned())
+        .and(skill_levels::value.eq(value)),
+    )
+  }
+
+  pub fn is_deprecated(&self) -> bool {
+    self.deprecated_at.is_some()
   }
 }
 
-pub struct Game {
-  pub id: GameId,
-  pub skill_levels: Vec<SkillLevel>,
-  pub name: String,
+#[allow(clippy::type_complexity)]
+impl TaxonomyEntry {
+  pub fn all() -> Order<taxonomy_entries::table, Asc<taxonomy_entries::position>> {
+    taxonomy_entries::table.order(taxonomy_entries::position.asc())
+  }
+
+  pub fn filter_by_game_and_kind(
+    game_id: &str,
+    kind: TaxonomyKind,
+  ) -> Filter<
+    taxonomy_entries::table,
+    And<Eq<taxonomy_entries::game_id, String>, Eq<taxonomy_entries::kind, TaxonomyKind>>,
+  > {
+    taxonomy_entries::table.filter(
+      taxonomy_entries::game_id
+        .eq(game_id.to_owned())
+        .and(taxonomy_entries::kind.eq(kind)),
+    )
+  }
+
+  pub fn find(
+    game_id: &str,
+    kind: TaxonomyKind,
+    key: &str,
+  ) -> Filter<
+    Filter<
+      taxonomy_entries::table,
+      And<Eq<taxonomy_entries::game_id, String>, Eq<taxonomy_entries::kind, TaxonomyKind>>,
+    >,
+    Eq<taxonomy_entries::key, String>,
+  > {
+    Self::filter_by_game_and_kind(game_id, kind).filter(taxonomy_entries::key.eq(key.to_owned()))
+  }
+
+  pub fn is_deprecated(&self) -> bool {
+    self.deprecated_at.is_some()
+  }
 }
@@ -0,0 +1,68 @@
+use crate::data_types::{NotificationCategory, NotificationFrequency};
+use crate::schema::notification_preferences;
+use derive_builder::Builder;
+use diesel::dsl::{And, Eq, Filter, FindBy};
+use diesel::helper_types::EqAny;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+#[derive(Builder, Queryable, Clone)]
+#[builder(
+  derive(AsChangeset, Insertable),
+  pattern = "owned",
+  name = "NotificationPreferenceChangeset"
+)]
+#[builder_struct_attr(diesel(table_name = notification_preferences))]
+pub struct NotificationPreference {
+  pub user_id: Uuid,
+  pub category: NotificationCategory,
+  pub frequency: NotificationFrequency,
+  pub last_sent_at: Option<chrono::NaiveDateTime>,
+  pub created_at: chrono::NaiveDateTime,
+  pub updated_at: chrono::NaiveDateTime,
+}
+
+#[allow(clippy::type_complexity)]
+impl NotificationPreference {
+  pub fn filter_for_user(
+    user_id: &Uuid,
+  ) -> FindBy<notification_preferences::table, notification_preferences::user_id, Uuid> {
+    notification_preferences::table.filter(notification_preferences::user_id.eq(*user_id))
+  }
+
+  pub fn filter_by_category(
+    category: NotificationCategory,
+    user_ids: Vec<Uuid>,
+  ) -> Filter<
+    notification_preferences::table,
+    And<
+      Eq<notification_preferences::category, NotificationCategory>,
+      EqAny<notification_preferences::user_id, Vec<Uuid>>,
+    >,
+  > {
+    notification_preferences::table.filter(
+      notification_preferences::category
+        .eq(category)
+        .and(notification_preferences::user_id.eq_any(user_ids)),
+    )
+  }
+
+  /// Preferences of users who asked for a category to be batched.
+  pub fn filter_batched(
+    category: NotificationCategory,
+  ) -> Filter<
+    notification_preferences::table,
+    And<
+      Eq<notification_preferences::category, NotificationCategory>,
+      EqAny<notification_preferences::frequency, Vec<NotificationFrequency>>,
+    >,
+  > {
+    notification_preferences::table.filter(notification_preferences::category.eq(category).and(
+      notification_preferences::frequency.eq_any(vec![
+        NotificationFrequency::Hourly,
+        NotificationFrequency::Daily,
+        NotificationFrequency::Weekly,
+      ]),
+    ))
+  }
+}
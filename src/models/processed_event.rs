@@ -0,0 +1,37 @@
+use crate::schema::processed_events;
+use derive_builder::Builder;
+use diesel::dsl::{And, Eq, Filter};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+#[derive(Builder, Queryable, Identifiable, Clone)]
+#[builder(
+  derive(AsChangeset, Insertable),
+  pattern = "owned",
+  name = "ProcessedEventChangeset"
+)]
+#[builder_struct_attr(diesel(table_name = processed_events))]
+pub struct ProcessedEvent {
+  pub id: Uuid,
+  pub event: String,
+  pub key: String,
+  pub created_at: chrono::NaiveDateTime,
+  pub updated_at: chrono::NaiveDateTime,
+}
+
+#[allow(clippy::type_complexity)]
+impl ProcessedEvent {
+  pub fn find_by_event_and_key(
+    event: &str,
+    key: &str,
+  ) -> Filter<
+    processed_events::table,
+    And<Eq<processed_events::event, String>, Eq<processed_events::key, String>>,
+  > {
+    processed_events::table.filter(
+      processed_events::event
+        .eq(event.to_string())
+        .and(processed_events::key.eq(key.to_string())),
+    )
+  }
+}
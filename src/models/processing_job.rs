@@ -0,0 +1,46 @@
+use crate::data_types::ProcessingJobKind;
+use crate::schema::processing_jobs;
+use derive_builder::Builder;
+use diesel::dsl::{Eq, Filter, FindBy};
+use diesel::helper_types::{Asc, Order};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+#[derive(Builder, Queryable, Identifiable, Clone)]
+#[builder(
+  derive(AsChangeset, Insertable),
+  pattern = "owned",
+  name = "ProcessingJobChangeset"
+)]
+#[builder_struct_attr(diesel(table_name = processing_jobs))]
+pub struct ProcessingJob {
+  pub id: Uuid,
+  pub recording_id: Uuid,
+  pub kind: ProcessingJobKind,
+  pub external_id: String,
+  pub progress: Option<i32>,
+  pub started_at: chrono::NaiveDateTime,
+  pub finished_at: Option<chrono::NaiveDateTime>,
+  pub created_at: chrono::NaiveDateTime,
+  pub updated_at: chrono::NaiveDateTime,
+}
+
+#[allow(clippy::type_complexity)]
+impl ProcessingJob {
+  pub fn find_by_external_id(
+    external_id: &str,
+  ) -> FindBy<processing_jobs::table, processing_jobs::external_id, String> {
+    processing_jobs::table.filter(processing_jobs::external_id.eq(external_id.to_string()))
+  }
+
+  pub fn filter_by_recording_id(
+    recording_id: &Uuid,
+  ) -> Order<
+    Filter<processing_jobs::table, Eq<processing_jobs::recording_id, Uuid>>,
+    Asc<processing_jobs::started_at>,
+  > {
+    processing_jobs::table
+      .filter(processing_jobs::recording_id.eq(*recording_id))
+      .order(processing_jobs::started_at.asc())
+  }
+}
@@ -1,18 +1,19 @@
-use crate::data_types::MediaState;
+use crate::data_types::{MediaState, MediaStateTransitionError};
 use crate::schema::{comments, recordings};
-use chrono::{Duration, NaiveDateTime, Utc};
+use chrono::{NaiveDateTime, Utc};
 use derive_builder::Builder;
 use diesel::dsl::{And, Eq, Filter, FindBy};
 use diesel::expression::SqlLiteral;
-use diesel::helper_types::{EqAny, GroupBy, Gt, LeftJoin, NotEq, On, Order, Select};
+use diesel::helper_types::{
+  Asc, EqAny, GroupBy, Gt, IntoBoxed, IsNull, LeftJoin, Lt, NeAny, NotEq, On, Order, Select,
+};
+use diesel::pg::Pg;
 use diesel::prelude::*;
 use diesel::sql_types::Bool;
 use schemars::JsonSchema;
 use serde::Serialize;
 use uuid::Uuid;
 
-use super::Digest;
-
 #[derive(Builder, Queryable, Identifiable, Clone, Serialize, JsonSchema)]
 #[builder(
   derive(AsChangeset, Insertable),
@@ -33,6 +34,18 @@ pub struct Recording {
   pub processed_video_key: Option<String>,
   pub state: MediaState,
   pub notes: String,
+  pub state_reason: Option<String>,
+  pub state_changed_at: NaiveDateTime,
+  pub playlist_key: Option<String>,
+  pub moderation_labels: Option<serde_json::Value>,
+  pub hidden_at: Option<NaiveDateTime>,
+  pub deleted_at: Option<NaiveDateTime>,
+  /// Id of the in-progress S3 multipart upload for `video_key`, if any.
+  pub upload_id: Option<String>,
+  /// Taxonomy keys of the game's map, the player's role and their hero.
+  pub map: Option<String>,
+  pub role: Option<String>,
+  pub hero: Option<String>,
 }
 
 #[derive(Queryable, Clone, Serialize, JsonSchema)]
@@ -42,53 +55,150 @@ pub struct RecordingWithCommentCount {
 }
 
 impl Recording {
+  pub fn transition_to(
+    &self,
+    state: MediaState,
+    reason: Option<String>,
+  ) -> Result<RecordingChangeset, MediaStateTransitionError> {
+    Ok(
+      RecordingChangeset::default()
+        .state(self.state.transition_to(state)?)
+        .state_reason(reason)
+        .state_changed_at(Utc::now().naive_utc()),
+    )
+  }
+
   #[allow(clippy::type_complexity)]
   pub fn filter_by_game_id(
     game_id: &str,
-  ) -> Select<
-    GroupBy<
-      LeftJoin<
-        Order<
-          Filter<
-            recordings::table,
-            And<Eq<recordings::state, MediaState>, Eq<recordings::game_id, String>>,
+    map: Option<String>,
+    role: Option<String>,
+    hero: Option<String>,
+    skill_levels: Option<Vec<i16>>,
+    excluded_user_ids: Vec<Uuid>,
+  ) -> IntoBoxed<
+    'static,
+    Select<
+      GroupBy<
+        LeftJoin<
+          Order<
+            Filter<
+              recordings::table,
+              And<
+                And<
+                  And<
+                    And<Eq<recordings::state, MediaState>, Eq<recordings::game_id, String>>,
+                    IsNull<recordings::hidden_at>,
+                  >,
+                  IsNull<recordings::deleted_at>,
+                >,
+                NeAny<recordings::user_id, Vec<Uuid>>,
+              >,
+            >,
+            SqlLiteral<Bool>,
+          >,
+          On<
+            comments::table,
+            And<
+              And<Eq<comments::recording_id, recordings::id>, IsNull<comments::deleted_at>>,
+              IsNull<comments::hidden_at>,
+            >,
           >,
-          SqlLiteral<Bool>,
         >,
-        On<comments::table, Eq<comments::recording_id, recordings::id>>,
+        recordings::id,
       >,
-      recordings::id,
+      (
+        <recordings::table as diesel::Table>::AllColumns,
+        diesel::dsl::count<diesel::dsl::Nullable<comments::id>>,
+      ),
     >,
-    (
-      <recordings::table as diesel::Table>::AllColumns,
-      diesel::dsl::count<diesel::dsl::Nullable<comments::id>>,
-    ),
+    Pg,
   > {
-    recordings::table
+    let mut query = recordings::table
       .filter(
         recordings::state
           .eq(MediaState::Processed)
-          .and(recordings::game_id.eq(game_id.to_string())),
+          .and(recordings::game_id.eq(game_id.to_string()))
+          .and(recordings::hidden_at.is_null())
+          .and(recordings::deleted_at.is_null())
+          .and(recordings::user_id.ne_all(excluded_user_ids)),
       )
       .order(diesel::dsl::sql::<Bool>("created_at desc"))
-      .left_join(comments::table.on(comments::recording_id.eq(recordings::id)))
+      .left_join(
+        comments::table.on(
+          comments::recording_id
+            .eq(recordings::id)
+            .and(comments::deleted_at.is_null())
+            .and(comments::hidden_at.is_null()),
+        ),
+      )
       .group_by(recordings::id)
       .select((
         recordings::all_columns,
         diesel::dsl::count(comments::id.nullable()),
       ))
+      .into_boxed();
+
+    if let Some(map) = map {
+      query = query.filter(recordings::map.eq(map));
+    }
+
+    if let Some(role) = role {
+      query = query.filter(recordings::role.eq(role));
+    }
+
+    if let Some(hero) = hero {
+      query = query.filter(recordings::hero.eq(hero));
+    }
+
+    if let Some(skill_levels) = skill_levels {
+      query = query.filter(recordings::skill_level.eq_any(skill_levels));
+    }
+
+    query
   }
 
+  /// Skips deleted recordings, whose media is only waiting to be removed.
   pub fn find_by_video_key(
     video_key: &str,
-  ) -> FindBy<recordings::table, recordings::video_key, String> {
-    recordings::table.filter(recordings::video_key.eq(video_key.to_string()))
+  ) -> Filter<
+    recordings::table,
+    And<Eq<recordings::video_key, String>, IsNull<recordings::deleted_at>>,
+  > {
+    recordings::table.filter(
+      recordings::video_key
+        .eq(video_key.to_string())
+        .and(recordings::deleted_at.is_null()),
+    )
   }
 
   pub fn find_by_id(id: &Uuid) -> FindBy<recordings::table, recordings::id, Uuid> {
     recordings::table.filter(recordings::id.eq(*id))
   }
 
+  pub fn find_undeleted_by_id(
+    id: &Uuid,
+  ) -> Filter<recordings::table, And<Eq<recordings::id, Uuid>, IsNull<recordings::deleted_at>>> {
+    recordings::table.filter(recordings::id.eq(*id).and(recordings::deleted_at.is_null()))
+  }
+
+  #[allow(clippy::type_complexity)]
+  pub fn filter_flagged() -> Order<
+    Filter<
+      recordings::table,
+      And<Eq<recordings::state, MediaState>, IsNull<recordings::deleted_at>>,
+    >,
+    Asc<recordings::state_changed_at>,
+  > {
+    recordings::table
+      .filter(
+        recordings::state
+          .eq(MediaState::Flagged)
+          .and(recordings::deleted_at.is_null()),
+      )
+      .order(recordings::state_changed_at.asc())
+  }
+
   #[allow(clippy::type_complexity)]
   pub fn find_for_user(
     user_id: &Uuid,
@@ -101,21 +211,51 @@ impl Recording {
   pub fn find_processed_for_user(
     user_id: &Uuid,
     id: &Uuid,
+  ) -> Filter<
+    recordings::table,
+    And<
+      And<
+        And<Eq<recordings::id, Uuid>, Eq<recordings::user_id, Uuid>>,
+        Eq<recordings::state, MediaState>,
+      >,
+      IsNull<recordings::deleted_at>,
+    >,
+  > {
+    recordings::table.filter(
+      recordings::id
+        .eq(*id)
+        .and(recordings::user_id.eq(*user_id))
+        .and(recordings::state.eq(MediaState::Processed))
+        .and(recordings::deleted_at.is_null()),
+    )
+  }
+
+  #[allow(clippy::type_complexity)]
+  pub fn find_deleted_for_user(
+    user_id: &Uuid,
+    id: &Uuid,
+    deleted_since: NaiveDateTime,
   ) -> Filter<
     recordings::table,
     And<
       And<Eq<recordings::id, Uuid>, Eq<recordings::user_id, Uuid>>,
-      Eq<recordings::state, MediaState>,
+      Gt<recordings::deleted_at, NaiveDateTime>,
     >,
   > {
     recordings::table.filter(
       recordings::id
         .eq(*id)
         .and(recordings::user_id.eq(*user_id))
-        .and(recordings::state.eq(MediaState::Processed)),
+        .and(recordings::deleted_at.gt(deleted_since)),
     )
   }
 
+  pub fn filter_deleted_before(
+    cutoff: NaiveDateTime,
+  ) -> Filter<recordings::table, Lt<recordings::deleted_at, NaiveDateTime>> {
+    recordings::table.filter(recordings::deleted_at.lt(cutoff))
+  }
+
   #[allow(clippy::type_complexity)]
   pub fn filter_for_user(
     user_id: &Uuid,
@@ -125,11 +265,20 @@ impl Recording {
         Order<
           Filter<
             recordings::table,
-            And<NotEq<recordings::state, MediaState>, Eq<recordings::user_id, Uuid>>,
+            And<
+              And<NotEq<recordings::state, MediaState>, Eq<recordings::user_id, Uuid>>,
+              IsNull<recordings::deleted_at>,
+            >,
           >,
           SqlLiteral<Bool>,
         >,
-        On<comments::table, Eq<comments::recording_id, recordings::id>>,
+        On<
+          comments::table,
+          And<
+            And<Eq<comments::recording_id, recordings::id>, IsNull<comments::deleted_at>>,
+            IsNull<comments::hidden_at>,
+          >,
+        >,
       >,
       recordings::id,
     >,
@@ -142,10 +291,18 @@ impl Recording {
       .filter(
         recordings::state
           .ne(MediaState::Created)
-          .and(recordings::user_id.eq(*user_id)),
+          .and(recordings::user_id.eq(*user_id))
+          .and(recordings::deleted_at.is_null()),
       )
       .order(diesel::dsl::sql::<Bool>("created_at desc"))
-      .left_join(comments::table.on(comments::recording_id.eq(recordings::id)))
+      .left_join(
+        comments::table.on(
+          comments::recording_id
+            .eq(recordings::id)
+            .and(comments::deleted_at.is_null())
+            .and(comments::hidden_at.is_null()),
+        ),
+      )
       .group_by(recordings::id)
       .select((
         recordings::all_columns,
@@ -155,24 +312,47 @@ impl Recording {
 
   #[allow(clippy::type_complexity)]
   pub fn filter_for_digest(
-    last_digest: Option<Digest>,
+    since: NaiveDateTime,
   ) -> Filter<
     recordings::table,
-    And<Gt<recordings::updated_at, NaiveDateTime>, Eq<recordings::state, MediaState>>,
+    And<
+      And<
+        And<Gt<recordings::updated_at, NaiveDateTime>, Eq<recordings::state, MediaState>>,
+        IsNull<recordings::hidden_at>,
+      >,
+      IsNull<recordings::deleted_at>,
+    >,
   > {
-    let last_digest_at = last_digest
-      .map(|digest| digest.created_at)
-      .unwrap_or_else(|| {
-        Utc::now()
-          .naive_utc()
-          .checked_sub_signed(Duration::days(1))
-          .unwrap()
-      });
-
     recordings::table.filter(
       recordings::updated_at
-        .gt(last_digest_at)
-        .and(recordings::state.eq(MediaState::Processed)),
+        .gt(since)
+        .and(recordings::state.eq(MediaState::Processed))
+        .and(recordings::hidden_at.is_null())
+        .and(recordings::deleted_at.is_null()),
+    )
+  }
+
+  /// Recordings of the given users that finished processing after `since`.
+  #[allow(clippy::type_complexity)]
+  pub fn filter_processed_since(
+    user_ids: Vec<Uuid>,
+    since: NaiveDateTime,
+  ) -> Filter<
+    recordings::table,
+    And<
+      And<
+        And<EqAny<recordings::user_id, Vec<Uuid>>, Eq<recordings::state, MediaState>>,
+        Gt<recordings::state_changed_at, NaiveDateTime>,
+      >,
+      IsNull<recordings::deleted_at>,
+    >,
+  > {
+    recordings::table.filter(
+      recordings::user_id
+        .eq_any(user_ids)
+        .and(recordings::state.eq(MediaState::Processed))
+        .and(recordings::state_changed_at.gt(since))
+        .and(recordings::deleted_at.is_null()),
     )
   }
 
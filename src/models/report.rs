@@ -0,0 +1,78 @@
+use crate::data_types::{ReportReason, ReportStatus};
+use crate::schema::reports;
+use derive_builder::Builder;
+use diesel::dsl::{And, Eq, Filter, FindBy};
+use diesel::helper_types::{Asc, Order};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+#[derive(Builder, Queryable, Identifiable, Clone)]
+#[builder(
+  derive(AsChangeset, Insertable),
+  pattern = "owned",
+  name = "ReportChangeset"
+)]
+#[builder_struct_attr(diesel(table_name = reports))]
+pub struct Report {
+  pub id: Uuid,
+  pub reporter_id: Uuid,
+  pub recording_id: Option<Uuid>,
+  pub comment_id: Option<Uuid>,
+  pub reason: ReportReason,
+  pub details: String,
+  pub status: ReportStatus,
+  pub resolved_at: Option<chrono::NaiveDateTime>,
+  pub created_at: chrono::NaiveDateTime,
+  pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Clone, Copy)]
+pub enum ReportTarget {
+  Recording(Uuid),
+  Comment(Uuid),
+}
+
+#[allow(clippy::type_complexity)]
+impl Report {
+  pub fn target(&self) -> ReportTarget {
+    match (self.recording_id, self.comment_id) {
+      (Some(recording_id), _) => ReportTarget::Recording(recording_id),
+      (None, Some(comment_id)) => ReportTarget::Comment(comment_id),
+      (None, None) => unreachable!("reports must reference a recording or a comment"),
+    }
+  }
+
+  pub fn find_by_id(id: &Uuid) -> FindBy<reports::table, reports::id, Uuid> {
+    reports::table.filter(reports::id.eq(*id))
+  }
+
+  pub fn filter_by_status(
+    status: ReportStatus,
+  ) -> Order<FindBy<reports::table, reports::status, ReportStatus>, Asc<reports::created_at>> {
+    reports::table
+      .filter(reports::status.eq(status))
+      .order(reports::created_at.asc())
+  }
+
+  pub fn filter_open_for_recording(
+    recording_id: &Uuid,
+  ) -> Filter<reports::table, And<Eq<reports::recording_id, Uuid>, Eq<reports::status, ReportStatus>>>
+  {
+    reports::table.filter(
+      reports::recording_id
+        .eq(*recording_id)
+        .and(reports::status.eq(ReportStatus::Open)),
+    )
+  }
+
+  pub fn filter_open_for_comment(
+    comment_id: &Uuid,
+  ) -> Filter<reports::table, And<Eq<reports::comment_id, Uuid>, Eq<reports::status, ReportStatus>>>
+  {
+    reports::table.filter(
+      reports::comment_id
+        .eq(*comment_id)
+        .and(reports::status.eq(ReportStatus::Open)),
+    )
+  }
+}
@@ -0,0 +1,147 @@
+use crate::schema::{review_scores, reviews, users};
+use derive_builder::Builder;
+use diesel::dsl::{And, Eq, Filter, FindBy};
+use diesel::helper_types::{Asc, EqAny, InnerJoin, IsNotNull, IsNull, On, Order, Select};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+pub const MIN_REVIEW_SCORE: i16 = 1;
+pub const MAX_REVIEW_SCORE: i16 = 5;
+
+#[derive(Builder, Queryable, Identifiable, Clone)]
+#[builder(
+  derive(AsChangeset, Insertable),
+  pattern = "owned",
+  name = "ReviewChangeset"
+)]
+#[builder_struct_attr(diesel(table_name = reviews))]
+pub struct Review {
+  pub id: Uuid,
+  pub recording_id: Uuid,
+  pub reviewer_id: Uuid,
+  pub body: String,
+  pub created_at: chrono::NaiveDateTime,
+  pub updated_at: chrono::NaiveDateTime,
+  pub rating: Option<i16>,
+  pub strengths: String,
+  pub weaknesses: String,
+  pub focus_area: String,
+  /// Set by the recording's owner on the review they found most useful.
+  pub accepted_at: Option<chrono::NaiveDateTime>,
+  pub helpful_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Builder, Queryable, Clone)]
+#[builder(derive(Insertable), pattern = "owned", name = "ReviewScoreChangeset")]
+#[builder_struct_attr(diesel(table_name = review_scores))]
+pub struct ReviewScore {
+  pub review_id: Uuid,
+  pub category: String,
+  pub score: i16,
+  pub comment: String,
+}
+
+#[allow(clippy::type_complexity)]
+impl Review {
+  pub fn find_by_id(id: &Uuid) -> FindBy<reviews::table, reviews::id, Uuid> {
+    reviews::table.filter(reviews::id.eq(*id))
+  }
+
+  pub fn filter_accepted_for_recording(
+    recording_id: &Uuid,
+  ) -> Filter<reviews::table, And<Eq<reviews::recording_id, Uuid>, IsNotNull<reviews::accepted_at>>>
+  {
+    reviews::table.filter(
+      reviews::recording_id
+        .eq(*recording_id)
+        .and(reviews::accepted_at.is_not_null()),
+    )
+  }
+
+  pub fn find_for_reviewer(
+    reviewer_id: &Uuid,
+    id: &Uuid,
+  ) -> Filter<reviews::table, And<Eq<reviews::id, Uuid>, Eq<reviews::reviewer_id, Uuid>>> {
+    reviews::table.filter(
+      reviews::id
+        .eq(*id)
+        .and(reviews::reviewer_id.eq(*reviewer_id)),
+    )
+  }
+
+  pub fn filter_by_recording_id(
+    recording_id: &Uuid,
+  ) -> Order<FindBy<reviews::table, reviews::recording_id, Uuid>, Asc<reviews::created_at>> {
+    reviews::table
+      .filter(reviews::recording_id.eq(*recording_id))
+      .order(reviews::created_at.asc())
+  }
+}
+
+#[allow(clippy::type_complexity)]
+impl ReviewScore {
+  pub fn filter_by_review_ids(
+    review_ids: Vec<Uuid>,
+  ) -> Filter<review_scores::table, EqAny<review_scores::review_id, Vec<Uuid>>> {
+    review_scores::table.filter(review_scores::review_id.eq_any(review_ids))
+  }
+
+  /// Scores that count towards a recording's aggregate, leaving out those
+  /// of shadow-banned reviewers.
+  pub fn filter_for_recording(
+    recording_id: &Uuid,
+  ) -> Select<
+    Filter<
+      InnerJoin<
+        InnerJoin<
+          review_scores::table,
+          On<reviews::table, Eq<review_scores::review_id, reviews::id>>,
+        >,
+        On<users::table, Eq<reviews::reviewer_id, users::id>>,
+      >,
+      And<Eq<reviews::recording_id, Uuid>, IsNull<users::shadow_banned_at>>,
+    >,
+    <review_scores::table as diesel::Table>::AllColumns,
+  > {
+    review_scores::table
+      .inner_join(reviews::table.on(review_scores::review_id.eq(reviews::id)))
+      .inner_join(users::table.on(reviews::reviewer_id.eq(users::id)))
+      .filter(
+        reviews::recording_id
+          .eq(*recording_id)
+          .and(users::shadow_banned_at.is_null()),
+      )
+      .select(review_scores::all_columns)
+  }
+
+  /// Same as `filter_for_recording`, for a page of recordings at once. Each
+  /// score comes with the id of its recording.
+  pub fn filter_for_recordings(
+    recording_ids: Vec<Uuid>,
+  ) -> Select<
+    Filter<
+      InnerJoin<
+        InnerJoin<
+          review_scores::table,
+          On<reviews::table, Eq<review_scores::review_id, reviews::id>>,
+        >,
+        On<users::table, Eq<reviews::reviewer_id, users::id>>,
+      >,
+      And<EqAny<reviews::recording_id, Vec<Uuid>>, IsNull<users::shadow_banned_at>>,
+    >,
+    (
+      reviews::recording_id,
+      <review_scores::table as diesel::Table>::AllColumns,
+    ),
+  > {
+    review_scores::table
+      .inner_join(reviews::table.on(review_scores::review_id.eq(reviews::id)))
+      .inner_join(users::table.on(reviews::reviewer_id.eq(users::id)))
+      .filter(
+        reviews::recording_id
+          .eq_any(recording_ids)
+          .and(users::shadow_banned_at.is_null()),
+      )
+      .select((reviews::recording_id, review_scores::all_columns))
+  }
+}
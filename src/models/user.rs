@@ -1,3 +1,4 @@
+use crate::data_types::UserRole;
 use crate::schema::followings;
 use crate::schema::users;
 use derive_builder::Builder;
@@ -5,7 +6,7 @@ use diesel::dsl::{Find, FindBy};
 use diesel::helper_types::DistinctOn;
 use diesel::helper_types::On;
 use diesel::helper_types::Select;
-use diesel::helper_types::{Eq, EqAny, Filter, InnerJoin};
+use diesel::helper_types::{Desc, Eq, EqAny, Filter, ILike, InnerJoin, IsNotNull, Or, Order};
 use diesel::prelude::*;
 use uuid::Uuid;
 
@@ -23,10 +24,29 @@ pub struct User {
   pub name: String,
   pub password: Option<String>,
   pub updated_at: chrono::NaiveDateTime,
-  pub emails_enabled: bool,
+  pub role: UserRole,
+  pub suspended_at: Option<chrono::NaiveDateTime>,
+  pub suspended_until: Option<chrono::NaiveDateTime>,
+  pub suspension_reason: Option<String>,
+  pub shadow_banned_at: Option<chrono::NaiveDateTime>,
 }
 
 impl User {
+  pub fn is_suspended(&self) -> bool {
+    self.suspended_at.is_some()
+      && self
+        .suspended_until
+        .map_or(true, |until| until > chrono::Utc::now().naive_utc())
+  }
+
+  pub fn is_shadow_banned(&self) -> bool {
+    self.shadow_banned_at.is_some()
+  }
+
+  pub fn filter_shadow_banned() -> Filter<users::table, IsNotNull<users::shadow_banned_at>> {
+    users::table.filter(users::shadow_banned_at.is_not_null())
+  }
+
   pub fn find_by_id(id: &Uuid) -> Find<users::table, Uuid> {
     users::table.find(*id)
   }
@@ -42,21 +62,35 @@ impl User {
   #[allow(clippy::type_complexity)]
   pub fn filter_for_digest() -> DistinctOn<
     Select<
-      Filter<
-        InnerJoin<users::table, On<followings::table, Eq<users::id, followings::user_id>>>,
-        Eq<users::emails_enabled, bool>,
-      >,
+      InnerJoin<users::table, On<followings::table, Eq<users::id, followings::user_id>>>,
       <users::table as diesel::Table>::AllColumns,
     >,
     users::id,
   > {
     users::table
       .inner_join(followings::table.on(users::id.eq(followings::user_id)))
-      .filter(users::emails_enabled.eq(true))
       .select(users::all_columns)
       .distinct_on(users::id)
   }
 
+  #[allow(clippy::type_complexity)]
+  pub fn filter_by_query(
+    query: &str,
+  ) -> Order<
+    Filter<users::table, Or<ILike<users::name, String>, ILike<users::email, String>>>,
+    Desc<users::created_at>,
+  > {
+    let pattern = format!("%{}%", query);
+
+    users::table
+      .filter(
+        users::name
+          .ilike(pattern.clone())
+          .or(users::email.ilike(pattern)),
+      )
+      .order(users::created_at.desc())
+  }
+
   pub fn all() -> users::table {
     users::table
   }
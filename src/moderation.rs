@@ -0,0 +1,183 @@
+mod allow_all;
+mod rekognition;
+mod rules;
+use crate::config::Config;
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub use allow_all::AllowAllModeration;
+pub use rekognition::RekognitionModeration;
+pub use rules::{ModerationRule, RuleBasedModeration};
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+pub struct ModerationLabel {
+  pub name: String,
+  pub parent_name: Option<String>,
+  pub confidence: f64,
+  pub timestamp: Option<i64>,
+}
+
+impl ModerationLabel {
+  pub fn from_json(value: Option<serde_json::Value>) -> Vec<Self> {
+    value
+      .and_then(|value| serde_json::from_value(value).ok())
+      .unwrap_or_default()
+  }
+
+  pub fn category(&self) -> &str {
+    match &self.parent_name {
+      Some(parent_name) if !parent_name.is_empty() => parent_name,
+      _ => &self.name,
+    }
+  }
+}
+
+pub enum VideoModeration {
+  /// The provider will report back on the rekognition queue with this job id.
+  Pending(String),
+  Complete(Vec<ModerationLabel>),
+}
+
+#[async_trait]
+pub trait ModerationProvider: Send + Sync {
+  async fn moderate_image(&self, key: &str) -> Result<Vec<ModerationLabel>>;
+  async fn start_video_moderation(&self, key: &str) -> Result<VideoModeration>;
+  async fn video_moderation_result(&self, job_id: &str) -> Result<Vec<ModerationLabel>>;
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationProviderKind {
+  #[default]
+  Rekognition,
+  AllowAll,
+  Rules,
+}
+
+fn default_min_confidence() -> f64 {
+  50.0
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ModerationConfig {
+  #[serde(default)]
+  pub provider: ModerationProviderKind,
+  /// Labels below this confidence never cause a rejection.
+  #[serde(default = "default_min_confidence")]
+  pub min_confidence: f64,
+  /// Top level label categories that cause a rejection, mapped to the
+  /// confidence required for that category. When empty every category is
+  /// checked against `min_confidence`.
+  #[serde(default)]
+  pub categories: HashMap<String, f64>,
+  #[serde(default)]
+  pub rules: Vec<ModerationRule>,
+}
+
+impl Default for ModerationConfig {
+  fn default() -> Self {
+    Self {
+      provider: ModerationProviderKind::default(),
+      min_confidence: default_min_confidence(),
+      categories: HashMap::new(),
+      rules: vec![],
+    }
+  }
+}
+
+impl ModerationConfig {
+  pub fn flagged<'a>(&self, labels: &'a [ModerationLabel]) -> Vec<&'a ModerationLabel> {
+    labels
+      .iter()
+      .filter(|label| {
+        let threshold = if self.categories.is_empty() {
+          Some(self.min_confidence)
+        } else {
+          self
+            .categories
+            .get(label.category())
+            .map(|threshold| threshold.max(self.min_confidence))
+        };
+
+        threshold.map_or(false, |threshold| label.confidence >= threshold)
+      })
+      .collect()
+  }
+}
+
+pub fn provider(config: &Config) -> Box<dyn ModerationProvider> {
+  match config.moderation.provider {
+    ModerationProviderKind::Rekognition => Box::new(RekognitionModeration::new(config.clone())),
+    ModerationProviderKind::AllowAll => Box::new(AllowAllModeration),
+    ModerationProviderKind::Rules => {
+      Box::new(RuleBasedModeration::new(config.moderation.rules.clone()))
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn label(name: &str, parent_name: Option<&str>, confidence: f64) -> ModerationLabel {
+    ModerationLabel {
+      name: name.to_owned(),
+      parent_name: parent_name.map(|parent_name| parent_name.to_owned()),
+      confidence,
+      timestamp: None,
+    }
+  }
+
+  #[test]
+  fn category_falls_back_to_name() {
+    assert_eq!(label("Violence", None, 90.0).category(), "Violence");
+    assert_eq!(label("Violence", Some(""), 90.0).category(), "Violence");
+    assert_eq!(
+      label("Graphic Violence", Some("Violence"), 90.0).category(),
+      "Violence"
+    );
+  }
+
+  #[test]
+  fn flags_every_category_above_min_confidence() {
+    let config = ModerationConfig::default();
+    let labels = vec![
+      label("Violence", None, 49.9),
+      label("Explicit Nudity", None, 50.0),
+      label("Drugs", None, 80.0),
+    ];
+
+    assert_eq!(config.flagged(&labels), vec![&labels[1], &labels[2]]);
+  }
+
+  #[test]
+  fn flags_only_configured_categories() {
+    let config = ModerationConfig {
+      categories: HashMap::from([("Explicit Nudity".to_owned(), 70.0)]),
+      ..Default::default()
+    };
+
+    let labels = vec![
+      label("Graphic Violence", Some("Violence"), 99.0),
+      label("Nudity", Some("Explicit Nudity"), 69.0),
+      label("Sexual Activity", Some("Explicit Nudity"), 71.0),
+    ];
+
+    assert_eq!(config.flagged(&labels), vec![&labels[2]]);
+  }
+
+  #[test]
+  fn category_thresholds_never_go_below_min_confidence() {
+    let config = ModerationConfig {
+      min_confidence: 60.0,
+      categories: HashMap::from([("Violence".to_owned(), 10.0)]),
+      ..Default::default()
+    };
+
+    let labels = vec![label("Violence", None, 55.0), label("Violence", None, 65.0)];
+
+    assert_eq!(config.flagged(&labels), vec![&labels[1]]);
+  }
+}
@@ -0,0 +1,131 @@
+use super::{ModerationLabel, ModerationProvider, VideoModeration};
+use crate::aws::ConfigCredentialsProvider;
+use crate::config::Config;
+use anyhow::{anyhow, Result};
+use hyper_tls::HttpsConnector;
+use rusoto_core::HttpClient;
+use rusoto_rekognition::{
+  DetectModerationLabelsRequest, GetContentModerationRequest, Image, NotificationChannel,
+  Rekognition, RekognitionClient, S3Object, StartContentModerationRequest, Video,
+};
+use rusoto_signature::Region;
+
+pub struct RekognitionModeration {
+  config: Config,
+  client: RekognitionClient,
+}
+
+impl RekognitionModeration {
+  pub fn new(config: Config) -> Self {
+    let client = RekognitionClient::new_with(
+      HttpClient::from_connector(HttpsConnector::new()),
+      ConfigCredentialsProvider::new(config.clone()),
+      Region::EuWest2,
+    );
+
+    Self { config, client }
+  }
+
+  fn s3_object(&self, key: &str) -> S3Object {
+    S3Object {
+      bucket: Some(self.config.uploads_bucket.clone()),
+      name: Some(key.to_owned()),
+      ..Default::default()
+    }
+  }
+}
+
+fn moderation_label(
+  label: rusoto_rekognition::ModerationLabel,
+  timestamp: Option<i64>,
+) -> ModerationLabel {
+  ModerationLabel {
+    name: label.name.unwrap_or_default(),
+    parent_name: label.parent_name,
+    confidence: label.confidence.unwrap_or_default() as f64,
+    timestamp,
+  }
+}
+
+#[async_trait]
+impl ModerationProvider for RekognitionModeration {
+  async fn moderate_image(&self, key: &str) -> Result<Vec<ModerationLabel>> {
+    let response = self
+      .client
+      .detect_moderation_labels(DetectModerationLabelsRequest {
+        image: Image {
+          s3_object: Some(self.s3_object(key)),
+          ..Default::default()
+        },
+        ..Default::default()
+      })
+      .await?;
+
+    Ok(
+      response
+        .moderation_labels
+        .unwrap_or_default()
+        .into_iter()
+        .map(|label| moderation_label(label, None))
+        .collect(),
+    )
+  }
+
+  async fn start_video_moderation(&self, key: &str) -> Result<VideoModeration> {
+    let response = self
+      .client
+      .start_content_moderation(StartContentModerationRequest {
+        video: Video {
+          s3_object: Some(self.s3_object(key)),
+        },
+        notification_channel: Some(NotificationChannel {
+          role_arn: self.config.rekognition_role_arn.clone(),
+          sns_topic_arn: self.config.rekognition_topic_arn.clone(),
+        }),
+        ..Default::default()
+      })
+      .await?;
+
+    let job_id = response
+      .job_id
+      .ok_or_else(|| anyhow!("No job id returned for content moderation"))?;
+
+    Ok(VideoModeration::Pending(job_id))
+  }
+
+  async fn video_moderation_result(&self, job_id: &str) -> Result<Vec<ModerationLabel>> {
+    let mut labels = vec![];
+    let mut next_token = None;
+
+    loop {
+      let response = self
+        .client
+        .get_content_moderation(GetContentModerationRequest {
+          job_id: job_id.to_owned(),
+          next_token,
+          ..Default::default()
+        })
+        .await?;
+
+      labels.extend(
+        response
+          .moderation_labels
+          .unwrap_or_default()
+          .into_iter()
+          .filter_map(|detection| {
+            detection
+              .moderation_label
+              .map(|label| moderation_label(label, detection.timestamp))
+          }),
+      );
+
+      next_token = response.next_token;
+
+      if next_token.is_none() {
+        break;
+      }
+    }
+
+    Ok(labels)
+  }
+}
@@ -0,0 +1,88 @@
+use super::{ModerationLabel, ModerationProvider, VideoModeration};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Returns `label` for every object whose key contains `key_contains`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ModerationRule {
+  pub key_contains: String,
+  pub label: ModerationLabel,
+}
+
+pub struct RuleBasedModeration {
+  rules: Vec<ModerationRule>,
+}
+
+impl RuleBasedModeration {
+  pub fn new(rules: Vec<ModerationRule>) -> Self {
+    Self { rules }
+  }
+
+  fn labels(&self, key: &str) -> Vec<ModerationLabel> {
+    self
+      .rules
+      .iter()
+      .filter(|rule| key.contains(&rule.key_contains))
+      .map(|rule| rule.label.clone())
+      .collect()
+  }
+}
+
+#[async_trait]
+impl ModerationProvider for RuleBasedModeration {
+  async fn moderate_image(&self, key: &str) -> Result<Vec<ModerationLabel>> {
+    Ok(self.labels(key))
+  }
+
+  async fn start_video_moderation(&self, key: &str) -> Result<VideoModeration> {
+    Ok(VideoModeration::Complete(self.labels(key)))
+  }
+
+  async fn video_moderation_result(&self, job_id: &str) -> Result<Vec<ModerationLabel>> {
+    Err(anyhow::anyhow!(
+      "Rule based moderation has no pending job {}",
+      job_id
+    ))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rule(key_contains: &str, name: &str) -> ModerationRule {
+    ModerationRule {
+      key_contains: key_contains.to_owned(),
+      label: ModerationLabel {
+        name: name.to_owned(),
+        parent_name: None,
+        confidence: 99.0,
+        timestamp: None,
+      },
+    }
+  }
+
+  #[test]
+  fn returns_labels_of_matching_rules() {
+    let moderation = RuleBasedModeration::new(vec![
+      rule("gore", "Violence"),
+      rule("nsfw", "Explicit Nudity"),
+      rule("recordings/", "Recording"),
+    ]);
+
+    let names = moderation
+      .labels("recordings/originals/gore-nsfw")
+      .into_iter()
+      .map(|label| label.name)
+      .collect::<Vec<_>>();
+
+    assert_eq!(names, vec!["Violence", "Explicit Nudity", "Recording"]);
+  }
+
+  #[test]
+  fn returns_no_labels_without_a_match() {
+    let moderation = RuleBasedModeration::new(vec![rule("gore", "Violence")]);
+
+    assert!(moderation.labels("avatars/originals/clean").is_empty());
+  }
+}
@@ -0,0 +1,170 @@
+use crate::config::Config;
+use crate::data_types::{NotificationCategory, NotificationFrequency};
+use crate::guards::DbConn;
+use crate::models::{NotificationPreference, NotificationPreferenceChangeset};
+use crate::schema::notification_preferences;
+use chrono::{Duration, NaiveDateTime};
+use diesel::prelude::*;
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+const UNSUBSCRIBE_AUDIENCE: &str = "unsubscribe";
+
+/// How far back an immediate notification looks when it has never been sent,
+/// comfortably longer than the interval of the jobs sending them.
+const IMMEDIATE_LOOKBACK_HOURS: i64 = 1;
+
+/// A user's effective preference for one category.
+#[derive(Clone, Copy)]
+pub struct Setting {
+  pub frequency: NotificationFrequency,
+  pub last_sent_at: Option<NaiveDateTime>,
+}
+
+impl Setting {
+  pub fn is_due(&self, now: NaiveDateTime) -> bool {
+    match (self.frequency, self.frequency.interval(), self.last_sent_at) {
+      (NotificationFrequency::Off, _, _) => false,
+      (_, Some(interval), Some(last_sent_at)) => now - last_sent_at >= interval,
+      _ => true,
+    }
+  }
+
+  /// Start of the period the next email should cover. Capped to one interval
+  /// so switching frequency doesn't resend a backlog.
+  pub fn window_start(&self, now: NaiveDateTime) -> NaiveDateTime {
+    let lookback = self
+      .frequency
+      .interval()
+      .unwrap_or_else(|| Duration::hours(IMMEDIATE_LOOKBACK_HOURS));
+
+    self.last_sent_at.map_or(now - lookback, |last_sent_at| {
+      last_sent_at.max(now - lookback)
+    })
+  }
+}
+
+impl From<&NotificationPreference> for Setting {
+  fn from(preference: &NotificationPreference) -> Self {
+    Setting {
+      frequency: preference.frequency,
+      last_sent_at: preference.last_sent_at,
+    }
+  }
+}
+
+/// Loads the settings of the given users for a category, falling back to its
+/// default frequency.
+pub async fn settings(
+  db_conn: &DbConn,
+  category: NotificationCategory,
+  user_ids: Vec<Uuid>,
+) -> QueryResult<HashMap<Uuid, Setting>> {
+  let ids = user_ids.clone();
+
+  let preferences = db_conn
+    .run(move |conn| {
+      NotificationPreference::filter_by_category(category, ids).load::<NotificationPreference>(conn)
+    })
+    .await?;
+
+  let mut settings = user_ids
+    .into_iter()
+    .map(|user_id| {
+      let setting = Setting {
+        frequency: category.default_frequency(),
+        last_sent_at: None,
+      };
+
+      (user_id, setting)
+    })
+    .collect::<HashMap<_, _>>();
+
+  for preference in &preferences {
+    settings.insert(preference.user_id, Setting::from(preference));
+  }
+
+  Ok(settings)
+}
+
+/// Records that the given users were just emailed about a category.
+pub async fn mark_sent(
+  db_conn: &DbConn,
+  category: NotificationCategory,
+  user_ids: HashSet<Uuid>,
+  sent_at: NaiveDateTime,
+) -> QueryResult<()> {
+  db_conn
+    .run(move |conn| {
+      conn.transaction(|conn| {
+        for user_id in user_ids {
+          diesel::insert_into(notification_preferences::table)
+            .values(
+              NotificationPreferenceChangeset::default()
+                .user_id(user_id)
+                .category(category)
+                .frequency(category.default_frequency())
+                .last_sent_at(Some(sent_at)),
+            )
+            .on_conflict((
+              notification_preferences::user_id,
+              notification_preferences::category,
+            ))
+            .do_update()
+            .set(notification_preferences::last_sent_at.eq(sent_at))
+            .execute(conn)?;
+        }
+
+        Ok(())
+      })
+    })
+    .await
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnsubscribeClaims {
+  pub sub: Uuid,
+  /// Unsubscribes from every category when missing.
+  pub category: Option<NotificationCategory>,
+  aud: String,
+  iss: String,
+}
+
+/// Creates a token that turns off a category without signing in. It doesn't
+/// expire, since old emails should keep working.
+pub fn unsubscribe_token(
+  config: &Config,
+  user_id: Uuid,
+  category: Option<NotificationCategory>,
+) -> String {
+  let claims = UnsubscribeClaims {
+    sub: user_id,
+    category,
+    aud: UNSUBSCRIBE_AUDIENCE.to_owned(),
+    iss: config.host.clone(),
+  };
+
+  let key = EncodingKey::from_secret(config.auth_client_secret.as_ref());
+
+  encode(&Header::default(), &claims, &key).unwrap()
+}
+
+pub fn decode_unsubscribe_token(config: &Config, token: &str) -> Option<UnsubscribeClaims> {
+  let mut validation = Validation::new(Algorithm::HS256);
+
+  validation.set_issuer(&[config.host.clone()]);
+  validation.set_audience(&[UNSUBSCRIBE_AUDIENCE]);
+  validation.set_required_spec_claims(&["aud", "iss", "sub"]);
+  validation.validate_exp = false;
+
+  let token = decode::<UnsubscribeClaims>(
+    token,
+    &DecodingKey::from_secret(config.auth_client_secret.as_ref()),
+    &validation,
+  )
+  .ok()?;
+
+  Some(token.claims)
+}
@@ -1,4 +1,13 @@
+mod avatar_moderation;
+mod media_convert;
+mod notifications;
+mod processed_events;
+mod recording_moderation;
 mod rekognition;
 mod uploads;
+pub use avatar_moderation::{avatar_uploaded_event, reject_avatar, start_avatar_processing};
+pub use media_convert::MediaConvertHandler;
+pub use notifications::notify_media_owner;
+pub use recording_moderation::{fail_transcoding, reject_recording, transcode_recording};
 pub use rekognition::RekognitionHandler;
 pub use uploads::UploadsHandler;
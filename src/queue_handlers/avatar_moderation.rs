@@ -0,0 +1,92 @@
+use crate::aws::ConfigCredentialsProvider;
+use crate::config::Config;
+use crate::data_types::MediaState;
+use crate::guards::DbConn;
+use crate::models::Avatar;
+use crate::queue_handlers::notifications::notify_media_owner;
+use anyhow::Result;
+use diesel::prelude::*;
+use hyper_tls::HttpsConnector;
+use rusoto_core::HttpClient;
+use rusoto_lambda::{InvocationRequest, Lambda, LambdaClient};
+use rusoto_s3::{DeleteObjectRequest, S3Client, S3};
+use rusoto_signature::Region;
+use serde_json::json;
+
+/// Builds the S3 event the avatar processor expects for an upload that is not
+/// being handled straight off the uploads queue.
+pub fn avatar_uploaded_event(config: &Config, key: &str) -> String {
+  json!({
+    "Records": [{
+      "s3": {
+        "bucket": { "name": config.uploads_bucket },
+        "object": { "key": key },
+      },
+    }],
+  })
+  .to_string()
+}
+
+pub async fn start_avatar_processing(
+  db_conn: &DbConn,
+  config: &Config,
+  avatar: Avatar,
+  payload: String,
+) -> Result<()> {
+  let lambda = LambdaClient::new_with(
+    HttpClient::from_connector(HttpsConnector::new()),
+    ConfigCredentialsProvider::new(config.clone()),
+    Region::EuWest2,
+  );
+
+  lambda
+    .invoke(InvocationRequest {
+      function_name: config.avatar_processor_lambda_arn.clone(),
+      invocation_type: Some("Event".to_owned()),
+      payload: Some(payload.into_bytes().into()),
+      ..Default::default()
+    })
+    .await?;
+
+  let changeset = avatar.transition_to(MediaState::Transcoding, None)?;
+
+  db_conn
+    .run::<_, QueryResult<_>>(move |conn| diesel::update(&avatar).set(changeset).execute(conn))
+    .await?;
+
+  Ok(())
+}
+
+pub async fn reject_avatar(
+  db_conn: &DbConn,
+  config: &Config,
+  client: &S3Client,
+  avatar: Avatar,
+  reason: &str,
+) -> Result<()> {
+  let user_id = avatar.user_id;
+  let image_key = avatar.image_key.clone();
+  let changeset = avatar.transition_to(MediaState::Rejected, Some(reason.to_owned()))?;
+
+  db_conn
+    .run::<_, QueryResult<_>>(move |conn| diesel::update(&avatar).set(changeset).execute(conn))
+    .await?;
+
+  client
+    .delete_object(DeleteObjectRequest {
+      bucket: config.uploads_bucket.clone(),
+      key: image_key,
+      ..Default::default()
+    })
+    .await?;
+
+  notify_media_owner(
+    db_conn,
+    config,
+    user_id,
+    "Your avatar was rejected",
+    reason,
+    None,
+  )
+  .await
+}
@@ -0,0 +1,186 @@
+use crate::config::Config;
+use crate::data_types::ClipRenderState;
+use crate::fairings::sqs::QueueHandler;
+use crate::guards::DbConn;
+use crate::models::{Clip, ClipChangeset, ProcessingJob, ProcessingJobChangeset, Recording};
+use crate::queue_handlers::recording_moderation::fail_transcoding;
+use crate::schema::{clips, processing_jobs};
+use anyhow::Result;
+use chrono::Utc;
+use diesel::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MediaConvertJobProgress {
+  job_percent_complete: Option<i32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MediaConvertJobStateChange {
+  job_id: String,
+  status: String,
+  job_progress: Option<MediaConvertJobProgress>,
+  error_message: Option<String>,
+  #[serde(default)]
+  user_metadata: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct MediaConvertEvent {
+  detail: MediaConvertJobStateChange,
+}
+
+pub struct MediaConvertHandler {
+  db_conn: DbConn,
+  config: Config,
+}
+
+#[async_trait]
+impl QueueHandler for MediaConvertHandler {
+  fn name(&self) -> &'static str {
+    "media_convert"
+  }
+
+  fn new(db_conn: DbConn, config: Config) -> Self {
+    Self { db_conn, config }
+  }
+
+  fn url(&self) -> String {
+    self
+      .config
+      .media_convert_events_queue_url
+      .clone()
+      .unwrap_or_default()
+  }
+
+  async fn instance_id(&self, message: String) -> Result<Option<String>> {
+    let event = self.message_body(message)?;
+
+    Ok(event.detail.user_metadata.get("instance-id").cloned())
+  }
+
+  async fn handle(&self, message: String) -> Result<()> {
+    let event = self.message_body(message)?;
+    let job_id = event.detail.job_id.clone();
+
+    let job = self
+      .db_conn
+      .run(move |conn| {
+        ProcessingJob::find_by_external_id(&job_id)
+          .first::<ProcessingJob>(conn)
+          .optional()
+      })
+      .await?;
+
+    let job = match job {
+      Some(job) => job,
+      None => return self.handle_clip(event).await,
+    };
+
+    let job_id = job.id;
+    let recording_id = job.recording_id;
+
+    match event.detail.status.as_str() {
+      "PROGRESSING" | "STATUS_UPDATE" => {
+        let progress = match event
+          .detail
+          .job_progress
+          .and_then(|progress| progress.job_percent_complete)
+        {
+          Some(progress) => progress,
+          None => return Ok(()),
+        };
+
+        // Status updates can arrive late or out of order, so they never move
+        // a finished job or its progress backwards.
+        self
+          .db_conn
+          .run::<_, QueryResult<_>>(move |conn| {
+            diesel::update(processing_jobs::table.find(job_id))
+              .filter(processing_jobs::finished_at.is_null())
+              .filter(
+                processing_jobs::progress
+                  .is_null()
+                  .or(processing_jobs::progress.lt(progress)),
+              )
+              .set(ProcessingJobChangeset::default().progress(Some(progress)))
+              .execute(conn)
+          })
+          .await?;
+
+        return Ok(());
+      }
+      "COMPLETE" => {
+        self
+          .db_conn
+          .run::<_, QueryResult<_>>(move |conn| {
+            diesel::update(processing_jobs::table.find(job_id))
+              .filter(processing_jobs::finished_at.is_null())
+              .set(
+                ProcessingJobChangeset::default()
+                  .progress(Some(100))
+                  .finished_at(Some(Utc::now().naive_utc())),
+              )
+              .execute(conn)
+          })
+          .await?;
+
+        return Ok(());
+      }
+      "ERROR" | "CANCELED" => {}
+      _ => return Ok(()),
+    }
+
+    let recording = self
+      .db_conn
+      .run(move |conn| {
+        Recording::find_undeleted_by_id(&recording_id)
+          .first::<Recording>(conn)
+          .optional()
+      })
+      .await?;
+
+    let recording = match recording {
+      Some(recording) => recording,
+      None => return Ok(()),
+    };
+
+    error!(
+      "[media_convert] Job {} failed: {:?}",
+      event.detail.job_id, event.detail.error_message
+    );
+
+    fail_transcoding(&self.db_conn, &self.config, recording, event.detail.job_id).await
+  }
+}
+
+impl MediaConvertHandler {
+  /// Clip renders aren't tracked as processing jobs, so only their failures
+  /// need handling here. Finished clips are picked up by the uploads queue.
+  async fn handle_clip(&self, event: MediaConvertEvent) -> Result<()> {
+    if !["ERROR", "CANCELED"].contains(&event.detail.status.as_str()) {
+      return Ok(());
+    }
+
+    let job_id = event.detail.job_id;
+
+    self
+      .db_conn
+      .run::<_, QueryResult<_>>(move |conn| {
+        diesel::update(Clip::find_by_render_job_id(&job_id))
+          .filter(clips::render_state.eq(ClipRenderState::Pending))
+          .set(ClipChangeset::default().render_state(Some(ClipRenderState::Failed)))
+          .execute(conn)
+      })
+      .await?;
+
+    Ok(())
+  }
+
+  fn message_body(&self, message: String) -> Result<MediaConvertEvent> {
+    Ok(serde_json::from_str(&message)?)
+  }
+}
@@ -0,0 +1,67 @@
+use crate::config::Config;
+use crate::data_types::{NotificationCategory, NotificationFrequency};
+use crate::emails::{Email, Recipient};
+use crate::guards::DbConn;
+use crate::models::User;
+use crate::notifications;
+use anyhow::Result;
+use diesel::prelude::*;
+use serde_json::json;
+use uuid::Uuid;
+
+/// Tells the owner what happened to their upload. These count as processing
+/// notifications, but go out straight away unless the user turned them off.
+pub async fn notify_media_owner(
+  db_conn: &DbConn,
+  config: &Config,
+  user_id: Uuid,
+  subject: &str,
+  body: &str,
+  cta: Option<(&str, String)>,
+) -> Result<()> {
+  let user = db_conn
+    .run(move |conn| User::find_by_id(&user_id).first::<User>(conn))
+    .await?;
+
+  let settings = notifications::settings(
+    db_conn,
+    NotificationCategory::ProcessingComplete,
+    vec![user_id],
+  )
+  .await?;
+
+  if settings[&user_id].frequency == NotificationFrequency::Off {
+    return Ok(());
+  }
+
+  let mut template_data = json!({
+    "subject": subject,
+    "title": subject,
+    "body": body,
+  });
+
+  if let Some((cta, cta_url)) = cta {
+    template_data["cta"] = json!(cta);
+    template_data["cta_url"] = json!(cta_url);
+  }
+
+  let email = Email::new(
+    config,
+    "notification".to_owned(),
+    template_data,
+    vec![Recipient::new(
+      user.email.clone(),
+      json!({
+        "name": user.name.clone()
+      }),
+    )
+    .unsubscribable(config, user_id, NotificationCategory::ProcessingComplete)],
+  );
+
+  email
+    .deliver()
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to send media notification email: {}", e))?;
+
+  Ok(())
+}
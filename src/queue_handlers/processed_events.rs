@@ -0,0 +1,50 @@
+use crate::guards::DbConn;
+use crate::models::{ProcessedEvent, ProcessedEventChangeset};
+use crate::schema::processed_events;
+use anyhow::Result;
+use diesel::prelude::*;
+use std::future::Future;
+
+// S3 and SNS notifications are delivered at least once, so every handler with
+// side effects claims its (event, key) pair before running. The claim is
+// released if the handler fails so that the redelivered message can retry it.
+pub async fn process_once<F>(db_conn: &DbConn, event: &'static str, key: String, f: F) -> Result<()>
+where
+  F: Future<Output = Result<()>>,
+{
+  let claimed_key = key.clone();
+
+  let claimed = db_conn
+    .run(move |conn| {
+      diesel::insert_into(processed_events::table)
+        .values(
+          ProcessedEventChangeset::default()
+            .event(event.to_owned())
+            .key(claimed_key),
+        )
+        .on_conflict_do_nothing()
+        .execute(conn)
+    })
+    .await?
+    > 0;
+
+  if !claimed {
+    info!(
+      "[processed_events] {} for {} already processed, skipping",
+      event, key
+    );
+    return Ok(());
+  }
+
+  if let Err(e) = f.await {
+    db_conn
+      .run(move |conn| {
+        diesel::delete(ProcessedEvent::find_by_event_and_key(event, &key)).execute(conn)
+      })
+      .await?;
+
+    return Err(e);
+  }
+
+  Ok(())
+}
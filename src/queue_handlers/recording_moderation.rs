@@ -0,0 +1,233 @@
+use crate::config::Config;
+use crate::data_types::{MediaState, ProcessingJobKind};
+use crate::guards::DbConn;
+use crate::models::{ProcessingJob, ProcessingJobChangeset, Recording, RecordingChangeset};
+use crate::moderation::ModerationLabel;
+use crate::queue_handlers::notifications::notify_media_owner;
+use crate::schema::processing_jobs;
+use crate::transcoding::{profile_for_game, Transcoder};
+use anyhow::Result;
+use chrono::Utc;
+use diesel::prelude::*;
+use rusoto_s3::{DeleteObjectRequest, S3Client, S3};
+
+pub async fn complete_recording_moderation(
+  db_conn: &DbConn,
+  config: &Config,
+  transcoder: &dyn Transcoder,
+  recording: Recording,
+  labels: Vec<ModerationLabel>,
+) -> Result<()> {
+  let flagged = !config.moderation.flagged(&labels).is_empty();
+  let moderation_labels = serde_json::to_value(&labels)?;
+
+  let recording = db_conn
+    .run(move |conn| {
+      diesel::update(&recording)
+        .set(RecordingChangeset::default().moderation_labels(Some(moderation_labels)))
+        .get_result::<Recording>(conn)
+    })
+    .await?;
+
+  if flagged {
+    let changeset = recording.transition_to(
+      MediaState::Flagged,
+      Some("Your VOD is being reviewed by our moderation team.".to_owned()),
+    )?;
+
+    db_conn
+      .run::<_, QueryResult<_>>(move |conn| diesel::update(&recording).set(changeset).execute(conn))
+      .await?;
+
+    return Ok(());
+  }
+
+  start_transcoding(db_conn, config, transcoder, recording).await
+}
+
+pub async fn start_transcoding(
+  db_conn: &DbConn,
+  config: &Config,
+  transcoder: &dyn Transcoder,
+  recording: Recording,
+) -> Result<()> {
+  // The recording moves to transcoding before the job starts, since its
+  // outputs can reach the uploads queue before the job is recorded.
+  let changeset = recording.transition_to(MediaState::Transcoding, None)?;
+
+  let recording = db_conn
+    .run(move |conn| {
+      diesel::update(&recording)
+        .set(changeset)
+        .get_result::<Recording>(conn)
+    })
+    .await?;
+
+  transcode_recording(db_conn, config, transcoder, recording).await?;
+
+  Ok(())
+}
+
+/// Starts the job for a recording that has already moved to transcoding and
+/// returns whether it started. Recordings whose job can't be started are
+/// failed and their owner is told.
+pub async fn transcode_recording(
+  db_conn: &DbConn,
+  config: &Config,
+  transcoder: &dyn Transcoder,
+  recording: Recording,
+) -> Result<bool> {
+  let job_id = match start_job(db_conn, config, transcoder, &recording).await {
+    Ok(job_id) => job_id,
+    Err(e) => {
+      error!("[transcoding] Failed to start transcoding: {:?}", e);
+      sentry::capture_error(e.root_cause());
+
+      let user_id = recording.user_id;
+      let recording_id = recording.id;
+      let reason = "We couldn't process your VOD. Please try uploading it again.";
+      let changeset = recording.transition_to(MediaState::Failed, Some(reason.to_owned()))?;
+
+      db_conn
+        .run::<_, QueryResult<_>>(move |conn| {
+          diesel::update(&recording).set(changeset).execute(conn)
+        })
+        .await?;
+
+      notify_media_owner(
+        db_conn,
+        config,
+        user_id,
+        "Your VOD could not be processed",
+        reason,
+        Some((
+          "View VOD",
+          format!("{}/recordings/{}", config.web_host, recording_id),
+        )),
+      )
+      .await?;
+
+      return Ok(false);
+    }
+  };
+
+  let recording_id = recording.id;
+
+  db_conn
+    .run::<_, QueryResult<_>>(move |conn| {
+      diesel::insert_into(processing_jobs::table)
+        .values(
+          ProcessingJobChangeset::default()
+            .recording_id(recording_id)
+            .kind(ProcessingJobKind::Transcoding)
+            .external_id(job_id),
+        )
+        .execute(conn)
+    })
+    .await?;
+
+  Ok(true)
+}
+
+async fn start_job(
+  db_conn: &DbConn,
+  config: &Config,
+  transcoder: &dyn Transcoder,
+  recording: &Recording,
+) -> Result<String> {
+  let video_key = recording
+    .video_key
+    .clone()
+    .ok_or_else(|| anyhow::anyhow!("No video key found for recording"))?;
+
+  let profile = profile_for_game(db_conn, config, &recording.game_id)
+    .await?
+    .ok_or_else(|| anyhow::anyhow!("No transcoding profile found"))?;
+
+  transcoder.transcode(profile, &video_key).await
+}
+
+/// Finishes a transcoding job that errored or was canceled and fails its
+/// recording, unless the recording has already moved on.
+pub async fn fail_transcoding(
+  db_conn: &DbConn,
+  config: &Config,
+  recording: Recording,
+  job_id: String,
+) -> Result<()> {
+  db_conn
+    .run::<_, QueryResult<_>>(move |conn| {
+      diesel::update(ProcessingJob::find_by_external_id(&job_id))
+        .filter(processing_jobs::finished_at.is_null())
+        .set(ProcessingJobChangeset::default().finished_at(Some(Utc::now().naive_utc())))
+        .execute(conn)
+    })
+    .await?;
+
+  if !recording.state.can_transition_to(MediaState::Failed) {
+    return Ok(());
+  }
+
+  let user_id = recording.user_id;
+  let recording_id = recording.id;
+  let reason = "We couldn't process your VOD. Please try uploading it again.";
+  let changeset = recording.transition_to(MediaState::Failed, Some(reason.to_owned()))?;
+
+  db_conn
+    .run::<_, QueryResult<_>>(move |conn| diesel::update(&recording).set(changeset).execute(conn))
+    .await?;
+
+  notify_media_owner(
+    db_conn,
+    config,
+    user_id,
+    "Your VOD could not be processed",
+    reason,
+    Some((
+      "View VOD",
+      format!("{}/recordings/{}", config.web_host, recording_id),
+    )),
+  )
+  .await
+}
+
+pub async fn reject_recording(
+  db_conn: &DbConn,
+  config: &Config,
+  client: &S3Client,
+  recording: Recording,
+  state: MediaState,
+  reason: &str,
+) -> Result<()> {
+  let recording_id = recording.id;
+  let user_id = recording.user_id;
+  let video_key = recording.video_key.clone();
+  let changeset = recording.transition_to(state, Some(reason.to_owned()))?;
+
+  db_conn
+    .run::<_, QueryResult<_>>(move |conn| diesel::update(&recording).set(changeset).execute(conn))
+    .await?;
+
+  if let Some(video_key) = video_key {
+    let delete_object_params = DeleteObjectRequest {
+      bucket: config.uploads_bucket.clone(),
+      key: video_key,
+      ..Default::default()
+    };
+
+    client.delete_object(delete_object_params).await?;
+  }
+
+  notify_media_owner(
+    db_conn,
+    config,
+    user_id,
+    "Your VOD could not be processed",
+    reason,
+    Some((
+      "View VOD",
+      format!("{}/recordings/{}", config.web_host, recording_id),
+    )),
+  )
+  .await
+}
@@ -1,8 +1,11 @@
 mod handlers;
+use crate::aws::s3::original_key;
 use crate::aws::ConfigCredentialsProvider;
 use crate::config::Config;
 use crate::fairings::sqs::QueueHandler;
 use crate::guards::DbConn;
+use crate::moderation::{self, ModerationProvider};
+use crate::transcoding::{transcoder, Transcoder};
 use anyhow::{anyhow, Result};
 use hyper_tls::HttpsConnector;
 use rusoto_core::HttpClient;
@@ -14,6 +17,7 @@ use self::handlers::{
   handle_avatar_processed, handle_avatar_uploaded, handle_recording_processed,
   handle_recording_uploaded,
 };
+use super::processed_events::process_once;
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -43,6 +47,8 @@ pub struct UploadsHandler {
   db_conn: DbConn,
   config: Config,
   client: S3Client,
+  moderation: Box<dyn ModerationProvider>,
+  transcoder: Box<dyn Transcoder>,
 }
 
 #[async_trait]
@@ -58,10 +64,15 @@ impl QueueHandler for UploadsHandler {
       Region::EuWest2,
     );
 
+    let moderation = moderation::provider(&config);
+    let transcoder = transcoder(&config);
+
     Self {
       db_conn,
       config,
       client,
+      moderation,
+      transcoder,
     }
   }
 
@@ -102,13 +113,37 @@ impl QueueHandler for UploadsHandler {
       let original_key = self.original_key(key.clone())?;
 
       if key.starts_with("avatars/originals/") {
-        handle_avatar_uploaded(self, key, message.clone()).await?;
+        process_once(
+          &self.db_conn,
+          "avatar_uploaded",
+          key.clone(),
+          handle_avatar_uploaded(self, key, message.clone()),
+        )
+        .await?;
       } else if key.starts_with("recordings/originals/") {
-        handle_recording_uploaded(self, key).await?;
+        process_once(
+          &self.db_conn,
+          "recording_uploaded",
+          key.clone(),
+          handle_recording_uploaded(self, key),
+        )
+        .await?;
       } else if key.starts_with("avatars/processed/") {
-        handle_avatar_processed(self, key, original_key).await?;
-      } else if key.starts_with("recordings/processed/") {
-        handle_recording_processed(self, key, original_key).await?;
+        process_once(
+          &self.db_conn,
+          "avatar_processed",
+          key.clone(),
+          handle_avatar_processed(self, key, original_key),
+        )
+        .await?;
+      } else if key.starts_with("recordings/processed/") && !key.ends_with(".ts") {
+        process_once(
+          &self.db_conn,
+          "recording_processed",
+          key.clone(),
+          handle_recording_processed(self, key, original_key),
+        )
+        .await?;
       }
     }
 
@@ -118,11 +153,7 @@ impl QueueHandler for UploadsHandler {
 
 impl UploadsHandler {
   fn original_key(&self, key: String) -> Result<String> {
-    let original_key = key.replace("/processed", "/originals");
-    let original_key = original_key.split('_').next().unwrap_or_default();
-    let original_key = original_key.split('.').next().unwrap_or_default();
-
-    Ok(original_key.to_string())
+    Ok(original_key(&key))
   }
 
   fn message_body(&self, message: String) -> Result<S3Event> {
@@ -1,5 +1,5 @@
 use crate::data_types::MediaState;
-use crate::models::{Avatar, AvatarChangeset};
+use crate::models::Avatar;
 use crate::queue_handlers::UploadsHandler;
 use anyhow::Result;
 use diesel::prelude::*;
@@ -16,6 +16,10 @@ pub async fn handle_avatar_processed(
 
   let user_id = avatar.user_id;
 
+  let changeset = avatar
+    .transition_to(MediaState::Processed, None)?
+    .processed_image_key(Some(key));
+
   let previous_avatar = handler
     .db_conn
     .run(move |conn| {
@@ -47,13 +51,7 @@ pub async fn handle_avatar_processed(
           diesel::delete(Avatar::find_by_id(&previous_avatar_id)).execute(conn)?;
         }
 
-        diesel::update(&avatar)
-          .set(
-            AvatarChangeset::default()
-              .state(MediaState::Processed)
-              .processed_image_key(Some(key)),
-          )
-          .execute(conn)?;
+        diesel::update(&avatar).set(changeset).execute(conn)?;
 
         Ok(())
       })
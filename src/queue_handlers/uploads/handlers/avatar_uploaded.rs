@@ -1,17 +1,10 @@
-use crate::aws::ConfigCredentialsProvider;
 use crate::data_types::MediaState;
 use crate::models::{Avatar, AvatarChangeset};
+use crate::queue_handlers::avatar_moderation::{reject_avatar, start_avatar_processing};
 use crate::queue_handlers::UploadsHandler;
 use anyhow::Result;
 use diesel::prelude::*;
-use hyper_tls::HttpsConnector;
-use rusoto_core::HttpClient;
-use rusoto_lambda::{InvocationRequest, Lambda, LambdaClient};
-use rusoto_rekognition::{
-  DetectModerationLabelsRequest, Image, Rekognition, RekognitionClient, S3Object,
-};
 use rusoto_s3::{HeadObjectRequest, S3};
-use rusoto_signature::Region;
 
 pub async fn handle_avatar_uploaded(
   handler: &UploadsHandler,
@@ -20,18 +13,6 @@ pub async fn handle_avatar_uploaded(
 ) -> Result<()> {
   let config = &handler.config;
 
-  let lambda = LambdaClient::new_with(
-    HttpClient::from_connector(HttpsConnector::new()),
-    ConfigCredentialsProvider::new(config.clone()),
-    Region::EuWest2,
-  );
-
-  let rekognition = RekognitionClient::new_with(
-    HttpClient::from_connector(HttpsConnector::new()),
-    ConfigCredentialsProvider::new(config.clone()),
-    Region::EuWest2,
-  );
-
   let image_key = key.clone();
 
   let object = handler
@@ -48,14 +29,18 @@ pub async fn handle_avatar_uploaded(
     .run(move |conn| Avatar::find_by_image_key(&image_key).first::<Avatar>(conn))
     .await?;
 
-  let avatar_to_update = avatar.clone();
+  if !avatar.state.can_transition_to(MediaState::Uploaded) {
+    return Ok(());
+  }
 
-  handler
+  let changeset = avatar.transition_to(MediaState::Uploaded, None)?;
+
+  let avatar = handler
     .db_conn
-    .run::<_, QueryResult<_>>(move |conn| {
-      diesel::update(&avatar_to_update)
-        .set(AvatarChangeset::default().state(MediaState::Uploaded))
-        .execute(conn)
+    .run(move |conn| {
+      diesel::update(&avatar)
+        .set(changeset)
+        .get_result::<Avatar>(conn)
     })
     .await?;
 
@@ -64,39 +49,53 @@ pub async fn handle_avatar_uploaded(
     .ok_or_else(|| anyhow::anyhow!("No content type found for object"))?;
 
   if !["image/jpeg", "image/png"].contains(&content_type.as_str()) {
-    return handler.delete_upload(key).await;
+    return reject_avatar(
+      &handler.db_conn,
+      config,
+      &handler.client,
+      avatar,
+      "The image must be a JPEG or PNG file.",
+    )
+    .await;
   }
 
-  let moderation_labels = rekognition
-    .detect_moderation_labels(DetectModerationLabelsRequest {
-      image: Image {
-        s3_object: Some(S3Object {
-          bucket: Some(config.uploads_bucket.clone()),
-          name: Some(key.clone()),
-          ..Default::default()
-        }),
-        ..Default::default()
-      },
-      ..Default::default()
+  let changeset = avatar.transition_to(MediaState::Moderating, None)?;
+
+  let avatar = handler
+    .db_conn
+    .run(move |conn| {
+      diesel::update(&avatar)
+        .set(changeset)
+        .get_result::<Avatar>(conn)
     })
     .await?;
 
-  if let Some(moderation_labels) = moderation_labels.moderation_labels {
-    if !moderation_labels.is_empty() {
-      return handler.delete_upload(key).await;
-    }
-  }
-
-  let avatar_processor_lambda_arn = config.avatar_processor_lambda_arn.clone();
+  let labels = handler.moderation.moderate_image(&key).await?;
+  let flagged = !config.moderation.flagged(&labels).is_empty();
+  let moderation_labels = serde_json::to_value(&labels)?;
 
-  lambda
-    .invoke(InvocationRequest {
-      function_name: avatar_processor_lambda_arn,
-      invocation_type: Some("Event".to_owned()),
-      payload: Some(message.into_bytes().into()),
-      ..Default::default()
+  let avatar = handler
+    .db_conn
+    .run(move |conn| {
+      diesel::update(&avatar)
+        .set(AvatarChangeset::default().moderation_labels(Some(moderation_labels)))
+        .get_result::<Avatar>(conn)
     })
     .await?;
 
-  Ok(())
+  if flagged {
+    let changeset = avatar.transition_to(
+      MediaState::Flagged,
+      Some("Your avatar is being reviewed by our moderation team.".to_owned()),
+    )?;
+
+    handler
+      .db_conn
+      .run::<_, QueryResult<_>>(move |conn| diesel::update(&avatar).set(changeset).execute(conn))
+      .await?;
+
+    return Ok(());
+  }
+
+  start_avatar_processing(&handler.db_conn, config, avatar, message).await
 }
@@ -1,10 +1,16 @@
-use crate::data_types::MediaState;
+use crate::data_types::{ClipRenderState, MediaState, NotificationCategory, NotificationFrequency};
 use crate::emails::{Email, Recipient};
-use crate::models::{Recording, RecordingChangeset, User};
+use crate::models::{Clip, ClipChangeset, Recording, RecordingChangeset, User};
+use crate::notifications;
 use crate::queue_handlers::UploadsHandler;
+use crate::schema::clips;
+use crate::transcoding::{CLIP_NAME_MODIFIER, THUMBNAIL_NAME_MODIFIER, VIDEO_NAME_MODIFIER};
 use anyhow::Result;
+use chrono::Utc;
 use diesel::prelude::*;
 use serde_json::json;
+use std::collections::HashSet;
+use uuid::Uuid;
 
 pub async fn handle_recording_processed(
   handler: &UploadsHandler,
@@ -13,31 +19,69 @@ pub async fn handle_recording_processed(
 ) -> Result<()> {
   let recording = handler
     .db_conn
-    .run(move |conn| Recording::find_by_video_key(&original_key).first::<Recording>(conn))
+    .run(move |conn| {
+      Recording::find_by_video_key(&original_key)
+        .first::<Recording>(conn)
+        .optional()
+    })
     .await?;
 
-  if key.contains("_720p") {
-    let user_id = recording.user_id;
+  let recording = match recording {
+    Some(recording) => recording,
+    None => return Ok(()),
+  };
+
+  let file_name = key.rsplit('/').next().unwrap_or_default().to_owned();
+
+  if let Some(clip_id) = file_name.split(CLIP_NAME_MODIFIER).nth(1) {
+    let clip_id: Uuid = clip_id.trim_end_matches(".mp4").parse()?;
     let recording_id = recording.id;
 
     handler
       .db_conn
       .run::<_, QueryResult<_>>(move |conn| {
-        diesel::update(&recording)
+        diesel::update(Clip::find_by_id(&clip_id))
+          .filter(clips::recording_id.eq(recording_id))
+          .filter(clips::render_state.eq(ClipRenderState::Pending))
           .set(
-            RecordingChangeset::default()
-              .state(MediaState::Processed)
-              .processed_video_key(Some(key)),
+            ClipChangeset::default()
+              .render_state(Some(ClipRenderState::Rendered))
+              .video_key(Some(key)),
           )
           .execute(conn)
       })
       .await?;
+  } else if file_name.ends_with(&format!("{}.mp4", VIDEO_NAME_MODIFIER)) {
+    let user_id = recording.user_id;
+    let recording_id = recording.id;
+
+    let changeset = recording
+      .transition_to(MediaState::Processed, None)?
+      .processed_video_key(Some(key));
+
+    handler
+      .db_conn
+      .run::<_, QueryResult<_>>(move |conn| diesel::update(&recording).set(changeset).execute(conn))
+      .await?;
 
     let user = handler
       .db_conn
       .run(move |conn| User::find_by_id(&user_id).first::<User>(conn))
       .await?;
 
+    let settings = notifications::settings(
+      &handler.db_conn,
+      NotificationCategory::ProcessingComplete,
+      vec![user_id],
+    )
+    .await?;
+
+    // Batched frequencies are sent by the cron job instead, and nothing is
+    // sent when the category is off.
+    if settings[&user_id].frequency != NotificationFrequency::Immediate {
+      return Ok(());
+    }
+
     let email = Email::new(
       &handler.config,
       "notification".to_owned(),
@@ -53,6 +97,11 @@ pub async fn handle_recording_processed(
         json!({
           "name": user.name.clone()
         }),
+      )
+      .unsubscribable(
+        &handler.config,
+        user_id,
+        NotificationCategory::ProcessingComplete,
       )],
     );
 
@@ -60,7 +109,24 @@ pub async fn handle_recording_processed(
       .deliver()
       .await
       .map_err(|e| anyhow::anyhow!("Failed to send VOD processed email: {}", e))?;
-  } else if key.contains("_thumbnail") {
+
+    notifications::mark_sent(
+      &handler.db_conn,
+      NotificationCategory::ProcessingComplete,
+      HashSet::from([user_id]),
+      Utc::now().naive_utc(),
+    )
+    .await?;
+  } else if file_name.ends_with(".m3u8") && !file_name.contains('_') {
+    handler
+      .db_conn
+      .run::<_, QueryResult<_>>(move |conn| {
+        diesel::update(&recording)
+          .set(RecordingChangeset::default().playlist_key(Some(key)))
+          .execute(conn)
+      })
+      .await?;
+  } else if file_name.contains(THUMBNAIL_NAME_MODIFIER) {
     handler
       .db_conn
       .run::<_, QueryResult<_>>(move |conn| {
@@ -1,50 +1,114 @@
-use crate::aws::ConfigCredentialsProvider;
-use crate::data_types::MediaState;
-use crate::models::{Recording, RecordingChangeset};
+use crate::data_types::{MediaState, ProcessingJobKind};
+use crate::models::{ProcessingJobChangeset, Recording};
+use crate::moderation::VideoModeration;
+use crate::queue_handlers::recording_moderation::{
+  complete_recording_moderation, reject_recording,
+};
 use crate::queue_handlers::UploadsHandler;
+use crate::schema::processing_jobs;
+use crate::upload_limits::{validate_recording, FfprobeUnavailable};
 use anyhow::Result;
 use diesel::prelude::*;
-use hyper_tls::HttpsConnector;
-use rusoto_core::HttpClient;
-use rusoto_rekognition::{
-  NotificationChannel, Rekognition, RekognitionClient, S3Object, StartContentModerationRequest,
-  Video,
-};
-use rusoto_signature::Region;
 
 pub async fn handle_recording_uploaded(handler: &UploadsHandler, key: String) -> Result<()> {
   let config = &handler.config;
   let video_key = key.clone();
 
-  let rekognition = RekognitionClient::new_with(
-    HttpClient::from_connector(HttpsConnector::new()),
-    ConfigCredentialsProvider::new(config.clone()),
-    Region::EuWest2,
-  );
+  let recording = handler
+    .db_conn
+    .run(move |conn| {
+      Recording::find_by_video_key(&video_key)
+        .first::<Recording>(conn)
+        .optional()
+    })
+    .await?;
+
+  let recording = match recording {
+    Some(recording) if recording.state.can_transition_to(MediaState::Uploaded) => recording,
+    _ => return Ok(()),
+  };
 
-  handler
+  let changeset = recording.transition_to(MediaState::Uploaded, None)?;
+
+  let recording = handler
     .db_conn
-    .run::<_, QueryResult<_>>(move |conn| {
-      diesel::update(Recording::find_by_video_key(&video_key))
-        .set(RecordingChangeset::default().state(MediaState::Uploaded))
-        .execute(conn)
+    .run(move |conn| {
+      diesel::update(&recording)
+        .set(changeset)
+        .get_result::<Recording>(conn)
     })
     .await?;
 
-  rekognition
-    .start_content_moderation(StartContentModerationRequest {
-      video: Video {
-        s3_object: Some(S3Object {
-          bucket: Some(config.uploads_bucket.clone()),
-          name: Some(key.clone()),
-          ..Default::default()
-        }),
-      },
-      notification_channel: Some(NotificationChannel {
-        role_arn: config.rekognition_role_arn.clone(),
-        sns_topic_arn: config.rekognition_topic_arn.clone(),
-      }),
-      ..Default::default()
+  let rejection = match validate_recording(config, &handler.client, &key).await {
+    Ok(reason) => reason.map(|reason| (MediaState::Rejected, reason)),
+    // Retrying won't help until the deployment is fixed, so the recording
+    // is failed instead of the message being redelivered.
+    Err(e) if e.is::<FfprobeUnavailable>() => {
+      error!("[recording_uploaded] {}", e);
+      sentry::capture_error(e.root_cause());
+
+      Some((
+        MediaState::Failed,
+        "We couldn't process your VOD. Please try uploading it again.".to_owned(),
+      ))
+    }
+    Err(e) => return Err(e),
+  };
+
+  if let Some((state, reason)) = rejection {
+    return reject_recording(
+      &handler.db_conn,
+      config,
+      &handler.client,
+      recording,
+      state,
+      &reason,
+    )
+    .await;
+  }
+
+  let job_id = match handler.moderation.start_video_moderation(&key).await? {
+    VideoModeration::Pending(job_id) => job_id,
+    VideoModeration::Complete(labels) => {
+      let changeset = recording.transition_to(MediaState::Moderating, None)?;
+
+      let recording = handler
+        .db_conn
+        .run(move |conn| {
+          diesel::update(&recording)
+            .set(changeset)
+            .get_result::<Recording>(conn)
+        })
+        .await?;
+
+      return complete_recording_moderation(
+        &handler.db_conn,
+        config,
+        handler.transcoder.as_ref(),
+        recording,
+        labels,
+      )
+      .await;
+    }
+  };
+
+  let changeset = recording.transition_to(MediaState::Moderating, None)?;
+
+  handler
+    .db_conn
+    .run::<_, QueryResult<_>>(move |conn| {
+      conn.transaction(|conn| {
+        diesel::insert_into(processing_jobs::table)
+          .values(
+            ProcessingJobChangeset::default()
+              .recording_id(recording.id)
+              .kind(ProcessingJobKind::Moderation)
+              .external_id(job_id),
+          )
+          .execute(conn)?;
+
+        diesel::update(&recording).set(changeset).execute(conn)
+      })
     })
     .await?;
 
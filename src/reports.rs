@@ -0,0 +1,82 @@
+use crate::data_types::ReportStatus;
+use crate::models::{Comment, Recording, Report, ReportChangeset, ReportTarget};
+use crate::schema::{comments, recordings};
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+pub fn count_open(conn: &mut PgConnection, target: ReportTarget) -> QueryResult<i64> {
+  match target {
+    ReportTarget::Recording(id) => Report::filter_open_for_recording(&id)
+      .count()
+      .get_result(conn),
+    ReportTarget::Comment(id) => Report::filter_open_for_comment(&id)
+      .count()
+      .get_result(conn),
+  }
+}
+
+pub fn resolve(
+  conn: &mut PgConnection,
+  target: ReportTarget,
+  status: ReportStatus,
+) -> QueryResult<usize> {
+  let changeset = ReportChangeset::default()
+    .status(status)
+    .resolved_at(Some(Utc::now().naive_utc()));
+
+  match target {
+    ReportTarget::Recording(id) => diesel::update(Report::filter_open_for_recording(&id))
+      .set(changeset)
+      .execute(conn),
+    ReportTarget::Comment(id) => diesel::update(Report::filter_open_for_comment(&id))
+      .set(changeset)
+      .execute(conn),
+  }
+}
+
+pub fn hide(conn: &mut PgConnection, target: ReportTarget) -> QueryResult<usize> {
+  let now = Utc::now().naive_utc();
+
+  match target {
+    ReportTarget::Recording(id) => diesel::update(
+      recordings::table
+        .find(id)
+        .filter(recordings::hidden_at.is_null()),
+    )
+    .set(recordings::hidden_at.eq(now))
+    .execute(conn),
+    ReportTarget::Comment(id) => diesel::update(
+      comments::table
+        .find(id)
+        .filter(comments::hidden_at.is_null()),
+    )
+    .set(comments::hidden_at.eq(now))
+    .execute(conn),
+  }
+}
+
+pub fn unhide(conn: &mut PgConnection, target: ReportTarget) -> QueryResult<usize> {
+  let hidden_at: Option<NaiveDateTime> = None;
+
+  match target {
+    ReportTarget::Recording(id) => diesel::update(recordings::table.find(id))
+      .set(recordings::hidden_at.eq(hidden_at))
+      .execute(conn),
+    ReportTarget::Comment(id) => diesel::update(comments::table.find(id))
+      .set(comments::hidden_at.eq(hidden_at))
+      .execute(conn),
+  }
+}
+
+pub fn author_id(conn: &mut PgConnection, target: ReportTarget) -> QueryResult<Uuid> {
+  match target {
+    ReportTarget::Recording(id) => Recording::find_by_id(&id)
+      .first::<Recording>(conn)
+      .map(|recording| recording.user_id),
+    ReportTarget::Comment(id) => comments::table
+      .find(id)
+      .first::<Comment>(conn)
+      .map(|comment| comment.user_id),
+  }
+}
@@ -1,3 +1,4 @@
+use crate::guards::auth::Suspension;
 use okapi::openapi3::Responses;
 use rocket::http::Status;
 use rocket::response::Responder;
@@ -46,6 +47,21 @@ fn add_401_error(responses: &mut Responses) {
     });
 }
 
+fn add_403_error(responses: &mut Responses) {
+  responses
+    .responses
+    .entry("403".to_owned())
+    .or_insert_with(|| {
+      let response = okapi::openapi3::Response {
+        description:
+          "# [403 Forbidden](https://developer.mozilla.org/en-US/docs/Web/HTTP/Status/403)"
+            .to_owned(),
+        ..Default::default()
+      };
+      response.into()
+    });
+}
+
 fn add_404_error(responses: &mut Responses) {
   responses
     .responses
@@ -95,6 +111,8 @@ fn add_500_error(responses: &mut Responses) {
 pub enum MutationError {
   ValidationErrors(ValidationErrors),
   Status(Status),
+  /// A 403 the catcher explains with the suspension's end and reason.
+  Suspended(Suspension),
   InternalServerError(Box<dyn Error + Send + Sync>),
 }
 
@@ -136,6 +154,10 @@ impl<'r> Responder<'r, 'static> for MutationError {
   fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
     match self {
       MutationError::Status(status) => status.respond_to(req),
+      MutationError::Suspended(suspension) => {
+        req.local_cache(|| Some(suspension));
+        Status::Forbidden.respond_to(req)
+      }
       MutationError::ValidationErrors(errors) => {
         (Status::UnprocessableEntity, Json(errors)).respond_to(req)
       }
@@ -158,6 +180,7 @@ impl OpenApiResponderInner for MutationError {
     let mut responses = Responses::default();
     add_400_error(&mut responses);
     add_401_error(&mut responses);
+    add_403_error(&mut responses);
     add_404_error(&mut responses);
     add_422_error(&mut responses);
     add_500_error(&mut responses);
@@ -170,6 +193,7 @@ impl OpenApiResponderInner for QueryError {
     let mut responses = Responses::default();
     add_400_error(&mut responses);
     add_401_error(&mut responses);
+    add_403_error(&mut responses);
     add_404_error(&mut responses);
     add_500_error(&mut responses);
     Ok(responses)
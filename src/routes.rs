@@ -1,8 +1,15 @@
+pub mod admin;
 pub mod avatars;
+pub mod clips;
 pub mod comments;
 pub mod games;
 pub mod index;
+pub mod moderation;
+pub mod notification_preferences;
 pub mod passwords;
+pub mod recording_uploads;
 pub mod recordings;
+pub mod reports;
+pub mod reviews;
 pub mod sessions;
 pub mod users;
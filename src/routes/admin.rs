@@ -0,0 +1,972 @@
+use crate::audit;
+use crate::aws::s3::{delete_recording_objects, make_objects_private};
+use crate::config::Config;
+use crate::data_types::{AuditAction, AuditTargetType, TaxonomyKind, UserRole};
+use crate::garbage_collection::{self, GarbageCollectionReport};
+use crate::guards::{Admin, Auth, ClientIp, DbConn, S3};
+use crate::models::{
+  AuditEvent, Comment, Game, GameChangeset, ProcessingJob, Recording, ReviewCategory, SkillLevel,
+  SkillLevelChangeset, TaxonomyEntry, TaxonomyEntryChangeset, User, UserChangeset,
+};
+use crate::pagination::{Paginate, PaginatedResult};
+use crate::response::{MutationError, MutationResponse, QueryResponse, Response, StatusResponse};
+use crate::schema::{comments, games, skill_levels, taxonomy_entries};
+use crate::views::{AdminUserView, AuditEventView, GameView, RecordingProcessingView};
+use chrono::Utc;
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{tokio, State};
+use rocket_okapi::openapi;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashSet;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(FromForm, JsonSchema)]
+pub struct ListUsersParams {
+  page: Option<i64>,
+  query: Option<String>,
+}
+
+#[derive(FromForm, JsonSchema)]
+pub struct ListAuditEventsParams {
+  page: Option<i64>,
+  actor_id: Option<Uuid>,
+  target_id: Option<Uuid>,
+  action: Option<AuditAction>,
+}
+
+#[derive(FromForm, JsonSchema)]
+pub struct CollectGarbageParams {
+  dry_run: Option<bool>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct UpdateRoleRequest {
+  role: UserRole,
+}
+
+#[derive(Deserialize, JsonSchema, Validate)]
+#[validate(schema(function = "validate_suspend_user"))]
+pub struct SuspendUserRequest {
+  until: Option<chrono::NaiveDateTime>,
+  #[validate(length(min = 1))]
+  reason: String,
+}
+
+#[derive(Deserialize, JsonSchema, Validate)]
+#[validate(schema(function = "validate_create_game"))]
+pub struct CreateGameRequest {
+  /// Slug used in URLs and stored on recordings, e.g. `overwatch`.
+  #[validate(length(min = 1, max = 50))]
+  id: String,
+  #[validate(length(min = 1, max = 100))]
+  name: String,
+  transcoding_profile: String,
+  review_categories: Vec<ReviewCategory>,
+}
+
+#[derive(Deserialize, JsonSchema, Validate)]
+#[validate(schema(function = "validate_update_game"))]
+pub struct UpdateGameRequest {
+  #[validate(length(min = 1, max = 100))]
+  name: String,
+  transcoding_profile: String,
+  review_categories: Vec<ReviewCategory>,
+}
+
+#[derive(Deserialize, JsonSchema, Validate)]
+pub struct CreateSkillLevelRequest {
+  #[validate(length(min = 1, max = 100))]
+  name: String,
+  /// Where to insert the rank, shifting later ranks back. Appended when
+  /// omitted.
+  #[validate(range(min = 0))]
+  position: Option<i16>,
+}
+
+#[derive(Deserialize, JsonSchema, Validate)]
+pub struct UpdateSkillLevelRequest {
+  #[validate(length(min = 1, max = 100))]
+  name: String,
+  deprecated: bool,
+}
+
+#[derive(Deserialize, JsonSchema, Validate)]
+#[validate(schema(function = "validate_create_taxonomy_entry"))]
+pub struct CreateTaxonomyEntryRequest {
+  kind: TaxonomyKind,
+  #[validate(length(min = 1, max = 50))]
+  key: String,
+  #[validate(length(min = 1, max = 100))]
+  name: String,
+  /// Where to insert the entry among those of the same kind. Appended when
+  /// omitted.
+  #[validate(range(min = 0))]
+  position: Option<i16>,
+}
+
+#[derive(Deserialize, JsonSchema, Validate)]
+pub struct UpdateTaxonomyEntryRequest {
+  #[validate(length(min = 1, max = 100))]
+  name: String,
+  deprecated: bool,
+}
+
+fn is_slug(value: &str) -> bool {
+  value
+    .chars()
+    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn validate_create_taxonomy_entry(
+  entry: &CreateTaxonomyEntryRequest,
+) -> Result<(), validator::ValidationError> {
+  if !is_slug(&entry.key) {
+    return Err(validator::ValidationError::new("invalid"));
+  }
+
+  Ok(())
+}
+
+fn validate_review_categories(
+  categories: &[ReviewCategory],
+) -> Result<(), validator::ValidationError> {
+  let keys = categories
+    .iter()
+    .map(|category| category.key.as_str())
+    .collect::<HashSet<_>>();
+
+  if keys.len() != categories.len()
+    || categories
+      .iter()
+      .any(|category| category.key.is_empty() || category.name.is_empty())
+  {
+    return Err(validator::ValidationError::new("invalid"));
+  }
+
+  Ok(())
+}
+
+fn validate_create_game(game: &CreateGameRequest) -> Result<(), validator::ValidationError> {
+  if !is_slug(&game.id) {
+    return Err(validator::ValidationError::new("invalid"));
+  }
+
+  validate_review_categories(&game.review_categories)
+}
+
+fn validate_update_game(game: &UpdateGameRequest) -> Result<(), validator::ValidationError> {
+  validate_review_categories(&game.review_categories)
+}
+
+fn validate_suspend_user(body: &SuspendUserRequest) -> Result<(), validator::ValidationError> {
+  match body.until {
+    Some(until) if until <= Utc::now().naive_utc() => {
+      Err(validator::ValidationError::new("in_past"))
+    }
+    _ => Ok(()),
+  }
+}
+
+#[openapi(tag = "Admin")]
+#[get("/admin/users?<params..>")]
+pub async fn list_users(
+  #[allow(unused_variables)] auth: Auth<Admin>,
+  db_conn: DbConn,
+  params: ListUsersParams,
+) -> QueryResponse<PaginatedResult<AdminUserView>> {
+  let page = params.page.unwrap_or(1);
+  let query = params.query.unwrap_or_default();
+
+  let users = db_conn
+    .run(move |conn| {
+      User::filter_by_query(&query)
+        .paginate(page)
+        .load_and_count_pages::<User>(conn)
+    })
+    .await?;
+
+  let views = users
+    .records
+    .clone()
+    .into_iter()
+    .map(AdminUserView::from)
+    .collect();
+
+  Response::success(users.records(views))
+}
+
+#[openapi(tag = "Admin")]
+#[get("/admin/users/<id>")]
+pub async fn get_user(
+  id: Uuid,
+  #[allow(unused_variables)] auth: Auth<Admin>,
+  db_conn: DbConn,
+) -> QueryResponse<AdminUserView> {
+  let user = db_conn
+    .run(move |conn| User::find_by_id(&id).first::<User>(conn))
+    .await?;
+
+  Response::success(AdminUserView::from(user))
+}
+
+#[openapi(tag = "Admin")]
+#[put("/admin/users/<id>/role", data = "<body>")]
+pub async fn update_user_role(
+  id: Uuid,
+  body: Json<UpdateRoleRequest>,
+  auth: Auth<Admin>,
+  db_conn: DbConn,
+  ip: ClientIp,
+) -> MutationResponse<AdminUserView> {
+  let actor_id = auth.into_user().id;
+
+  if actor_id == id {
+    return Response::mutation_error(Status::UnprocessableEntity);
+  }
+
+  let role = body.role;
+  let event = audit::event(AuditAction::UserRoleChanged, Some(actor_id), &ip)
+    .target(AuditTargetType::User, id)
+    .payload(json!({ "role": role }));
+
+  let user = db_conn
+    .run(move |conn| {
+      conn.transaction(|conn| {
+        let user = diesel::update(User::find_by_id(&id))
+          .set(UserChangeset::default().role(role))
+          .get_result::<User>(conn)?;
+
+        audit::record(conn, event)?;
+
+        Ok::<_, diesel::result::Error>(user)
+      })
+    })
+    .await?;
+
+  Response::success(AdminUserView::from(user))
+}
+
+#[openapi(tag = "Admin")]
+#[post("/admin/users/<id>/suspend", data = "<body>")]
+pub async fn suspend_user(
+  id: Uuid,
+  body: Json<SuspendUserRequest>,
+  auth: Auth<Admin>,
+  db_conn: DbConn,
+  ip: ClientIp,
+) -> MutationResponse<AdminUserView> {
+  if let Err(errors) = body.validate() {
+    return Response::validation_error(errors);
+  }
+
+  let actor = auth.into_user();
+  let actor_id = actor.id;
+  let actor_role = actor.role;
+
+  if actor_id == id {
+    return Response::mutation_error(Status::UnprocessableEntity);
+  }
+
+  let event = audit::event(AuditAction::UserSuspended, Some(actor_id), &ip)
+    .target(AuditTargetType::User, id)
+    .payload(json!({ "until": body.until, "reason": body.reason }));
+
+  let user = db_conn
+    .run::<_, Result<_, MutationError>>(move |conn| {
+      conn.transaction(|conn| {
+        let user = User::find_by_id(&id).first::<User>(conn)?;
+
+        if !actor_role.outranks(user.role) {
+          return Err(MutationError::Status(Status::Forbidden));
+        }
+
+        let user = diesel::update(&user)
+          .set(
+            UserChangeset::default()
+              .suspended_at(Some(Utc::now().naive_utc()))
+              .suspended_until(body.until)
+              .suspension_reason(Some(body.reason.clone())),
+          )
+          .get_result::<User>(conn)?;
+
+        audit::record(conn, event)?;
+
+        Ok(user)
+      })
+    })
+    .await?;
+
+  Response::success(AdminUserView::from(user))
+}
+
+#[openapi(tag = "Admin")]
+#[post("/admin/users/<id>/unsuspend")]
+pub async fn unsuspend_user(
+  id: Uuid,
+  auth: Auth<Admin>,
+  db_conn: DbConn,
+  ip: ClientIp,
+) -> MutationResponse<AdminUserView> {
+  let event = audit::event(AuditAction::UserUnsuspended, Some(auth.into_user().id), &ip)
+    .target(AuditTargetType::User, id);
+
+  let user = db_conn
+    .run(move |conn| {
+      conn.transaction(|conn| {
+        let user = diesel::update(User::find_by_id(&id))
+          .set(
+            UserChangeset::default()
+              .suspended_at(None)
+              .suspended_until(None)
+              .suspension_reason(None),
+          )
+          .get_result::<User>(conn)?;
+
+        audit::record(conn, event)?;
+
+        Ok::<_, diesel::result::Error>(user)
+      })
+    })
+    .await?;
+
+  Response::success(AdminUserView::from(user))
+}
+
+#[openapi(tag = "Admin")]
+#[post("/admin/users/<id>/shadow_ban")]
+pub async fn shadow_ban_user(
+  id: Uuid,
+  auth: Auth<Admin>,
+  db_conn: DbConn,
+  ip: ClientIp,
+) -> MutationResponse<AdminUserView> {
+  let actor_id = auth.into_user().id;
+
+  if actor_id == id {
+    return Response::mutation_error(Status::UnprocessableEntity);
+  }
+
+  let event = audit::event(AuditAction::UserShadowBanned, Some(actor_id), &ip)
+    .target(AuditTargetType::User, id);
+
+  let user = db_conn
+    .run(move |conn| {
+      conn.transaction(|conn| {
+        let user = diesel::update(User::find_by_id(&id))
+          .set(UserChangeset::default().shadow_banned_at(Some(Utc::now().naive_utc())))
+          .get_result::<User>(conn)?;
+
+        audit::record(conn, event)?;
+
+        Ok::<_, diesel::result::Error>(user)
+      })
+    })
+    .await?;
+
+  Response::success(AdminUserView::from(user))
+}
+
+#[openapi(tag = "Admin")]
+#[delete("/admin/users/<id>/shadow_ban")]
+pub async fn unshadow_ban_user(
+  id: Uuid,
+  auth: Auth<Admin>,
+  db_conn: DbConn,
+  ip: ClientIp,
+) -> MutationResponse<AdminUserView> {
+  let event = audit::event(
+    AuditAction::UserUnshadowBanned,
+    Some(auth.into_user().id),
+    &ip,
+  )
+  .target(AuditTargetType::User, id);
+
+  let user = db_conn
+    .run(move |conn| {
+      conn.transaction(|conn| {
+        let user = diesel::update(User::find_by_id(&id))
+          .set(UserChangeset::default().shadow_banned_at(None))
+          .get_result::<User>(conn)?;
+
+        audit::record(conn, event)?;
+
+        Ok::<_, diesel::result::Error>(user)
+      })
+    })
+    .await?;
+
+  Response::success(AdminUserView::from(user))
+}
+
+#[openapi(tag = "Admin")]
+#[get("/admin/recordings/<id>/processing")]
+pub async fn get_recording_processing(
+  id: Uuid,
+  #[allow(unused_variables)] auth: Auth<Admin>,
+  db_conn: DbConn,
+) -> QueryResponse<RecordingProcessingView> {
+  let recording = db_conn
+    .run(move |conn| Recording::find_by_id(&id).first::<Recording>(conn))
+    .await?;
+
+  let jobs = db_conn
+    .run(move |conn| ProcessingJob::filter_by_recording_id(&id).load::<ProcessingJob>(conn))
+    .await?;
+
+  Response::success(RecordingProcessingView::new(recording, jobs))
+}
+
+#[openapi(tag = "Admin")]
+#[delete("/admin/recordings/<id>")]
+pub async fn delete_recording(
+  id: Uuid,
+  auth: Auth<Admin>,
+  db_conn: DbConn,
+  config: &State<Config>,
+  s3: S3,
+  ip: ClientIp,
+) -> MutationResponse<StatusResponse> {
+  let recording = db_conn
+    .run(move |conn| Recording::find_by_id(&id).first::<Recording>(conn))
+    .await?;
+
+  let event = audit::event(
+    AuditAction::RecordingDeleted,
+    Some(auth.into_user().id),
+    &ip,
+  )
+  .target(AuditTargetType::Recording, recording.id)
+  .payload(json!({ "user_id": recording.user_id, "game_id": recording.game_id }));
+
+  let deleted_recording = recording.clone();
+
+  db_conn
+    .run::<_, QueryResult<_>>(move |conn| {
+      conn.transaction(|conn| {
+        diesel::delete(&deleted_recording).execute(conn)?;
+        audit::record(conn, event)
+      })
+    })
+    .await?;
+
+  // The media goes once the row is gone, so a failed delete can't leave a
+  // recording without it. Anything left behind is collected as garbage.
+  if let Err(e) =
+    delete_recording_objects(&s3.into_inner(), &config.uploads_bucket, &recording).await
+  {
+    error!(
+      "[admin] Failed to delete media for recording {}: {:?}",
+      id, e
+    );
+    sentry::capture_error(e.root_cause());
+  }
+
+  Response::status(Status::NoContent)
+}
+
+#[openapi(tag = "Admin")]
+#[delete("/admin/comments/<id>")]
+pub async fn delete_comment(
+  id: Uuid,
+  auth: Auth<Admin>,
+  db_conn: DbConn,
+  ip: ClientIp,
+) -> MutationResponse<StatusResponse> {
+  let comment = db_conn
+    .run(move |conn| comments::table.find(id).first::<Comment>(conn))
+    .await?;
+
+  let event = audit::event(AuditAction::CommentDeleted, Some(auth.into_user().id), &ip)
+    .target(AuditTargetType::Comment, comment.id)
+    .payload(json!({ "user_id": comment.user_id, "recording_id": comment.recording_id }));
+
+  db_conn
+    .run::<_, QueryResult<_>>(move |conn| {
+      conn.transaction(|conn| {
+        diesel::delete(&comment).execute(conn)?;
+        audit::record(conn, event)
+      })
+    })
+    .await?;
+
+  Response::status(Status::NoContent)
+}
+
+#[openapi(tag = "Admin")]
+#[get("/admin/audit_events?<params..>")]
+pub async fn list_audit_events(
+  #[allow(unused_variables)] auth: Auth<Admin>,
+  db_conn: DbConn,
+  params: ListAuditEventsParams,
+) -> QueryResponse<PaginatedResult<AuditEventView>> {
+  let page = params.page.unwrap_or(1);
+  let actor_id = params.actor_id;
+  let target_id = params.target_id;
+  let action = params.action;
+
+  let events = db_conn
+    .run(move |conn| {
+      AuditEvent::filter_by(actor_id, target_id, action)
+        .paginate(page)
+        .load_and_count_pages::<AuditEvent>(conn)
+    })
+    .await?;
+
+  let views = events
+    .records
+    .clone()
+    .into_iter()
+    .map(AuditEventView::from)
+    .collect();
+
+  Response::success(events.records(views))
+}
+
+#[openapi(tag = "Admin")]
+#[post("/admin/garbage_collection?<params..>")]
+pub async fn collect_garbage(
+  #[allow(unused_variables)] auth: Auth<Admin>,
+  db_conn: DbConn,
+  config: &State<Config>,
+  s3: S3,
+  params: CollectGarbageParams,
+) -> MutationResponse<GarbageCollectionReport> {
+  let dry_run = params.dry_run.unwrap_or(true);
+
+  let report = garbage_collection::collect(&db_conn, config, &s3.into_inner(), dry_run)
+    .await
+    .map_err(|e| MutationError::InternalServerError(e.into()))?;
+
+  Response::success(report)
+}
+
+/// Backfills the private ACL onto media uploaded while objects were still
+/// `public-read`. Runs in the background since it touches every object.
+#[openapi(tag = "Admin")]
+#[post("/admin/media/reset_acls")]
+pub async fn reset_media_acls(
+  #[allow(unused_variables)] auth: Auth<Admin>,
+  config: &State<Config>,
+  s3: S3,
+) -> MutationResponse<StatusResponse> {
+  let bucket = config.uploads_bucket.clone();
+  let client = s3.into_inner();
+
+  tokio::spawn(async move {
+    for prefix in ["recordings/", "avatars/"] {
+      match make_objects_private(&client, &bucket, prefix).await {
+        Ok(count) => info!(
+          "[reset_media_acls] Made {} objects under {} private",
+          count, prefix
+        ),
+        Err(e) => {
+          error!("[reset_media_acls] Failed for {}: {}", prefix, e);
+          sentry::capture_error(e.root_cause());
+        }
+      }
+    }
+  });
+
+  Response::status(Status::Accepted)
+}
+
+#[openapi(tag = "Admin")]
+#[post("/admin/games", data = "<body>")]
+pub async fn create_game(
+  body: Json<CreateGameRequest>,
+  auth: Auth<Admin>,
+  db_conn: DbConn,
+  config: &State<Config>,
+  ip: ClientIp,
+) -> MutationResponse<GameView> {
+  if let Err(errors) = body.validate() {
+    return Response::validation_error(errors);
+  }
+
+  if !config
+    .transcoding_profiles
+    .contains_key(&body.transcoding_profile)
+  {
+    return Response::mutation_error(Status::UnprocessableEntity);
+  }
+
+  let actor_id = auth.into_user().id;
+  let id = body.id.clone();
+  let event = audit::event(AuditAction::GameCreated, Some(actor_id), &ip)
+    .payload(json!({ "game_id": body.id, "name": body.name }));
+
+  db_conn
+    .run::<_, Result<_, MutationError>>(move |conn| {
+      conn.transaction(|conn| {
+        let existing = Game::all().load::<Game>(conn)?;
+
+        if existing.iter().any(|game| game.id == body.id) {
+          return Err(MutationError::Status(Status::Conflict));
+        }
+
+        let position = existing
+          .iter()
+          .map(|game| game.position)
+          .max()
+          .map_or(0, |position| position + 1);
+
+        diesel::insert_into(games::table)
+          .values(
+            GameChangeset::default()
+              .id(body.id.clone())
+              .name(body.name.clone())
+              .transcoding_profile(body.transcoding_profile.clone())
+              .review_categories(json!(body.review_categories))
+              .position(position),
+          )
+          .execute(conn)?;
+
+        audit::record(conn, event)?;
+
+        Ok(())
+      })
+    })
+    .await?;
+
+  crate::games::invalidate();
+  let game = crate::games::find(&db_conn, &id).await?;
+
+  Response::success(GameView::from(&game))
+}
+
+#[openapi(tag = "Admin")]
+#[put("/admin/games/<id>", data = "<body>")]
+pub async fn update_game(
+  id: String,
+  body: Json<UpdateGameRequest>,
+  auth: Auth<Admin>,
+  db_conn: DbConn,
+  config: &State<Config>,
+  ip: ClientIp,
+) -> MutationResponse<GameView> {
+  if let Err(errors) = body.validate() {
+    return Response::validation_error(errors);
+  }
+
+  if !config
+    .transcoding_profiles
+    .contains_key(&body.transcoding_profile)
+  {
+    return Response::mutation_error(Status::UnprocessableEntity);
+  }
+
+  let actor_id = auth.into_user().id;
+  let game_id = id.clone();
+  let event = audit::event(AuditAction::GameUpdated, Some(actor_id), &ip).payload(json!({
+    "game_id": id,
+    "name": body.name,
+    "transcoding_profile": body.transcoding_profile,
+    "review_categories": body.review_categories,
+  }));
+
+  db_conn
+    .run(move |conn| {
+      conn.transaction(|conn| {
+        diesel::update(Game::find_by_id(&game_id))
+          .set(
+            GameChangeset::default()
+              .name(body.name.clone())
+              .transcoding_profile(body.transcoding_profile.clone())
+              .review_categories(json!(body.review_categories)),
+          )
+          .get_result::<Game>(conn)?;
+
+        audit::record(conn, event)?;
+
+        Ok::<_, diesel::result::Error>(())
+      })
+    })
+    .await?;
+
+  crate::games::invalidate();
+  let game = crate::games::find(&db_conn, &id).await?;
+
+  Response::success(GameView::from(&game))
+}
+
+/// Adds a rank to a game. Values are never reused, so recordings keep
+/// pointing at the rank they were created with.
+#[openapi(tag = "Admin")]
+#[post("/admin/games/<id>/skill_levels", data = "<body>")]
+pub async fn create_skill_level(
+  id: String,
+  body: Json<CreateSkillLevelRequest>,
+  auth: Auth<Admin>,
+  db_conn: DbConn,
+  ip: ClientIp,
+) -> MutationResponse<GameView> {
+  if let Err(errors) = body.validate() {
+    return Response::validation_error(errors);
+  }
+
+  let actor_id = auth.into_user().id;
+  let game_id = id.clone();
+
+  db_conn
+    .run(move |conn| {
+      conn.transaction(|conn| {
+        Game::find_by_id(&game_id).first::<Game>(conn)?;
+
+        let existing = SkillLevel::filter_by_game_id(&game_id).load::<SkillLevel>(conn)?;
+
+        let value = existing
+          .iter()
+          .map(|skill_level| skill_level.value)
+          .max()
+          .map_or(0, |value| value + 1);
+
+        let last_position = existing
+          .iter()
+          .map(|skill_level| skill_level.position)
+          .max()
+          .map_or(0, |position| position + 1);
+
+        let position = body
+          .position
+          .map_or(last_position, |position| position.min(last_position));
+
+        diesel::update(
+          SkillLevel::filter_by_game_id(&game_id).filter(skill_levels::position.ge(position)),
+        )
+        .set(skill_levels::position.eq(skill_levels::position + 1))
+        .execute(conn)?;
+
+        diesel::insert_into(skill_levels::table)
+          .values(
+            SkillLevelChangeset::default()
+              .game_id(game_id.clone())
+              .value(value)
+              .name(body.name.clone())
+              .position(position),
+          )
+          .execute(conn)?;
+
+        audit::record(
+          conn,
+          audit::event(AuditAction::SkillLevelCreated, Some(actor_id), &ip).payload(json!({
+            "game_id": game_id,
+            "value": value,
+            "name": body.name,
+            "position": position,
+          })),
+        )?;
+
+        Ok::<_, diesel::result::Error>(())
+      })
+    })
+    .await?;
+
+  crate::games::invalidate();
+  let game = crate::games::find(&db_conn, &id).await?;
+
+  Response::success(GameView::from(&game))
+}
+
+/// Renames or deprecates a rank. Deprecated ranks stay attached to existing
+/// recordings but can't be chosen for new ones.
+#[openapi(tag = "Admin")]
+#[put("/admin/games/<id>/skill_levels/<value>", data = "<body>")]
+pub async fn update_skill_level(
+  id: String,
+  value: i16,
+  body: Json<UpdateSkillLevelRequest>,
+  auth: Auth<Admin>,
+  db_conn: DbConn,
+  ip: ClientIp,
+) -> MutationResponse<GameView> {
+  if let Err(errors) = body.validate() {
+    return Response::validation_error(errors);
+  }
+
+  let actor_id = auth.into_user().id;
+  let game_id = id.clone();
+  let event = audit::event(AuditAction::SkillLevelUpdated, Some(actor_id), &ip).payload(json!({
+    "game_id": id,
+    "value": value,
+    "name": body.name,
+    "deprecated": body.deprecated,
+  }));
+
+  db_conn
+    .run(move |conn| {
+      conn.transaction(|conn| {
+        let skill_level = SkillLevel::find(&game_id, value).first::<SkillLevel>(conn)?;
+
+        let deprecated_at = body.deprecated.then(|| {
+          skill_level
+            .deprecated_at
+            .unwrap_or_else(|| Utc::now().naive_utc())
+        });
+
+        diesel::update(SkillLevel::find(&game_id, value))
+          .set(
+            SkillLevelChangeset::default()
+              .name(body.name.clone())
+              .deprecated_at(deprecated_at),
+          )
+          .execute(conn)?;
+
+        audit::record(conn, event)?;
+
+        Ok::<_, diesel::result::Error>(())
+      })
+    })
+    .await?;
+
+  crate::games::invalidate();
+  let game = crate::games::find(&db_conn, &id).await?;
+
+  Response::success(GameView::from(&game))
+}
+
+#[openapi(tag = "Admin")]
+#[post("/admin/games/<id>/taxonomy_entries", data = "<body>")]
+pub async fn create_taxonomy_entry(
+  id: String,
+  body: Json<CreateTaxonomyEntryRequest>,
+  auth: Auth<Admin>,
+  db_conn: DbConn,
+  ip: ClientIp,
+) -> MutationResponse<GameView> {
+  if let Err(errors) = body.validate() {
+    return Response::validation_error(errors);
+  }
+
+  let actor_id = auth.into_user().id;
+  let game_id = id.clone();
+
+  db_conn
+    .run::<_, Result<_, MutationError>>(move |conn| {
+      conn.transaction(|conn| {
+        Game::find_by_id(&game_id).first::<Game>(conn)?;
+
+        let existing = TaxonomyEntry::filter_by_game_and_kind(&game_id, body.kind)
+          .load::<TaxonomyEntry>(conn)?;
+
+        if existing.iter().any(|entry| entry.key == body.key) {
+          return Err(MutationError::Status(Status::Conflict));
+        }
+
+        let last_position = existing
+          .iter()
+          .map(|entry| entry.position)
+          .max()
+          .map_or(0, |position| position + 1);
+
+        let position = body
+          .position
+          .map_or(last_position, |position| position.min(last_position));
+
+        diesel::update(
+          TaxonomyEntry::filter_by_game_and_kind(&game_id, body.kind)
+            .filter(taxonomy_entries::position.ge(position)),
+        )
+        .set(taxonomy_entries::position.eq(taxonomy_entries::position + 1))
+        .execute(conn)?;
+
+        diesel::insert_into(taxonomy_entries::table)
+          .values(
+            TaxonomyEntryChangeset::default()
+              .game_id(game_id.clone())
+              .kind(body.kind)
+              .key(body.key.clone())
+              .name(body.name.clone())
+              .position(position),
+          )
+          .execute(conn)?;
+
+        audit::record(
+          conn,
+          audit::event(AuditAction::TaxonomyEntryCreated, Some(actor_id), &ip).payload(json!({
+            "game_id": game_id,
+            "kind": body.kind,
+            "key": body.key,
+            "name": body.name,
+            "position": position,
+          })),
+        )?;
+
+        Ok(())
+      })
+    })
+    .await?;
+
+  crate::games::invalidate();
+  let game = crate::games::find(&db_conn, &id).await?;
+
+  Response::success(GameView::from(&game))
+}
+
+/// Renames or deprecates a map, role or hero. Deprecated entries stay on
+/// existing recordings but can't be picked for new ones.
+#[openapi(tag = "Admin")]
+#[put("/admin/games/<id>/taxonomy_entries/<kind>/<key>", data = "<body>")]
+pub async fn update_taxonomy_entry(
+  id: String,
+  kind: String,
+  key: String,
+  body: Json<UpdateTaxonomyEntryRequest>,
+  auth: Auth<Admin>,
+  db_conn: DbConn,
+  ip: ClientIp,
+) -> MutationResponse<GameView> {
+  if let Err(errors) = body.validate() {
+    return Response::validation_error(errors);
+  }
+
+  let kind = match serde_plain::from_str::<TaxonomyKind>(&kind) {
+    Ok(kind) => kind,
+    Err(_) => return Response::mutation_error(Status::NotFound),
+  };
+
+  let actor_id = auth.into_user().id;
+  let game_id = id.clone();
+  let event = audit::event(AuditAction::TaxonomyEntryUpdated, Some(actor_id), &ip).payload(json!({
+    "game_id": id,
+    "kind": kind,
+    "key": key,
+    "name": body.name,
+    "deprecated": body.deprecated,
+  }));
+
+  db_conn
+    .run(move |conn| {
+      conn.transaction(|conn| {
+        let entry = TaxonomyEntry::find(&game_id, kind, &key).first::<TaxonomyEntry>(conn)?;
+
+        let deprecated_at = body.deprecated.then(|| {
+          entry
+            .deprecated_at
+            .unwrap_or_else(|| Utc::now().naive_utc())
+        });
+
+        diesel::update(TaxonomyEntry::find(&game_id, kind, &key))
+          .set(
+            TaxonomyEntryChangeset::default()
+              .name(body.name.clone())
+              .deprecated_at(deprecated_at),
+          )
+          .execute(conn)?;
+
+        audit::record(conn, event)?;
+
+        Ok::<_, diesel::result::Error>(())
+      })
+    })
+    .await?;
+
+  crate::games::invalidate();
+  let game = crate::games::find(&db_conn, &id).await?;
+
+  Response::success(GameView::from(&game))
+}
@@ -1,3 +1,4 @@
+use crate::aws::s3::upload_metadata;
 use crate::config::Config;
 use crate::guards::{Auth, DbConn, Jwt, S3};
 use crate::models::{Avatar, AvatarChangeset};
@@ -12,7 +13,6 @@ use rusoto_core::Region;
 use rusoto_credential::AwsCredentials;
 use rusoto_s3::util::PreSignedRequest;
 use rusoto_s3::{Delete, DeleteObjectsRequest, ObjectIdentifier, PutObjectRequest, S3 as RusotoS3};
-use std::collections::HashMap;
 use uuid::Uuid;
 
 #[openapi(tag = "Ranklab")]
@@ -34,17 +34,10 @@ pub async fn create(
     })
     .await;
 
-  let mut metadata = HashMap::new();
-
-  if let Some(instance_id) = config.instance_id.as_ref() {
-    metadata.insert("instance-id".to_string(), instance_id.to_string());
-  }
-
   let req = PutObjectRequest {
     bucket: config.uploads_bucket.to_owned(),
     key: avatar.image_key.to_owned(),
-    acl: Some("public-read".to_string()),
-    metadata: Some(metadata),
+    metadata: Some(upload_metadata(config)),
     ..Default::default()
   };
 
@@ -61,6 +54,7 @@ pub async fn create(
 
   Response::success(AvatarView::new(
     avatar,
+    config,
     Some(url),
     config.instance_id.clone(),
   ))
@@ -110,12 +104,17 @@ pub async fn delete(
 
 #[openapi(tag = "Ranklab")]
 #[get("/avatars/<id>")]
-pub async fn get(auth: Auth<Jwt>, id: Uuid, db_conn: DbConn) -> QueryResponse<AvatarView> {
+pub async fn get(
+  auth: Auth<Jwt>,
+  id: Uuid,
+  db_conn: DbConn,
+  config: &State<Config>,
+) -> QueryResponse<AvatarView> {
   let user_id = auth.into_user().id;
 
   let avatar = db_conn
     .run(move |conn| Avatar::find_by_id_for_user(&id, &user_id).first::<Avatar>(conn))
     .await?;
 
-  Response::success(AvatarView::new(avatar, None, None))
+  Response::success(AvatarView::new(avatar, config, None, None))
 }
@@ -0,0 +1,280 @@
+use crate::aws::s3::delete_object_keys;
+use crate::config::Config;
+use crate::data_types::{ClipRenderState, MediaState};
+use crate::guards::{Auth, DbConn, Jwt, S3};
+use crate::models::{Clip, ClipChangeset, User};
+use crate::response::{MutationError, MutationResponse, QueryResponse, Response, StatusResponse};
+use crate::routes::recordings::find_visible_recording;
+use crate::schema::{clips, users};
+use crate::transcoding::{profile_for_game, transcoder, CLIP_NAME_MODIFIER};
+use crate::views::ClipView;
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{tokio, State};
+use rocket_okapi::openapi;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Longest clip that can be created, in seconds.
+const MAX_CLIP_LENGTH: i64 = 5 * 60;
+
+#[derive(Deserialize, JsonSchema, Validate)]
+#[validate(schema(function = "validate_clip"))]
+pub struct CreateClipRequest {
+  recording_id: Uuid,
+  #[validate(length(min = 1, max = 100))]
+  title: String,
+  #[validate(range(min = 0))]
+  start_timestamp: i64,
+  end_timestamp: i64,
+}
+
+fn validate_clip(clip: &CreateClipRequest) -> Result<(), validator::ValidationError> {
+  let length = clip.end_timestamp - clip.start_timestamp;
+
+  if length <= 0 || length > MAX_CLIP_LENGTH {
+    return Err(validator::ValidationError::new("invalid"));
+  }
+
+  Ok(())
+}
+
+#[derive(FromForm, JsonSchema)]
+pub struct ListParams {
+  recording_id: Uuid,
+}
+
+#[openapi(tag = "Ranklab")]
+#[post("/clips", data = "<clip>")]
+pub async fn create(
+  clip: Json<CreateClipRequest>,
+  auth: Auth<Jwt>,
+  db_conn: DbConn,
+  config: &State<Config>,
+) -> MutationResponse<ClipView> {
+  if let Err(errors) = clip.validate() {
+    return Response::validation_error(errors);
+  }
+
+  let user_id = auth.into_user().id;
+  find_visible_recording(&db_conn, Some(user_id), clip.recording_id).await?;
+
+  let clip = db_conn
+    .run(move |conn| {
+      diesel::insert_into(clips::table)
+        .values(
+          ClipChangeset::default()
+            .recording_id(clip.recording_id)
+            .user_id(user_id)
+            .title(ammonia::clean(&clip.title))
+            .start_timestamp(clip.start_timestamp)
+            .end_timestamp(clip.end_timestamp),
+        )
+        .get_result::<Clip>(conn)
+    })
+    .await?;
+
+  Response::success(ClipView::new(clip, config))
+}
+
+#[openapi(tag = "Ranklab")]
+#[get("/clips?<params..>")]
+pub async fn list(
+  params: ListParams,
+  auth: Auth<Option<Jwt>>,
+  db_conn: DbConn,
+  config: &State<Config>,
+) -> QueryResponse<Vec<ClipView>> {
+  let recording_id = params.recording_id;
+  let viewer_id = auth.into_user().map(|user| user.id);
+
+  find_visible_recording(&db_conn, viewer_id, recording_id).await?;
+
+  let clips = db_conn
+    .run(move |conn| {
+      // Shadow-banned authors still see their own clips.
+      let excluded_user_ids = User::filter_shadow_banned()
+        .select(users::id)
+        .load::<Uuid>(conn)?
+        .into_iter()
+        .filter(|id| Some(*id) != viewer_id)
+        .collect::<Vec<_>>();
+
+      Clip::filter_by_recording_id(&recording_id)
+        .filter(clips::user_id.ne_all(excluded_user_ids))
+        .load::<Clip>(conn)
+    })
+    .await?;
+
+  Response::success(
+    clips
+      .into_iter()
+      .map(|clip| ClipView::new(clip, config))
+      .collect(),
+  )
+}
+
+#[openapi(tag = "Ranklab")]
+#[get("/clips/<id>")]
+pub async fn get(
+  id: Uuid,
+  auth: Auth<Option<Jwt>>,
+  db_conn: DbConn,
+  config: &State<Config>,
+) -> QueryResponse<ClipView> {
+  let viewer_id = auth.into_user().map(|user| user.id);
+
+  let clip = db_conn
+    .run(move |conn| Clip::find_by_id(&id).first::<Clip>(conn))
+    .await?;
+
+  find_visible_recording(&db_conn, viewer_id, clip.recording_id).await?;
+
+  Response::success(ClipView::new(clip, config))
+}
+
+#[openapi(tag = "Ranklab")]
+#[post("/clips/<id>/render")]
+pub async fn render(
+  id: Uuid,
+  auth: Auth<Jwt>,
+  db_conn: DbConn,
+  config: &State<Config>,
+) -> MutationResponse<ClipView> {
+  let user_id = auth.into_user().id;
+
+  let clip = db_conn
+    .run(move |conn| Clip::find_for_user(&user_id, &id).first::<Clip>(conn))
+    .await?;
+
+  // Rendering is idempotent so a second request can't start another job.
+  if matches!(
+    clip.render_state,
+    Some(ClipRenderState::Pending) | Some(ClipRenderState::Rendered)
+  ) {
+    return Response::success(ClipView::new(clip, config));
+  }
+
+  let recording = find_visible_recording(&db_conn, Some(user_id), clip.recording_id).await?;
+
+  let video_key = match (&recording.state, &recording.video_key) {
+    (MediaState::Processed, Some(video_key)) => video_key.clone(),
+    _ => return Response::mutation_error(Status::UnprocessableEntity),
+  };
+
+  let profile = profile_for_game(&db_conn, config, &recording.game_id)
+    .await?
+    .ok_or_else(|| MutationError::InternalServerError("No transcoding profile found".into()))?;
+
+  // Claiming the render up front means a concurrent request sees it as
+  // pending instead of starting a second job.
+  let claimed = db_conn
+    .run(move |conn| {
+      diesel::update(Clip::find_by_id(&id))
+        .filter(
+          clips::render_state
+            .is_null()
+            .or(clips::render_state.eq(ClipRenderState::Failed)),
+        )
+        .set(
+          ClipChangeset::default()
+            .render_state(Some(ClipRenderState::Pending))
+            .render_job_id(None),
+        )
+        .get_result::<Clip>(conn)
+        .optional()
+    })
+    .await?;
+
+  let clip = match claimed {
+    Some(clip) => clip,
+    None => {
+      let clip = db_conn
+        .run(move |conn| Clip::find_by_id(&id).first::<Clip>(conn))
+        .await?;
+
+      return Response::success(ClipView::new(clip, config));
+    }
+  };
+
+  let view = ClipView::new(clip.clone(), config);
+  let config = config.inner().clone();
+  let profile = profile.clone();
+  let name_modifier = format!("{}{}", CLIP_NAME_MODIFIER, clip.id);
+
+  // Renders can take minutes with ffmpeg, so they never hold up the request.
+  // The finished clip is picked up by the uploads queue.
+  tokio::spawn(async move {
+    let result = transcoder(&config)
+      .clip(
+        &profile,
+        &video_key,
+        &name_modifier,
+        clip.start_timestamp,
+        clip.end_timestamp,
+      )
+      .await;
+
+    let update = match result {
+      Ok(job_id) => {
+        db_conn
+          .run(move |conn| {
+            diesel::update(&clip)
+              .set(ClipChangeset::default().render_job_id(Some(job_id)))
+              .execute(conn)
+          })
+          .await
+      }
+      Err(e) => {
+        error!("[clips] Failed to render clip {}: {:?}", clip.id, e);
+        sentry::capture_error(e.root_cause());
+
+        db_conn
+          .run(move |conn| {
+            diesel::update(&clip)
+              .filter(clips::render_state.eq(ClipRenderState::Pending))
+              .set(ClipChangeset::default().render_state(Some(ClipRenderState::Failed)))
+              .execute(conn)
+          })
+          .await
+      }
+    };
+
+    if let Err(e) = update {
+      error!("[clips] Failed to update clip {}: {:?}", id, e);
+    }
+  });
+
+  Response::success(view)
+}
+
+#[openapi(tag = "Ranklab")]
+#[delete("/clips/<id>")]
+pub async fn delete(
+  id: Uuid,
+  auth: Auth<Jwt>,
+  db_conn: DbConn,
+  config: &State<Config>,
+  s3: S3,
+) -> MutationResponse<StatusResponse> {
+  let user_id = auth.into_user().id;
+
+  let clip = db_conn
+    .run(move |conn| Clip::find_for_user(&user_id, &id).first::<Clip>(conn))
+    .await?;
+
+  if let Some(video_key) = clip.video_key.clone() {
+    delete_object_keys(&s3.into_inner(), &config.uploads_bucket, vec![video_key])
+      .await
+      .map_err(|e| MutationError::InternalServerError(e.into()))?;
+  }
+
+  db_conn
+    .run(move |conn| diesel::delete(&clip).execute(conn))
+    .await?;
+
+  Response::status(Status::NoContent)
+}
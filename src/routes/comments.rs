@@ -1,20 +1,28 @@
 use std::collections::HashSet;
 
-use crate::guards::{Auth, DbConn, Jwt};
+use crate::audit;
+use crate::config::Config;
+use crate::data_types::{AuditAction, AuditTargetType};
+use crate::guards::{Auth, ClientIp, DbConn, Jwt};
 use crate::models::{Comment, CommentChangeset, CommentMetadata, User};
 use crate::response::{MutationResponse, QueryResponse, Response, StatusResponse};
+use crate::routes::recordings::find_visible_recording;
 use crate::schema::comments;
 use crate::views::CommentView;
+use chrono::{Duration, Utc};
 use diesel::prelude::*;
 use rocket::http::Status;
 use rocket::serde::json::Json;
+use rocket::State;
 use rocket_okapi::openapi;
 use schemars::JsonSchema;
 use serde::Deserialize;
+use serde_json::json;
 use uuid::Uuid;
 use validator::Validate;
 
 #[derive(Deserialize, Validate, JsonSchema)]
+#[validate(schema(function = "validate_create_metadata"))]
 pub struct CreateCommentRequest {
   body: String,
   recording_id: Uuid,
@@ -22,11 +30,51 @@ pub struct CreateCommentRequest {
 }
 
 #[derive(Deserialize, Validate, JsonSchema)]
+#[validate(schema(function = "validate_update_metadata"))]
 pub struct UpdateCommentRequest {
   body: String,
   metadata: CommentMetadata,
 }
 
+const MAX_KEYFRAMES: usize = 100;
+
+fn validate_metadata(metadata: &CommentMetadata) -> Result<(), validator::ValidationError> {
+  let valid = match metadata {
+    CommentMetadata::General => true,
+    CommentMetadata::Video { timestamp, .. } => *timestamp >= 0,
+    CommentMetadata::Range {
+      start_timestamp,
+      end_timestamp,
+      ..
+    } => *start_timestamp >= 0 && end_timestamp > start_timestamp,
+    CommentMetadata::Keyframes { keyframes } => {
+      (1..=MAX_KEYFRAMES).contains(&keyframes.len())
+        && keyframes[0].timestamp >= 0
+        && keyframes
+          .windows(2)
+          .all(|pair| pair[1].timestamp > pair[0].timestamp)
+    }
+  };
+
+  if !valid {
+    return Err(validator::ValidationError::new("invalid"));
+  }
+
+  Ok(())
+}
+
+fn validate_create_metadata(
+  comment: &CreateCommentRequest,
+) -> Result<(), validator::ValidationError> {
+  validate_metadata(&comment.metadata)
+}
+
+fn validate_update_metadata(
+  comment: &UpdateCommentRequest,
+) -> Result<(), validator::ValidationError> {
+  validate_metadata(&comment.metadata)
+}
+
 #[derive(FromForm, JsonSchema)]
 pub struct ListParams {
   recording_id: Uuid,
@@ -47,48 +95,21 @@ pub async fn create(
   let user = auth.into_user();
   let user_id = user.id;
 
+  find_visible_recording(&db_conn, Some(user_id), recording_id).await?;
+
   let comment = db_conn
     .run(move |conn| {
-      let mut metadata_cleaner = ammonia::Builder::default();
-      let mut allowed_tags = HashSet::new();
-
-      allowed_tags.insert("svg");
-      allowed_tags.insert("path");
-
-      metadata_cleaner
-        .tags(allowed_tags)
-        .add_tag_attributes("svg", &["viewBox"])
-        .add_tag_attributes(
-          "path",
-          &[
-            "stroke",
-            "fill",
-            "stroke-linecap",
-            "stroke-linejoin",
-            "stroke-width",
-            "d",
-          ],
-        );
-
-      let metadata = match &comment.metadata {
-        CommentMetadata::Video { timestamp, drawing } => CommentMetadata::Video {
-          timestamp: *timestamp,
-          drawing: metadata_cleaner.clean(drawing).to_string(),
-        },
-      };
-
       diesel::insert_into(comments::table)
         .values(
           CommentChangeset::default()
             .body(ammonia::clean(&comment.body))
             .recording_id(recording_id)
             .user_id(user_id)
-            .metadata(serde_json::to_value(metadata).unwrap()),
+            .metadata(serde_json::to_value(comment.metadata.sanitized()).unwrap()),
         )
         .get_result::<Comment>(conn)
-        .unwrap()
     })
-    .await;
+    .await?;
 
   Response::success(CommentView::new(comment, Some(user)))
 }
@@ -117,8 +138,8 @@ pub async fn update(
       diesel::update(&existing_comment)
         .set(
           CommentChangeset::default()
-            .body(comment.body.clone())
-            .metadata(serde_json::to_value(&comment.metadata).unwrap()),
+            .body(ammonia::clean(&comment.body))
+            .metadata(serde_json::to_value(comment.metadata.sanitized()).unwrap()),
         )
         .get_result::<Comment>(conn)
         .unwrap()
@@ -134,6 +155,7 @@ pub async fn delete(
   id: Uuid,
   auth: Auth<Jwt>,
   db_conn: DbConn,
+  ip: ClientIp,
 ) -> MutationResponse<StatusResponse> {
   let user_id = auth.into_user().id;
 
@@ -141,17 +163,66 @@ pub async fn delete(
     .run(move |conn| Comment::find_for_user(&user_id, &id).first::<Comment>(conn))
     .await?;
 
+  let event = audit::event(AuditAction::CommentDeleted, Some(user_id), &ip)
+    .target(AuditTargetType::Comment, existing_comment.id)
+    .payload(json!({ "recording_id": existing_comment.recording_id }));
+
   db_conn
-    .run(move |conn| diesel::delete(&existing_comment).execute(conn))
+    .run::<_, QueryResult<_>>(move |conn| {
+      conn.transaction(|conn| {
+        diesel::update(&existing_comment)
+          .set(CommentChangeset::default().deleted_at(Some(Utc::now().naive_utc())))
+          .execute(conn)?;
+
+        audit::record(conn, event)
+      })
+    })
     .await?;
 
   Response::status(Status::NoContent)
 }
 
+#[openapi(tag = "Ranklab")]
+#[post("/comments/<id>/restore")]
+pub async fn restore(
+  id: Uuid,
+  auth: Auth<Jwt>,
+  db_conn: DbConn,
+  config: &State<Config>,
+  ip: ClientIp,
+) -> MutationResponse<CommentView> {
+  let user = auth.into_user();
+  let user_id = user.id;
+  let deleted_since = Utc::now().naive_utc() - Duration::days(config.deletion_retention_days);
+  let event = audit::event(AuditAction::CommentRestored, Some(user_id), &ip)
+    .target(AuditTargetType::Comment, id);
+
+  let comment = db_conn
+    .run(move |conn| {
+      conn.transaction(|conn| {
+        let comment = diesel::update(Comment::find_deleted_for_user(&user_id, &id, deleted_since))
+          .set(CommentChangeset::default().deleted_at(None))
+          .get_result::<Comment>(conn)?;
+
+        audit::record(conn, event)?;
+
+        Ok::<_, diesel::result::Error>(comment)
+      })
+    })
+    .await?;
+
+  Response::success(CommentView::new(comment, Some(user)))
+}
+
 #[openapi(tag = "Ranklab")]
 #[get("/comments?<params..>")]
-pub async fn list(params: ListParams, db_conn: DbConn) -> QueryResponse<Vec<CommentView>> {
+pub async fn list(
+  params: ListParams,
+  auth: Auth<Option<Jwt>>,
+  db_conn: DbConn,
+) -> QueryResponse<Vec<CommentView>> {
   let recording_id = params.recording_id;
+  let viewer_id = auth.into_user().map(|user| user.id);
 
   let comments = db_conn
     .run(move |conn| {
@@ -174,13 +245,20 @@ pub async fn list(params: ListParams, db_conn: DbConn) -> QueryResponse<Vec<Comm
 
   let comments = comments
     .into_iter()
-    .map(|comment| {
+    .filter_map(|comment| {
       let user = users
         .iter()
         .find(|user| user.id == comment.user_id)
         .cloned();
 
-      CommentView::new(comment, user)
+      // Shadow-banned authors still see their own comments.
+      if let Some(user) = &user {
+        if user.is_shadow_banned() && Some(user.id) != viewer_id {
+          return None;
+        }
+      }
+
+      Some(CommentView::new(comment, user))
     })
     .collect();
 
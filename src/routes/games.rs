@@ -1,9 +1,12 @@
+use crate::data_types::TaxonomyKind;
+use crate::games::CatalogueGame;
 use crate::guards::{Auth, DbConn, Jwt};
 use crate::models::{Following, FollowingChangeset};
 use crate::response::{MutationResponse, QueryResponse, Response};
 use crate::schema::followings;
 use crate::views::GameView;
 use diesel::prelude::*;
+use rocket::http::Status;
 use rocket::serde::json::Json;
 use rocket_okapi::openapi;
 use schemars::JsonSchema;
@@ -13,7 +16,7 @@ use serde::Deserialize;
 #[get("/games")]
 pub async fn list(auth: Auth<Option<Jwt>>, db_conn: DbConn) -> QueryResponse<Vec<GameView>> {
   let user = auth.into_user();
-  let games = crate::games::all().iter().collect::<Vec<_>>();
+  let games = crate::games::all(&db_conn).await?;
 
   let followings = match user {
     Some(user) => {
@@ -29,14 +32,13 @@ pub async fn list(auth: Auth<Option<Jwt>>, db_conn: DbConn) -> QueryResponse<Vec
   };
 
   let game_views = games
-    .clone()
-    .into_iter()
+    .iter()
     .map(|game| {
-      let followed = followings
+      let following = followings
         .iter()
-        .any(|following| following.game_id == game.id.to_string());
+        .find(|following| following.game_id == game.id);
 
-      GameView::new(game, followed)
+      GameView::new(game, following)
     })
     .collect::<Vec<_>>();
 
@@ -46,6 +48,36 @@ pub async fn list(auth: Auth<Option<Jwt>>, db_conn: DbConn) -> QueryResponse<Vec
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct UpdateGameRequest {
   followed: bool,
+  /// Only include recordings on this map in digests.
+  #[serde(default)]
+  map: Option<String>,
+  #[serde(default)]
+  role: Option<String>,
+  #[serde(default)]
+  hero: Option<String>,
+  /// Lowest and highest rank to include in digests, as skill level values.
+  #[serde(default)]
+  min_skill_level: Option<i16>,
+  #[serde(default)]
+  max_skill_level: Option<i16>,
+}
+
+/// Checks that both bounds are current ranks of the game and in order.
+fn valid_skill_level_range(game: &CatalogueGame, min: Option<i16>, max: Option<i16>) -> bool {
+  let known = [min, max]
+    .iter()
+    .flatten()
+    .all(|value| game.accepts_skill_level(*value));
+
+  let ordered = match (
+    min.and_then(|value| game.skill_level(value)),
+    max.and_then(|value| game.skill_level(value)),
+  ) {
+    (Some(min), Some(max)) => min.position <= max.position,
+    _ => true,
+  };
+
+  known && ordered
 }
 
 #[openapi(tag = "Ranklab")]
@@ -57,32 +89,46 @@ pub async fn update(
   db_conn: DbConn,
 ) -> MutationResponse<GameView> {
   let user = auth.into_user();
-  let game = crate::games::find(&id).unwrap();
+  let game = crate::games::find(&db_conn, &id).await?;
+
+  if !game.accepts_taxonomy_entry(TaxonomyKind::Map, request.map.as_deref())
+    || !game.accepts_taxonomy_entry(TaxonomyKind::Role, request.role.as_deref())
+    || !game.accepts_taxonomy_entry(TaxonomyKind::Hero, request.hero.as_deref())
+    || !valid_skill_level_range(&game, request.min_skill_level, request.max_skill_level)
+  {
+    return Response::mutation_error(Status::UnprocessableEntity);
+  }
 
   let following = db_conn
     .run(move |conn| {
       let following = Following::find_for_user_and_game(&user.id, &id)
         .get_result::<Following>(conn)
-        .optional()
-        .unwrap();
+        .optional()?;
 
-      match (request.followed, &following) {
+      let changeset = FollowingChangeset::default()
+        .map(request.map.clone())
+        .role(request.role.clone())
+        .hero(request.hero.clone())
+        .min_skill_level(request.min_skill_level)
+        .max_skill_level(request.max_skill_level);
+
+      match (request.followed, following) {
         (false, Some(following)) => {
-          diesel::delete(&following).execute(conn).unwrap();
-          None
+          diesel::delete(&following).execute(conn)?;
+          Ok(None)
         }
         (true, None) => diesel::insert_into(followings::table)
-          .values(
-            FollowingChangeset::default()
-              .user_id(user.id)
-              .game_id(id.to_string()),
-          )
+          .values(changeset.user_id(user.id).game_id(id.to_string()))
+          .get_result::<Following>(conn)
+          .map(Some),
+        (true, Some(following)) => diesel::update(&following)
+          .set(changeset)
           .get_result::<Following>(conn)
-          .ok(),
-        _ => following,
+          .map(Some),
+        (false, None) => QueryResult::Ok(None),
       }
     })
-    .await;
+    .await?;
 
-  Response::success(GameView::new(game, following.is_some()))
+  Response::success(GameView::new(&game, following.as_ref()))
 }
@@ -0,0 +1,450 @@
+use crate::audit;
+use crate::config::Config;
+use crate::data_types::{AuditAction, AuditTargetType, MediaState, ReportStatus};
+use crate::guards::{Auth, ClientIp, DbConn, Moderator, S3};
+use crate::models::{Avatar, Recording, Report, User, UserChangeset};
+use crate::pagination::{Paginate, PaginatedResult};
+use crate::queue_handlers;
+use crate::reports;
+use crate::response::{MutationError, MutationResponse, QueryResponse, Response, StatusResponse};
+use crate::schema::recordings;
+use crate::transcoding;
+use crate::views::{FlaggedAvatarView, FlaggedRecordingView, ReportView};
+use chrono::Utc;
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{tokio, State};
+use rocket_okapi::openapi;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Deserialize, JsonSchema, Validate)]
+pub struct RejectMediaRequest {
+  #[validate(length(min = 1))]
+  reason: String,
+}
+
+#[derive(FromForm, JsonSchema)]
+pub struct ListReportsParams {
+  page: Option<i64>,
+  status: Option<ReportStatus>,
+}
+
+#[openapi(tag = "Moderation")]
+#[get("/moderation/recordings")]
+pub async fn list_recordings(
+  #[allow(unused_variables)] auth: Auth<Moderator>,
+  db_conn: DbConn,
+  config: &State<Config>,
+) -> QueryResponse<Vec<FlaggedRecordingView>> {
+  let recordings = db_conn
+    .run(move |conn| Recording::filter_flagged().load::<Recording>(conn))
+    .await?;
+
+  let user_ids = recordings
+    .iter()
+    .map(|recording| recording.user_id)
+    .collect::<Vec<_>>();
+
+  let users = db_conn
+    .run(move |conn| User::filter_by_ids(user_ids).load::<User>(conn))
+    .await?;
+
+  let views = recordings
+    .into_iter()
+    .map(|recording| {
+      let user = users
+        .iter()
+        .find(|user| user.id == recording.user_id)
+        .cloned();
+
+      FlaggedRecordingView::new(recording, config, user)
+    })
+    .collect();
+
+  Response::success(views)
+}
+
+#[openapi(tag = "Moderation")]
+#[get("/moderation/avatars")]
+pub async fn list_avatars(
+  #[allow(unused_variables)] auth: Auth<Moderator>,
+  db_conn: DbConn,
+  config: &State<Config>,
+) -> QueryResponse<Vec<FlaggedAvatarView>> {
+  let avatars = db_conn
+    .run(move |conn| Avatar::filter_flagged().load::<Avatar>(conn))
+    .await?;
+
+  let user_ids = avatars
+    .iter()
+    .map(|avatar| avatar.user_id)
+    .collect::<Vec<_>>();
+
+  let users = db_conn
+    .run(move |conn| User::filter_by_ids(user_ids).load::<User>(conn))
+    .await?;
+
+  let views = avatars
+    .into_iter()
+    .map(|avatar| {
+      let user = users.iter().find(|user| user.id == avatar.user_id).cloned();
+
+      FlaggedAvatarView::new(avatar, config, user)
+    })
+    .collect();
+
+  Response::success(views)
+}
+
+#[openapi(tag = "Moderation")]
+#[post("/moderation/recordings/<id>/approve")]
+pub async fn approve_recording(
+  id: Uuid,
+  auth: Auth<Moderator>,
+  db_conn: DbConn,
+  config: &State<Config>,
+  ip: ClientIp,
+) -> MutationResponse<StatusResponse> {
+  let recording = db_conn
+    .run(move |conn| Recording::find_undeleted_by_id(&id).first::<Recording>(conn))
+    .await?;
+
+  if recording.state != MediaState::Flagged {
+    return Response::mutation_error(Status::UnprocessableEntity);
+  }
+
+  let changeset = recording
+    .transition_to(MediaState::Transcoding, None)
+    .map_err(|e| MutationError::InternalServerError(e.into()))?;
+
+  // Only the approval that moves the recording out of flagged starts its
+  // transcode, so concurrent approvals can't start it twice.
+  let recording = db_conn
+    .run(move |conn| {
+      diesel::update(&recording)
+        .filter(recordings::state.eq(MediaState::Flagged))
+        .filter(recordings::deleted_at.is_null())
+        .set(changeset)
+        .get_result::<Recording>(conn)
+        .optional()
+    })
+    .await?;
+
+  let recording = match recording {
+    Some(recording) => recording,
+    None => return Response::mutation_error(Status::UnprocessableEntity),
+  };
+
+  let event = audit::event(AuditAction::MediaApproved, Some(auth.into_user().id), &ip)
+    .target(AuditTargetType::Recording, id);
+
+  db_conn.run(move |conn| audit::record(conn, event)).await?;
+
+  let config = config.inner().clone();
+
+  // Starting the transcode can take a while depending on the backend, so it
+  // isn't awaited by the request.
+  tokio::spawn(async move {
+    let transcoder = transcoding::transcoder(&config);
+    let user_id = recording.user_id;
+
+    let result: anyhow::Result<()> = async {
+      if !queue_handlers::transcode_recording(&db_conn, &config, transcoder.as_ref(), recording)
+        .await?
+      {
+        return Ok(());
+      }
+
+      queue_handlers::notify_media_owner(
+        &db_conn,
+        &config,
+        user_id,
+        "Your VOD was approved",
+        "Your VOD passed review by our moderation team and is now being processed.",
+        Some(("View VOD", format!("{}/recordings/{}", config.web_host, id))),
+      )
+      .await
+    }
+    .await;
+
+    if let Err(e) = result {
+      error!("[moderation] Failed to start transcoding: {:?}", e);
+      sentry::capture_error(e.root_cause());
+    }
+  });
+
+  Response::status(Status::Ok)
+}
+
+#[openapi(tag = "Moderation")]
+#[post("/moderation/recordings/<id>/reject", data = "<body>")]
+pub async fn reject_recording(
+  id: Uuid,
+  body: Json<RejectMediaRequest>,
+  auth: Auth<Moderator>,
+  db_conn: DbConn,
+  config: &State<Config>,
+  s3: S3,
+  ip: ClientIp,
+) -> MutationResponse<StatusResponse> {
+  if let Err(errors) = body.validate() {
+    return Response::validation_error(errors);
+  }
+
+  let recording = db_conn
+    .run(move |conn| Recording::find_undeleted_by_id(&id).first::<Recording>(conn))
+    .await?;
+
+  if recording.state != MediaState::Flagged {
+    return Response::mutation_error(Status::UnprocessableEntity);
+  }
+
+  queue_handlers::reject_recording(
+    &db_conn,
+    config,
+    &s3.into_inner(),
+    recording,
+    MediaState::Rejected,
+    &body.reason,
+  )
+  .await
+  .map_err(|e| MutationError::InternalServerError(e.into()))?;
+
+  let event = audit::event(AuditAction::MediaRejected, Some(auth.into_user().id), &ip)
+    .target(AuditTargetType::Recording, id)
+    .payload(json!({ "reason": body.reason }));
+
+  db_conn.run(move |conn| audit::record(conn, event)).await?;
+
+  Response::status(Status::Ok)
+}
+
+#[openapi(tag = "Moderation")]
+#[post("/moderation/avatars/<id>/approve")]
+pub async fn approve_avatar(
+  id: Uuid,
+  auth: Auth<Moderator>,
+  db_conn: DbConn,
+  config: &State<Config>,
+  ip: ClientIp,
+) -> MutationResponse<StatusResponse> {
+  let avatar = db_conn
+    .run(move |conn| Avatar::find_by_id(&id).first::<Avatar>(conn))
+    .await?;
+
+  if avatar.state != MediaState::Flagged {
+    return Response::mutation_error(Status::UnprocessableEntity);
+  }
+
+  let user_id = avatar.user_id;
+  let payload = queue_handlers::avatar_uploaded_event(config, &avatar.image_key);
+
+  queue_handlers::start_avatar_processing(&db_conn, config, avatar, payload)
+    .await
+    .map_err(|e| MutationError::InternalServerError(e.into()))?;
+
+  queue_handlers::notify_media_owner(
+    &db_conn,
+    config,
+    user_id,
+    "Your avatar was approved",
+    "Your avatar passed review by our moderation team and will appear on your profile shortly.",
+    None,
+  )
+  .await
+  .map_err(|e| MutationError::InternalServerError(e.into()))?;
+
+  let event = audit::event(AuditAction::MediaApproved, Some(auth.into_user().id), &ip)
+    .target(AuditTargetType::Avatar, id);
+
+  db_conn.run(move |conn| audit::record(conn, event)).await?;
+
+  Response::status(Status::Ok)
+}
+
+#[openapi(tag = "Moderation")]
+#[post("/moderation/avatars/<id>/reject", data = "<body>")]
+pub async fn reject_avatar(
+  id: Uuid,
+  body: Json<RejectMediaRequest>,
+  auth: Auth<Moderator>,
+  db_conn: DbConn,
+  config: &State<Config>,
+  s3: S3,
+  ip: ClientIp,
+) -> MutationResponse<StatusResponse> {
+  if let Err(errors) = body.validate() {
+    return Response::validation_error(errors);
+  }
+
+  let avatar = db_conn
+    .run(move |conn| Avatar::find_by_id(&id).first::<Avatar>(conn))
+    .await?;
+
+  if avatar.state != MediaState::Flagged {
+    return Response::mutation_error(Status::UnprocessableEntity);
+  }
+
+  queue_handlers::reject_avatar(&db_conn, config, &s3.into_inner(), avatar, &body.reason)
+    .await
+    .map_err(|e| MutationError::InternalServerError(e.into()))?;
+
+  let event = audit::event(AuditAction::MediaRejected, Some(auth.into_user().id), &ip)
+    .target(AuditTargetType::Avatar, id)
+    .payload(json!({ "reason": body.reason }));
+
+  db_conn.run(move |conn| audit::record(conn, event)).await?;
+
+  Response::status(Status::Ok)
+}
+
+#[openapi(tag = "Moderation")]
+#[get("/moderation/reports?<params..>")]
+pub async fn list_reports(
+  #[allow(unused_variables)] auth: Auth<Moderator>,
+  db_conn: DbConn,
+  params: ListReportsParams,
+) -> QueryResponse<PaginatedResult<ReportView>> {
+  let page = params.page.unwrap_or(1);
+  let status = params.status.unwrap_or(ReportStatus::Open);
+
+  let reports = db_conn
+    .run(move |conn| {
+      Report::filter_by_status(status)
+        .paginate(page)
+        .load_and_count_pages::<Report>(conn)
+    })
+    .await?;
+
+  let views = reports
+    .records
+    .clone()
+    .into_iter()
+    .map(ReportView::from)
+    .collect();
+
+  Response::success(reports.records(views))
+}
+
+#[openapi(tag = "Moderation")]
+#[post("/moderation/reports/<id>/dismiss")]
+pub async fn dismiss_report(
+  id: Uuid,
+  auth: Auth<Moderator>,
+  db_conn: DbConn,
+  ip: ClientIp,
+) -> MutationResponse<StatusResponse> {
+  let actor_id = auth.into_user().id;
+
+  db_conn
+    .run::<_, QueryResult<_>>(move |conn| {
+      conn.transaction(|conn| {
+        let target = Report::find_by_id(&id).first::<Report>(conn)?.target();
+
+        reports::resolve(conn, target, ReportStatus::Dismissed)?;
+        reports::unhide(conn, target)?;
+
+        audit::record(
+          conn,
+          audit::event(AuditAction::ReportDismissed, Some(actor_id), &ip)
+            .target(AuditTargetType::Report, id),
+        )
+      })
+    })
+    .await?;
+
+  Response::status(Status::Ok)
+}
+
+#[openapi(tag = "Moderation")]
+#[post("/moderation/reports/<id>/hide")]
+pub async fn hide_report(
+  id: Uuid,
+  auth: Auth<Moderator>,
+  db_conn: DbConn,
+  ip: ClientIp,
+) -> MutationResponse<StatusResponse> {
+  let actor_id = auth.into_user().id;
+
+  db_conn
+    .run::<_, QueryResult<_>>(move |conn| {
+      conn.transaction(|conn| {
+        let target = Report::find_by_id(&id).first::<Report>(conn)?.target();
+
+        reports::hide(conn, target)?;
+        reports::resolve(conn, target, ReportStatus::Actioned)?;
+
+        audit::record(
+          conn,
+          audit::event(AuditAction::ReportActioned, Some(actor_id), &ip)
+            .target(AuditTargetType::Report, id)
+            .payload(json!({ "action": "hide" })),
+        )
+      })
+    })
+    .await?;
+
+  Response::status(Status::Ok)
+}
+
+#[openapi(tag = "Moderation")]
+#[post("/moderation/reports/<id>/suspend")]
+pub async fn suspend_report_author(
+  id: Uuid,
+  auth: Auth<Moderator>,
+  db_conn: DbConn,
+  ip: ClientIp,
+) -> MutationResponse<StatusResponse> {
+  let actor = auth.into_user();
+  let actor_id = actor.id;
+  let actor_role = actor.role;
+
+  db_conn
+    .run::<_, Result<_, MutationError>>(move |conn| {
+      conn.transaction(|conn| {
+        let target = Report::find_by_id(&id).first::<Report>(conn)?.target();
+        let author_id = reports::author_id(conn, target)?;
+        let author = User::find_by_id(&author_id).first::<User>(conn)?;
+
+        if !actor_role.outranks(author.role) {
+          return Err(MutationError::Status(Status::Forbidden));
+        }
+
+        diesel::update(&author)
+          .set(
+            UserChangeset::default()
+              .suspended_at(Some(Utc::now().naive_utc()))
+              .suspended_until(None)
+              .suspension_reason(Some("Reported content".to_owned())),
+          )
+          .execute(conn)?;
+
+        reports::hide(conn, target)?;
+        reports::resolve(conn, target, ReportStatus::Actioned)?;
+
+        audit::record(
+          conn,
+          audit::event(AuditAction::ReportActioned, Some(actor_id), &ip)
+            .target(AuditTargetType::Report, id)
+            .payload(json!({ "action": "suspend", "user_id": author_id })),
+        )?;
+
+        audit::record(
+          conn,
+          audit::event(AuditAction::UserSuspended, Some(actor_id), &ip)
+            .target(AuditTargetType::User, author_id)
+            .payload(json!({ "until": null, "reason": "Reported content", "report_id": id })),
+        )?;
+
+        Ok(())
+      })
+    })
+    .await?;
+
+  Response::status(Status::Ok)
+}
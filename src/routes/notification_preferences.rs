@@ -0,0 +1,158 @@
+use crate::config::Config;
+use crate::data_types::{NotificationCategory, NotificationFrequency};
+use crate::guards::{Auth, DbConn, Jwt};
+use crate::models::{NotificationPreference, NotificationPreferenceChangeset, User};
+use crate::notifications::decode_unsubscribe_token;
+use crate::response::{MutationResponse, QueryResponse, Response, StatusResponse};
+use crate::schema::notification_preferences;
+use crate::views::NotificationPreferenceView;
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rocket_okapi::openapi;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PreferenceRequest {
+  category: NotificationCategory,
+  frequency: NotificationFrequency,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct UpdateNotificationPreferencesRequest {
+  /// Categories left out keep their current frequency.
+  preferences: Vec<PreferenceRequest>,
+}
+
+/// Every category with the user's frequency, or its default if unset.
+async fn preference_views(
+  db_conn: &DbConn,
+  user_id: Uuid,
+) -> QueryResult<Vec<NotificationPreferenceView>> {
+  let preferences = db_conn
+    .run(move |conn| {
+      NotificationPreference::filter_for_user(&user_id).load::<NotificationPreference>(conn)
+    })
+    .await?;
+
+  Ok(
+    NotificationCategory::ALL
+      .iter()
+      .map(|category| {
+        let frequency = preferences
+          .iter()
+          .find(|preference| preference.category == *category)
+          .map_or(category.default_frequency(), |preference| {
+            preference.frequency
+          });
+
+        NotificationPreferenceView::new(*category, frequency)
+      })
+      .collect(),
+  )
+}
+
+/// Sets the frequency of the given categories, keeping when they were last
+/// sent.
+fn upsert_preferences(
+  conn: &mut PgConnection,
+  user_id: Uuid,
+  preferences: Vec<(NotificationCategory, NotificationFrequency)>,
+) -> QueryResult<()> {
+  conn.transaction(|conn| {
+    for (category, frequency) in preferences {
+      diesel::insert_into(notification_preferences::table)
+        .values(
+          NotificationPreferenceChangeset::default()
+            .user_id(user_id)
+            .category(category)
+            .frequency(frequency),
+        )
+        .on_conflict((
+          notification_preferences::user_id,
+          notification_preferences::category,
+        ))
+        .do_update()
+        .set(notification_preferences::frequency.eq(frequency))
+        .execute(conn)?;
+    }
+
+    Ok(())
+  })
+}
+
+#[openapi(tag = "Ranklab")]
+#[get("/notification_preferences")]
+pub async fn list(
+  auth: Auth<Jwt>,
+  db_conn: DbConn,
+) -> QueryResponse<Vec<NotificationPreferenceView>> {
+  let user_id = auth.into_user().id;
+
+  Response::success(preference_views(&db_conn, user_id).await?)
+}
+
+#[openapi(tag = "Ranklab")]
+#[put("/notification_preferences", data = "<body>")]
+pub async fn update(
+  body: Json<UpdateNotificationPreferencesRequest>,
+  auth: Auth<Jwt>,
+  db_conn: DbConn,
+) -> MutationResponse<Vec<NotificationPreferenceView>> {
+  let user_id = auth.into_user().id;
+
+  let preferences = body
+    .preferences
+    .iter()
+    .map(|preference| (preference.category, preference.frequency))
+    .collect::<Vec<_>>();
+
+  db_conn
+    .run(move |conn| upsert_preferences(conn, user_id, preferences))
+    .await?;
+
+  Response::success(preference_views(&db_conn, user_id).await?)
+}
+
+/// One-click unsubscribe (RFC 8058) from the link in an email. The token
+/// stands in for authentication and the request body is ignored.
+#[openapi(tag = "Ranklab")]
+#[post("/unsubscribe/<token>")]
+pub async fn unsubscribe(
+  token: String,
+  config: &State<Config>,
+  db_conn: DbConn,
+) -> MutationResponse<StatusResponse> {
+  let claims = match decode_unsubscribe_token(config, &token) {
+    Some(claims) => claims,
+    None => return Response::mutation_error(Status::NotFound),
+  };
+
+  let user_id = claims.sub;
+
+  let preferences = match claims.category {
+    Some(category) => vec![(category, NotificationFrequency::Off)],
+    None => NotificationCategory::ALL
+      .iter()
+      .map(|category| (*category, NotificationFrequency::Off))
+      .collect(),
+  };
+
+  let user = db_conn
+    .run(move |conn| User::find_by_id(&user_id).first::<User>(conn).optional())
+    .await?;
+
+  // Tokens in old emails outlive the accounts they were sent to.
+  if user.is_none() {
+    return Response::mutation_error(Status::Gone);
+  }
+
+  db_conn
+    .run(move |conn| upsert_preferences(conn, user_id, preferences))
+    .await?;
+
+  Response::status(Status::NoContent)
+}
@@ -1,7 +1,9 @@
+use crate::audit;
 use crate::config::Config;
+use crate::data_types::{AuditAction, AuditTargetType};
 use crate::emails::{Email, Recipient};
 use crate::guards::auth::{Ott, ResetPassword};
-use crate::guards::{Auth, DbConn};
+use crate::guards::{Auth, ClientIp, DbConn};
 use crate::models::{OneTimeToken, OneTimeTokenChangeset, User, UserChangeset};
 use crate::response::{MutationResponse, Response, StatusResponse};
 use crate::schema::one_time_tokens;
@@ -34,6 +36,7 @@ pub async fn create(
   password: Json<CreatePasswordRequest>,
   config: &State<Config>,
   db_conn: DbConn,
+  ip: ClientIp,
 ) -> MutationResponse<StatusResponse> {
   let email = password.email.clone();
   let response = Response::status(Status::Ok);
@@ -47,12 +50,14 @@ pub async fn create(
   };
 
   let name = user.name.clone();
+  let event = audit::event(AuditAction::PasswordResetRequested, None, &ip)
+    .target(AuditTargetType::User, user.id);
 
   let token = db_conn
     .run(move |conn| {
       let value = Alphanumeric.sample_string(&mut rand::thread_rng(), 16);
 
-      diesel::insert_into(one_time_tokens::table)
+      let token = diesel::insert_into(one_time_tokens::table)
         .values(
           OneTimeTokenChangeset::default()
             .value(value)
@@ -60,7 +65,11 @@ pub async fn create(
             .user_id(Some(user.id)),
         )
         .get_result::<OneTimeToken>(conn)
-        .unwrap()
+        .unwrap();
+
+      audit::record(conn, event).unwrap();
+
+      token
     })
     .await;
 
@@ -95,6 +104,7 @@ pub async fn update(
   password: Json<UpdatePasswordRequest>,
   db_conn: DbConn,
   auth: Auth<Ott<ResetPassword>>,
+  ip: ClientIp,
 ) -> MutationResponse<StatusResponse> {
   let token = auth.into_token();
   let user_id = token.user_id.unwrap();
@@ -104,6 +114,8 @@ pub async fn update(
     .await;
 
   let password_hash = hash(&password.password, DEFAULT_COST).unwrap();
+  let event = audit::event(AuditAction::PasswordReset, Some(user.id), &ip)
+    .target(AuditTargetType::User, user.id);
 
   db_conn
     .run(move |conn| {
@@ -116,6 +128,8 @@ pub async fn update(
           .set(OneTimeTokenChangeset::default().used_at(Some(Utc::now().naive_utc())))
           .execute(conn)?;
 
+        audit::record(conn, event)?;
+
         Ok(user)
       })
     })
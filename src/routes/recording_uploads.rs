@@ -0,0 +1,372 @@
+use crate::aws::s3::{abort_upload, list_upload_parts, presign_upload_part, upload_metadata};
+use crate::config::Config;
+use crate::data_types::MediaState;
+use crate::guards::{Auth, DbConn, Jwt, S3};
+use crate::models::{Recording, RecordingChangeset};
+use crate::response::{
+  MutationError, MutationResponse, QueryError, QueryResponse, Response, StatusResponse,
+};
+use crate::schema::recordings;
+use crate::views::{UploadPartView, UploadSessionView};
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rocket_okapi::openapi;
+use rusoto_s3::{
+  CompleteMultipartUploadRequest, CompletedMultipartUpload, CompletedPart,
+  CreateMultipartUploadRequest, S3Client, S3 as _,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+const PART_SIZE: i64 = 64 * 1024 * 1024;
+const MAX_PARTS: i64 = 10_000;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CreateUploadRequest {
+  content_type: String,
+}
+
+#[derive(Deserialize, JsonSchema, Validate)]
+#[validate(schema(function = "validate_presign_parts"))]
+pub struct PresignPartsRequest {
+  #[validate(length(min = 1, max = 100))]
+  part_numbers: Vec<i64>,
+}
+
+fn validate_presign_parts(request: &PresignPartsRequest) -> Result<(), validator::ValidationError> {
+  if request
+    .part_numbers
+    .iter()
+    .any(|part_number| !(1..=MAX_PARTS).contains(part_number))
+  {
+    return Err(validator::ValidationError::new("invalid"));
+  }
+
+  Ok(())
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CompletedPartRequest {
+  part_number: i64,
+  e_tag: String,
+}
+
+#[derive(Deserialize, JsonSchema, Validate)]
+pub struct CompleteUploadRequest {
+  #[validate(length(min = 1))]
+  parts: Vec<CompletedPartRequest>,
+}
+
+/// Loads a recording of the user that is still waiting for its upload.
+async fn find_uploading(
+  db_conn: &DbConn,
+  user_id: Uuid,
+  id: Uuid,
+) -> Result<(Recording, String), MutationError> {
+  let recording = db_conn
+    .run(move |conn| Recording::find_for_user(&user_id, &id).first::<Recording>(conn))
+    .await?;
+
+  if recording.state != MediaState::Created || recording.deleted_at.is_some() {
+    return Err(MutationError::Status(Status::UnprocessableEntity));
+  }
+
+  let video_key = recording
+    .video_key
+    .clone()
+    .ok_or(MutationError::Status(Status::UnprocessableEntity))?;
+
+  Ok((recording, video_key))
+}
+
+async fn load_session(
+  client: &S3Client,
+  config: &Config,
+  recording_id: Uuid,
+  video_key: &str,
+  upload_id: String,
+) -> anyhow::Result<UploadSessionView> {
+  let parts = list_upload_parts(client, &config.uploads_bucket, video_key, &upload_id).await?;
+
+  Ok(UploadSessionView {
+    recording_id,
+    upload_id,
+    part_size: PART_SIZE,
+    max_parts: MAX_PARTS,
+    parts: parts.into_iter().map(UploadPartView::from).collect(),
+  })
+}
+
+#[openapi(tag = "Ranklab")]
+#[post("/recordings/<id>/upload", data = "<body>")]
+pub async fn create(
+  id: Uuid,
+  body: Json<CreateUploadRequest>,
+  auth: Auth<Jwt>,
+  db_conn: DbConn,
+  config: &State<Config>,
+  s3: S3,
+) -> MutationResponse<UploadSessionView> {
+  let user_id = auth.into_user().id;
+  let (recording, video_key) = find_uploading(&db_conn, user_id, id).await?;
+  let client = s3.into_inner();
+
+  // Starting an upload that is already in progress resumes it instead.
+  if let Some(upload_id) = recording.upload_id.clone() {
+    let session = load_session(&client, config, id, &video_key, upload_id)
+      .await
+      .map_err(|e| MutationError::InternalServerError(e.into()))?;
+
+    return Response::success(session);
+  }
+
+  if !config
+    .upload_limits
+    .recording_content_types
+    .contains(&body.content_type)
+  {
+    return Response::mutation_error(Status::UnprocessableEntity);
+  }
+
+  let upload = client
+    .create_multipart_upload(CreateMultipartUploadRequest {
+      bucket: config.uploads_bucket.clone(),
+      key: video_key.clone(),
+      content_type: Some(body.content_type.clone()),
+      metadata: Some(upload_metadata(config)),
+      ..Default::default()
+    })
+    .await
+    .map_err(|e| MutationError::InternalServerError(e.into()))?;
+
+  let upload_id = upload
+    .upload_id
+    .ok_or_else(|| MutationError::InternalServerError("S3 returned no upload id".into()))?;
+
+  let changeset = RecordingChangeset::default().upload_id(Some(upload_id.clone()));
+
+  // Only the first of two concurrent starts keeps its upload. The other one
+  // aborts its own, since unfinished uploads are billed, and resumes the
+  // winner's.
+  let stored = db_conn
+    .run(move |conn| {
+      diesel::update(&recording)
+        .filter(recordings::upload_id.is_null())
+        .set(changeset)
+        .get_result::<Recording>(conn)
+        .optional()
+    })
+    .await?;
+
+  if stored.is_none() {
+    if let Err(e) = abort_upload(&client, &config.uploads_bucket, &video_key, &upload_id).await {
+      error!(
+        "[recording_uploads] Failed to abort upload {}: {:?}",
+        upload_id, e
+      );
+      sentry::capture_error(e.root_cause());
+    }
+
+    let (recording, video_key) = find_uploading(&db_conn, user_id, id).await?;
+
+    let upload_id = recording
+      .upload_id
+      .ok_or(MutationError::Status(Status::Conflict))?;
+
+    let session = load_session(&client, config, id, &video_key, upload_id)
+      .await
+      .map_err(|e| MutationError::InternalServerError(e.into()))?;
+
+    return Response::success(session);
+  }
+
+  Response::success(UploadSessionView {
+    recording_id: id,
+    upload_id,
+    part_size: PART_SIZE,
+    max_parts: MAX_PARTS,
+    parts: vec![],
+  })
+}
+
+#[openapi(tag = "Ranklab")]
+#[get("/recordings/<id>/upload")]
+pub async fn get(
+  id: Uuid,
+  auth: Auth<Jwt>,
+  db_conn: DbConn,
+  config: &State<Config>,
+  s3: S3,
+) -> QueryResponse<UploadSessionView> {
+  let user_id = auth.into_user().id;
+
+  let recording = db_conn
+    .run(move |conn| Recording::find_for_user(&user_id, &id).first::<Recording>(conn))
+    .await?;
+
+  let (video_key, upload_id) = match (recording.video_key, recording.upload_id) {
+    (Some(video_key), Some(upload_id)) => (video_key, upload_id),
+    _ => return Response::query_error(Status::NotFound),
+  };
+
+  let session = load_session(&s3.into_inner(), config, id, &video_key, upload_id)
+    .await
+    .map_err(|e| QueryError::InternalServerError(e.into()))?;
+
+  Response::success(session)
+}
+
+#[openapi(tag = "Ranklab")]
+#[post("/recordings/<id>/upload/parts", data = "<body>")]
+pub async fn presign_parts(
+  id: Uuid,
+  body: Json<PresignPartsRequest>,
+  auth: Auth<Jwt>,
+  db_conn: DbConn,
+  config: &State<Config>,
+) -> MutationResponse<Vec<UploadPartView>> {
+  if let Err(errors) = body.validate() {
+    return Response::validation_error(errors);
+  }
+
+  let (recording, video_key) = find_uploading(&db_conn, auth.into_user().id, id).await?;
+
+  let upload_id = recording
+    .upload_id
+    .ok_or(MutationError::Status(Status::NotFound))?;
+
+  let parts = body
+    .part_numbers
+    .iter()
+    .map(|part_number| {
+      UploadPartView::presigned(
+        *part_number,
+        presign_upload_part(config, &video_key, &upload_id, *part_number),
+      )
+    })
+    .collect();
+
+  Response::success(parts)
+}
+
+#[openapi(tag = "Ranklab")]
+#[put("/recordings/<id>/upload", data = "<body>")]
+pub async fn complete(
+  id: Uuid,
+  body: Json<CompleteUploadRequest>,
+  auth: Auth<Jwt>,
+  db_conn: DbConn,
+  config: &State<Config>,
+  s3: S3,
+) -> MutationResponse<StatusResponse> {
+  if let Err(errors) = body.validate() {
+    return Response::validation_error(errors);
+  }
+
+  let (recording, video_key) = find_uploading(&db_conn, auth.into_user().id, id).await?;
+
+  let upload_id = recording
+    .upload_id
+    .clone()
+    .ok_or(MutationError::Status(Status::NotFound))?;
+
+  let client = s3.into_inner();
+
+  let uploaded_size = list_upload_parts(&client, &config.uploads_bucket, &video_key, &upload_id)
+    .await
+    .map_err(|e| MutationError::InternalServerError(e.into()))?
+    .iter()
+    .filter_map(|part| part.size)
+    .sum::<i64>();
+
+  if uploaded_size > config.upload_limits.max_recording_size {
+    // Parts of an unfinished upload are billed until it's aborted.
+    abort_upload(&client, &config.uploads_bucket, &video_key, &upload_id)
+      .await
+      .map_err(|e| MutationError::InternalServerError(e.into()))?;
+
+    db_conn
+      .run(move |conn| {
+        diesel::update(&recording)
+          .set(RecordingChangeset::default().upload_id(None))
+          .execute(conn)
+      })
+      .await?;
+
+    return Response::mutation_error(Status::PayloadTooLarge);
+  }
+
+  let mut parts = body
+    .parts
+    .iter()
+    .map(|part| CompletedPart {
+      e_tag: Some(part.e_tag.clone()),
+      part_number: Some(part.part_number),
+    })
+    .collect::<Vec<_>>();
+
+  parts.sort_by_key(|part| part.part_number);
+
+  // Completing the upload emits the same bucket event as a single PutObject,
+  // so processing continues through the uploads queue.
+  client
+    .complete_multipart_upload(CompleteMultipartUploadRequest {
+      bucket: config.uploads_bucket.clone(),
+      key: video_key,
+      upload_id,
+      multipart_upload: Some(CompletedMultipartUpload { parts: Some(parts) }),
+      ..Default::default()
+    })
+    .await
+    .map_err(|e| MutationError::InternalServerError(e.into()))?;
+
+  db_conn
+    .run(move |conn| {
+      diesel::update(&recording)
+        .set(RecordingChangeset::default().upload_id(None))
+        .execute(conn)
+    })
+    .await?;
+
+  Response::status(Status::Ok)
+}
+
+#[openapi(tag = "Ranklab")]
+#[delete("/recordings/<id>/upload")]
+pub async fn abort(
+  id: Uuid,
+  auth: Auth<Jwt>,
+  db_conn: DbConn,
+  config: &State<Config>,
+  s3: S3,
+) -> MutationResponse<StatusResponse> {
+  let (recording, video_key) = find_uploading(&db_conn, auth.into_user().id, id).await?;
+
+  let upload_id = recording
+    .upload_id
+    .clone()
+    .ok_or(MutationError::Status(Status::NotFound))?;
+
+  abort_upload(
+    &s3.into_inner(),
+    &config.uploads_bucket,
+    &video_key,
+    &upload_id,
+  )
+  .await
+  .map_err(|e| MutationError::InternalServerError(e.into()))?;
+
+  db_conn
+    .run(move |conn| {
+      diesel::update(&recording)
+        .set(RecordingChangeset::default().upload_id(None))
+        .execute(conn)
+    })
+    .await?;
+
+  Response::status(Status::NoContent)
+}
@@ -1,59 +1,114 @@
+use crate::audit;
+use crate::aws::s3::{get_object_text, presign_download_for};
 use crate::config::Config;
-use crate::data_types::MediaState;
-use crate::games;
-use crate::games::GameId;
-use crate::guards::{Auth, DbConn, Jwt, S3};
-use crate::models::{Recording, RecordingChangeset, RecordingWithCommentCount, User};
+use crate::data_types::{AuditAction, AuditTargetType, MediaState, TaxonomyKind};
+use crate::games::{self, CatalogueGame};
+use crate::guards::{Auth, ClientIp, DbConn, Jwt, S3};
+use crate::models::{
+  ProcessingJob, Recording, RecordingChangeset, RecordingWithCommentCount, ReviewScore, User,
+};
 use crate::pagination::{Paginate, PaginatedResult};
-use crate::response::{MutationResponse, QueryResponse, Response, StatusResponse};
-use crate::schema::recordings;
-use crate::views::RecordingView;
+use crate::response::{MutationResponse, QueryError, QueryResponse, Response, StatusResponse};
+use crate::schema::{recordings, users};
+use crate::upload_limits::presign_recording_upload;
+use crate::views::{RecordingProcessingView, RecordingView};
+use chrono::{Duration, Utc};
 use diesel::prelude::*;
-use rocket::http::Status;
+use rocket::http::{ContentType, Status};
 use rocket::serde::json::Json;
 use rocket::State;
 use rocket_okapi::openapi;
-use rusoto_core::Region;
-use rusoto_credential::AwsCredentials;
-use rusoto_s3::util::PreSignedRequest;
-use rusoto_s3::{Delete, DeleteObjectsRequest, ObjectIdentifier, PutObjectRequest, S3 as RusotoS3};
 use schemars::JsonSchema;
 use serde::Deserialize;
-use std::collections::{HashMap, HashSet};
+use serde_json::json;
+use std::collections::HashSet;
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidationError, ValidationErrors};
 
 #[derive(Deserialize, JsonSchema, Validate)]
-#[validate(schema(function = "validate_recording"))]
 pub struct CreateRecordingRequest {
   #[validate(length(min = 1))]
   title: String,
   skill_level: i16,
-  game_id: GameId,
+  game_id: String,
   notes: String,
+  /// Keys from the game's maps, roles and heroes.
+  #[serde(default)]
+  map: Option<String>,
+  #[serde(default)]
+  role: Option<String>,
+  #[serde(default)]
+  hero: Option<String>,
 }
 
+/// Checks the ranks and taxonomy entries against the recording's game, on
+/// top of the request's own validations.
 fn validate_recording(
   recording: &CreateRecordingRequest,
-) -> Result<(), validator::ValidationError> {
-  let game = games::find(&recording.game_id.to_string()).unwrap();
-
-  if !game
-    .skill_levels
-    .iter()
-    .any(|skill_level| skill_level.value == recording.skill_level as u8)
-  {
-    return Err(validator::ValidationError::new("invalid"));
+  game: Option<&CatalogueGame>,
+) -> Result<(), ValidationErrors> {
+  let mut errors = recording
+    .validate()
+    .err()
+    .unwrap_or_else(ValidationErrors::new);
+
+  match game {
+    Some(game) => {
+      if !game.accepts_skill_level(recording.skill_level) {
+        errors.add("skill_level", ValidationError::new("invalid"));
+      }
+
+      for (field, kind, key) in [
+        ("map", TaxonomyKind::Map, &recording.map),
+        ("role", TaxonomyKind::Role, &recording.role),
+        ("hero", TaxonomyKind::Hero, &recording.hero),
+      ] {
+        if !game.accepts_taxonomy_entry(kind, key.as_deref()) {
+          errors.add(field, ValidationError::new("invalid"));
+        }
+      }
+    }
+    None => errors.add("game_id", ValidationError::new("invalid")),
   }
 
-  Ok(())
+  if errors.is_empty() {
+    Ok(())
+  } else {
+    Err(errors)
+  }
 }
 
 #[derive(FromForm, JsonSchema)]
 pub struct ListParams {
   page: Option<i64>,
   only_own: Option<bool>,
-  game_id: Option<GameId>,
+  game_id: Option<String>,
+  map: Option<String>,
+  role: Option<String>,
+  hero: Option<String>,
+  /// Rank range as skill level values, compared by the ranks' order.
+  min_skill_level: Option<i16>,
+  max_skill_level: Option<i16>,
+}
+
+/// Loads a recording the viewer is allowed to see, treating hidden and
+/// deleted recordings as missing.
+pub(crate) async fn find_visible_recording(
+  db_conn: &DbConn,
+  viewer_id: Option<Uuid>,
+  recording_id: Uuid,
+) -> QueryResult<Recording> {
+  let recording = db_conn
+    .run(move |conn| Recording::find_by_id(&recording_id).first::<Recording>(conn))
+    .await?;
+
+  if recording.deleted_at.is_some()
+    || (recording.hidden_at.is_some() && viewer_id != Some(recording.user_id))
+  {
+    return Err(diesel::result::Error::NotFound);
+  }
+
+  Ok(recording)
 }
 
 #[openapi(tag = "Ranklab")]
@@ -61,11 +116,25 @@ pub struct ListParams {
 pub async fn list(
   auth: Auth<Option<Jwt>>,
   db_conn: DbConn,
+  config: &State<Config>,
   params: ListParams,
 ) -> QueryResponse<PaginatedResult<RecordingView>> {
   let user = auth.into_user();
+  let viewer_id = user.as_ref().map(|user| user.id);
   let page = params.page.unwrap_or(1);
 
+  // Shadow-banned users only ever see their own recordings.
+  let excluded_user_ids = db_conn
+    .run(move |conn| {
+      User::filter_shadow_banned()
+        .select(users::id)
+        .load::<Uuid>(conn)
+    })
+    .await?
+    .into_iter()
+    .filter(|id| Some(*id) != viewer_id)
+    .collect::<Vec<_>>();
+
   let recordings = if params.only_own.unwrap_or(false) {
     if let Some(user) = user {
       let user_id = user.id;
@@ -73,6 +142,7 @@ pub async fn list(
       db_conn
         .run(move |conn| {
           Recording::filter_for_user(&user_id)
+            .filter(recordings::user_id.ne_all(excluded_user_ids))
             .paginate(page)
             .load_and_count_pages::<RecordingWithCommentCount>(conn)
             .unwrap()
@@ -82,9 +152,18 @@ pub async fn list(
       return Response::query_error(Status::BadRequest);
     }
   } else if let Some(game_id) = params.game_id {
+    let (map, role, hero) = (params.map, params.role, params.hero);
+
+    let skill_levels = if params.min_skill_level.is_some() || params.max_skill_level.is_some() {
+      let game = games::find(&db_conn, &game_id).await?;
+      Some(game.skill_levels_between(params.min_skill_level, params.max_skill_level))
+    } else {
+      None
+    };
+
     db_conn
       .run(move |conn| {
-        Recording::filter_by_game_id(&game_id.to_string())
+        Recording::filter_by_game_id(&game_id, map, role, hero, skill_levels, excluded_user_ids)
           .paginate(page)
           .load_and_count_pages::<RecordingWithCommentCount>(conn)
           .unwrap()
@@ -111,6 +190,18 @@ pub async fn list(
     })
     .await;
 
+  let recording_ids = recordings
+    .records
+    .iter()
+    .map(|recording| recording.recording.id)
+    .collect::<Vec<_>>();
+
+  let review_scores = db_conn
+    .run(move |conn| {
+      ReviewScore::filter_for_recordings(recording_ids).load::<(Uuid, ReviewScore)>(conn)
+    })
+    .await?;
+
   let recording_views = recordings
     .records
     .clone()
@@ -122,12 +213,20 @@ pub async fn list(
         .find(|user| user.id == recording.recording.user_id)
         .unwrap();
 
+      let scores = review_scores
+        .iter()
+        .filter(|(recording_id, _)| *recording_id == recording.recording.id)
+        .map(|(_, score)| score.clone())
+        .collect();
+
       RecordingView::new(
         recording.recording,
+        config,
         None,
         None,
         Some(user),
         Some(recording.comment_count),
+        Some(scores),
       )
     })
     .collect::<Vec<RecordingView>>();
@@ -143,7 +242,9 @@ pub async fn create(
   auth: Auth<Jwt>,
   recording: Json<CreateRecordingRequest>,
 ) -> MutationResponse<RecordingView> {
-  if let Err(errors) = recording.validate() {
+  let game = games::find(&db_conn, &recording.game_id).await.optional()?;
+
+  if let Err(errors) = validate_recording(&recording, game.as_ref()) {
     return Response::validation_error(errors);
   }
 
@@ -158,9 +259,12 @@ pub async fn create(
         .values(
           RecordingChangeset::default()
             .user_id(user_id)
-            .game_id(recording.game_id.to_string())
+            .game_id(recording.game_id.clone())
             .title(recording.title.clone())
             .skill_level(recording.skill_level)
+            .map(recording.map.clone())
+            .role(recording.role.clone())
+            .hero(recording.hero.clone())
             .video_key(key)
             .state(state)
             .notes(ammonia::clean(&recording.notes)),
@@ -170,17 +274,19 @@ pub async fn create(
     })
     .await;
 
-  let url = recording
+  let upload = recording
     .video_key
     .as_ref()
-    .map(|video_key| create_upload_url(config, video_key));
+    .map(|video_key| presign_recording_upload(config, video_key));
 
   Response::success(RecordingView::new(
     recording,
-    url,
+    config,
+    upload,
     config.instance_id.clone(),
     Some(user),
     None,
+    None,
   ))
 }
 
@@ -188,111 +294,218 @@ pub async fn create(
 #[get("/recordings/<id>")]
 pub async fn get(
   id: Uuid,
-  #[allow(unused_variables)] auth: Auth<Option<Jwt>>,
+  auth: Auth<Option<Jwt>>,
   db_conn: DbConn,
   config: &State<Config>,
 ) -> QueryResponse<RecordingView> {
+  let viewer_id = auth.into_user().map(|user| user.id);
+
   let recording = db_conn
     .run(move |conn| Recording::find_by_id(&id).first::<Recording>(conn))
     .await?;
 
+  if recording.deleted_at.is_some() {
+    return Response::query_error(Status::NotFound);
+  }
+
+  let is_owner = viewer_id == Some(recording.user_id);
+
+  if recording.hidden_at.is_some() && !is_owner {
+    return Response::query_error(Status::NotFound);
+  }
+
   let recording_user_id = recording.user_id;
 
   let recording_user = db_conn
     .run(move |conn| User::find_by_id(&recording_user_id).first::<User>(conn))
     .await?;
 
-  let url = recording
+  let review_scores = db_conn
+    .run(move |conn| ReviewScore::filter_for_recording(&id).load::<ReviewScore>(conn))
+    .await?;
+
+  let upload = recording
     .video_key
     .as_ref()
-    .map(|video_key| create_upload_url(config, video_key));
+    .filter(|_| is_owner && recording.state == MediaState::Created)
+    .map(|video_key| presign_recording_upload(config, video_key));
 
   Response::success(RecordingView::new(
     recording,
-    url,
+    config,
+    upload,
     None,
     Some(recording_user),
     None,
+    Some(review_scores),
   ))
 }
 
+/// Total length of a media playlist in seconds, from its `#EXTINF` tags.
+fn playlist_duration(playlist: &str) -> f64 {
+  playlist
+    .lines()
+    .filter_map(|line| line.strip_prefix("#EXTINF:"))
+    .filter_map(|info| info.split(',').next()?.trim().parse::<f64>().ok())
+    .sum()
+}
+
+/// Serves an HLS playlist with every segment URL signed, since the segments
+/// are private and relative URIs can't carry a signature.
+#[openapi(skip)]
+#[get("/recordings/<id>/playlists/<name>")]
+pub async fn get_playlist(
+  id: Uuid,
+  name: String,
+  auth: Auth<Option<Jwt>>,
+  db_conn: DbConn,
+  config: &State<Config>,
+  s3: S3,
+) -> Result<(ContentType, String), QueryError> {
+  let viewer_id = auth.into_user().map(|user| user.id);
+  let recording = find_visible_recording(&db_conn, viewer_id, id).await?;
+
+  let playlist_key = recording
+    .playlist_key
+    .ok_or(QueryError::Status(Status::NotFound))?;
+
+  let (directory, file_name) = playlist_key
+    .rsplit_once('/')
+    .ok_or(QueryError::Status(Status::NotFound))?;
+
+  // Variant playlists sit next to the main one and share its stem.
+  if name.contains('/')
+    || !name.ends_with(".m3u8")
+    || !name.starts_with(file_name.trim_end_matches(".m3u8"))
+  {
+    return Err(QueryError::Status(Status::NotFound));
+  }
+
+  let playlist = get_object_text(
+    &s3.into_inner(),
+    &config.uploads_bucket,
+    &format!("{}/{}", directory, name),
+  )
+  .await
+  .map_err(|e| QueryError::InternalServerError(e.into()))?;
+
+  // Players fetch segments as they go, so their URLs have to stay valid for
+  // at least as long as it takes to watch the whole playlist.
+  let ttl = config.download_url_ttl + playlist_duration(&playlist).ceil() as u64;
+
+  let playlist = playlist
+    .lines()
+    .map(|line| {
+      if line.is_empty() || line.starts_with('#') {
+        line.to_owned()
+      } else if line.ends_with(".m3u8") {
+        format!("{}/recordings/{}/playlists/{}", config.host, id, line)
+      } else {
+        presign_download_for(config, &format!("{}/{}", directory, line), ttl)
+      }
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+
+  Ok((
+    ContentType::new("application", "vnd.apple.mpegurl"),
+    playlist,
+  ))
+}
+
+#[openapi(tag = "Ranklab")]
+#[get("/recordings/<id>/processing")]
+pub async fn get_processing(
+  id: Uuid,
+  auth: Auth<Jwt>,
+  db_conn: DbConn,
+) -> QueryResponse<RecordingProcessingView> {
+  let user_id = auth.into_user().id;
+
+  let recording = db_conn
+    .run(move |conn| Recording::find_for_user(&user_id, &id).first::<Recording>(conn))
+    .await?;
+
+  let jobs = db_conn
+    .run(move |conn| ProcessingJob::filter_by_recording_id(&id).load::<ProcessingJob>(conn))
+    .await?;
+
+  Response::success(RecordingProcessingView::new(recording, jobs))
+}
+
 #[openapi(tag = "Ranklab")]
 #[delete("/recordings/<id>")]
 pub async fn delete(
   id: Uuid,
   auth: Auth<Jwt>,
   db_conn: DbConn,
-  config: &State<Config>,
-  s3: S3,
+  ip: ClientIp,
 ) -> MutationResponse<StatusResponse> {
   let user_id = auth.into_user().id;
-  let s3 = s3.into_inner();
 
   let recording: Recording = db_conn
     .run(move |conn| Recording::find_processed_for_user(&user_id, &id).first::<Recording>(conn))
     .await?;
 
-  let mut objects_to_delete = vec![];
-
-  if let Some(video_key) = &recording.video_key {
-    objects_to_delete.push(video_key.clone());
-  }
-
-  if let Some(thumbnail_key) = &recording.thumbnail_key {
-    objects_to_delete.push(thumbnail_key.clone());
-  }
-
-  if let Some(processed_video_key) = &recording.processed_video_key {
-    objects_to_delete.push(processed_video_key.clone());
-  }
-
-  let req = DeleteObjectsRequest {
-    bucket: config.uploads_bucket.to_owned(),
-    delete: Delete {
-      objects: objects_to_delete
-        .into_iter()
-        .map(|key| ObjectIdentifier {
-          key: key.clone(),
-          ..Default::default()
-        })
-        .collect(),
-      ..Default::default()
-    },
-    ..Default::default()
-  };
-
-  s3.delete_objects(req).await.unwrap();
+  let event = audit::event(AuditAction::RecordingDeleted, Some(user_id), &ip)
+    .target(AuditTargetType::Recording, recording.id)
+    .payload(json!({ "game_id": recording.game_id, "video_key": recording.video_key }));
 
   db_conn
-    .run(move |conn| diesel::delete(&recording).execute(conn))
+    .run::<_, QueryResult<_>>(move |conn| {
+      conn.transaction(|conn| {
+        diesel::update(&recording)
+          .set(RecordingChangeset::default().deleted_at(Some(Utc::now().naive_utc())))
+          .execute(conn)?;
+
+        audit::record(conn, event)
+      })
+    })
     .await?;
 
   Response::status(Status::NoContent)
 }
 
-fn create_upload_url(config: &Config, recording_video_key: &String) -> String {
-  let mut metadata = HashMap::new();
-
-  if let Some(instance_id) = config.instance_id.as_ref() {
-    metadata.insert("instance-id".to_string(), instance_id.to_string());
-  }
+#[openapi(tag = "Ranklab")]
+#[post("/recordings/<id>/restore")]
+pub async fn restore(
+  id: Uuid,
+  auth: Auth<Jwt>,
+  db_conn: DbConn,
+  config: &State<Config>,
+  ip: ClientIp,
+) -> MutationResponse<RecordingView> {
+  let user = auth.into_user();
+  let user_id = user.id;
+  let deleted_since = Utc::now().naive_utc() - Duration::days(config.deletion_retention_days);
+  let event = audit::event(AuditAction::RecordingRestored, Some(user_id), &ip)
+    .target(AuditTargetType::Recording, id);
 
-  let req = PutObjectRequest {
-    bucket: config.uploads_bucket.to_owned(),
-    key: recording_video_key.to_owned(),
-    acl: Some("public-read".to_string()),
-    metadata: Some(metadata),
-    ..Default::default()
-  };
+  let recording = db_conn
+    .run(move |conn| {
+      conn.transaction(|conn| {
+        let recording = diesel::update(Recording::find_deleted_for_user(
+          &user_id,
+          &id,
+          deleted_since,
+        ))
+        .set(RecordingChangeset::default().deleted_at(None))
+        .get_result::<Recording>(conn)?;
+
+        audit::record(conn, event)?;
+
+        Ok::<_, diesel::result::Error>(recording)
+      })
+    })
+    .await?;
 
-  req.get_presigned_url(
-    &Region::EuWest2,
-    &AwsCredentials::new(
-      &config.aws_access_key_id,
-      &config.aws_secret_key,
-      None,
-      None,
-    ),
-    &Default::default(),
-  )
+  Response::success(RecordingView::new(
+    recording,
+    config,
+    None,
+    None,
+    Some(user),
+    None,
+    None,
+  ))
 }
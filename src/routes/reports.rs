@@ -0,0 +1,90 @@
+use crate::config::Config;
+use crate::data_types::ReportReason;
+use crate::guards::{Auth, DbConn, Jwt};
+use crate::models::{ReportChangeset, ReportTarget};
+use crate::reports;
+use crate::response::{MutationResponse, Response, StatusResponse};
+use crate::schema::reports as reports_table;
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rocket_okapi::openapi;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Deserialize, JsonSchema, Validate)]
+#[validate(schema(function = "validate_report"))]
+pub struct CreateReportRequest {
+  recording_id: Option<Uuid>,
+  comment_id: Option<Uuid>,
+  reason: ReportReason,
+  #[validate(length(max = 2000))]
+  details: String,
+}
+
+fn validate_report(report: &CreateReportRequest) -> Result<(), validator::ValidationError> {
+  if report.recording_id.is_some() == report.comment_id.is_some() {
+    return Err(validator::ValidationError::new("invalid"));
+  }
+
+  Ok(())
+}
+
+#[openapi(tag = "Ranklab")]
+#[post("/reports", data = "<report>")]
+pub async fn create(
+  report: Json<CreateReportRequest>,
+  auth: Auth<Jwt>,
+  db_conn: DbConn,
+  config: &State<Config>,
+) -> MutationResponse<StatusResponse> {
+  if let Err(errors) = report.validate() {
+    return Response::validation_error(errors);
+  }
+
+  let reporter_id = auth.into_user().id;
+  let hide_threshold = config.report_hide_threshold;
+
+  let target = match (report.recording_id, report.comment_id) {
+    (Some(recording_id), _) => ReportTarget::Recording(recording_id),
+    (None, Some(comment_id)) => ReportTarget::Comment(comment_id),
+    (None, None) => return Response::mutation_error(Status::UnprocessableEntity),
+  };
+
+  let author_id = db_conn
+    .run(move |conn| reports::author_id(conn, target))
+    .await?;
+
+  if author_id == reporter_id {
+    return Response::mutation_error(Status::UnprocessableEntity);
+  }
+
+  db_conn
+    .run::<_, QueryResult<_>>(move |conn| {
+      conn.transaction(|conn| {
+        diesel::insert_into(reports_table::table)
+          .values(
+            ReportChangeset::default()
+              .reporter_id(reporter_id)
+              .recording_id(report.recording_id)
+              .comment_id(report.comment_id)
+              .reason(report.reason)
+              .details(report.details.clone()),
+          )
+          .on_conflict_do_nothing()
+          .execute(conn)?;
+
+        if reports::count_open(conn, target)? >= hide_threshold {
+          reports::hide(conn, target)?;
+        }
+
+        Ok(())
+      })
+    })
+    .await?;
+
+  Response::status(Status::Created)
+}
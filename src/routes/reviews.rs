@@ -0,0 +1,383 @@
+use crate::data_types::MediaState;
+use crate::games;
+use crate::guards::{Auth, DbConn, Jwt};
+use crate::models::{
+  Review, ReviewChangeset, ReviewScore, ReviewScoreChangeset, User, MAX_REVIEW_SCORE,
+  MIN_REVIEW_SCORE,
+};
+use crate::response::{MutationError, MutationResponse, QueryResponse, Response, StatusResponse};
+use crate::routes::recordings::find_visible_recording;
+use crate::schema::{review_scores, reviews};
+use crate::views::ReviewView;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::result::DatabaseErrorKind;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket_okapi::openapi;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::collections::HashSet;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ScoreRequest {
+  category: String,
+  score: i16,
+  comment: String,
+}
+
+#[derive(Deserialize, JsonSchema, Validate)]
+pub struct CreateReviewRequest {
+  recording_id: Uuid,
+  #[validate(length(max = 10000))]
+  body: String,
+  #[validate(range(min = 1, max = 5))]
+  rating: i16,
+  #[validate(length(max = 5000))]
+  strengths: String,
+  #[validate(length(max = 5000))]
+  weaknesses: String,
+  #[validate(length(max = 1000))]
+  focus_area: String,
+  /// Leave out for a review without rubric scores.
+  #[serde(default)]
+  scores: Vec<ScoreRequest>,
+}
+
+#[derive(Deserialize, JsonSchema, Validate)]
+pub struct UpdateReviewRequest {
+  #[validate(length(max = 10000))]
+  body: String,
+  #[validate(range(min = 1, max = 5))]
+  rating: i16,
+  #[validate(length(max = 5000))]
+  strengths: String,
+  #[validate(length(max = 5000))]
+  weaknesses: String,
+  #[validate(length(max = 1000))]
+  focus_area: String,
+  /// Leave out for a review without rubric scores.
+  #[serde(default)]
+  scores: Vec<ScoreRequest>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct UpdateReviewFeedbackRequest {
+  accepted: bool,
+  helpful: bool,
+}
+
+#[derive(FromForm, JsonSchema)]
+pub struct ListParams {
+  recording_id: Uuid,
+}
+
+/// Checks that every category of the game's rubric is scored exactly once.
+/// Scores can also be left out entirely for a review that only gives a
+/// summary and an overall rating.
+async fn valid_scores(
+  db_conn: &DbConn,
+  game_id: &str,
+  scores: &[ScoreRequest],
+) -> QueryResult<bool> {
+  if scores.is_empty() {
+    return Ok(true);
+  }
+
+  let game = match games::find(db_conn, game_id).await.optional()? {
+    Some(game) => game,
+    None => return Ok(false),
+  };
+
+  let categories = scores
+    .iter()
+    .map(|score| score.category.as_str())
+    .collect::<HashSet<_>>();
+
+  Ok(
+    categories.len() == scores.len()
+      && categories.len() == game.review_categories.len()
+      && game
+        .review_categories
+        .iter()
+        .all(|category| categories.contains(category.key.as_str()))
+      && scores
+        .iter()
+        .all(|score| (MIN_REVIEW_SCORE..=MAX_REVIEW_SCORE).contains(&score.score)),
+  )
+}
+
+fn insert_scores(
+  conn: &mut PgConnection,
+  review_id: Uuid,
+  scores: &[ScoreRequest],
+) -> QueryResult<Vec<ReviewScore>> {
+  if scores.is_empty() {
+    return Ok(vec![]);
+  }
+
+  let changesets = scores
+    .iter()
+    .map(|score| {
+      ReviewScoreChangeset::default()
+        .review_id(review_id)
+        .category(score.category.clone())
+        .score(score.score)
+        .comment(ammonia::clean(&score.comment))
+    })
+    .collect::<Vec<_>>();
+
+  diesel::insert_into(review_scores::table)
+    .values(changesets)
+    .get_results::<ReviewScore>(conn)
+}
+
+#[openapi(tag = "Ranklab")]
+#[post("/reviews", data = "<review>")]
+pub async fn create(
+  review: Json<CreateReviewRequest>,
+  auth: Auth<Jwt>,
+  db_conn: DbConn,
+) -> MutationResponse<ReviewView> {
+  if let Err(errors) = review.validate() {
+    return Response::validation_error(errors);
+  }
+
+  let user = auth.into_user();
+  let reviewer_id = user.id;
+  let recording = find_visible_recording(&db_conn, Some(reviewer_id), review.recording_id).await?;
+
+  if recording.user_id == reviewer_id {
+    return Response::mutation_error(Status::Forbidden);
+  }
+
+  if recording.state != MediaState::Processed
+    || !valid_scores(&db_conn, &recording.game_id, &review.scores).await?
+  {
+    return Response::mutation_error(Status::UnprocessableEntity);
+  }
+
+  let (review, scores) = db_conn
+    .run::<_, Result<_, MutationError>>(move |conn| {
+      conn.transaction(|conn| {
+        let existing = reviews::table
+          .filter(reviews::recording_id.eq(recording.id))
+          .filter(reviews::reviewer_id.eq(reviewer_id))
+          .first::<Review>(conn)
+          .optional()?;
+
+        if existing.is_some() {
+          return Err(MutationError::Status(Status::Conflict));
+        }
+
+        let created = diesel::insert_into(reviews::table)
+          .values(
+            ReviewChangeset::default()
+              .recording_id(recording.id)
+              .reviewer_id(reviewer_id)
+              .body(ammonia::clean(&review.body))
+              .rating(Some(review.rating))
+              .strengths(ammonia::clean(&review.strengths))
+              .weaknesses(ammonia::clean(&review.weaknesses))
+              .focus_area(ammonia::clean(&review.focus_area)),
+          )
+          .get_result::<Review>(conn)
+          .map_err(|err| match err {
+            // A concurrent request got past the check above.
+            diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+              MutationError::Status(Status::Conflict)
+            }
+            err => err.into(),
+          })?;
+
+        let scores = insert_scores(conn, created.id, &review.scores)?;
+
+        Ok((created, scores))
+      })
+    })
+    .await?;
+
+  Response::success(ReviewView::new(review, scores, Some(user)))
+}
+
+#[openapi(tag = "Ranklab")]
+#[get("/reviews?<params..>")]
+pub async fn list(
+  params: ListParams,
+  auth: Auth<Option<Jwt>>,
+  db_conn: DbConn,
+) -> QueryResponse<Vec<ReviewView>> {
+  let recording_id = params.recording_id;
+  let viewer_id = auth.into_user().map(|user| user.id);
+
+  find_visible_recording(&db_conn, viewer_id, recording_id).await?;
+
+  let (reviews, scores, users) = db_conn
+    .run(move |conn| {
+      let reviews = Review::filter_by_recording_id(&recording_id).load::<Review>(conn)?;
+      let review_ids = reviews.iter().map(|review| review.id).collect();
+      let reviewer_ids = reviews.iter().map(|review| review.reviewer_id).collect();
+      let scores = ReviewScore::filter_by_review_ids(review_ids).load::<ReviewScore>(conn)?;
+      let users = User::filter_by_ids(reviewer_ids).load::<User>(conn)?;
+
+      QueryResult::Ok((reviews, scores, users))
+    })
+    .await?;
+
+  let reviews = reviews
+    .into_iter()
+    .filter_map(|review| {
+      let reviewer = users
+        .iter()
+        .find(|user| user.id == review.reviewer_id)
+        .cloned();
+
+      // Shadow-banned reviewers still see their own reviews.
+      if let Some(reviewer) = &reviewer {
+        if reviewer.is_shadow_banned() && Some(reviewer.id) != viewer_id {
+          return None;
+        }
+      }
+
+      let review_scores = scores
+        .iter()
+        .filter(|score| score.review_id == review.id)
+        .cloned()
+        .collect();
+
+      Some(ReviewView::new(review, review_scores, reviewer))
+    })
+    .collect();
+
+  Response::success(reviews)
+}
+
+#[openapi(tag = "Ranklab")]
+#[put("/reviews/<id>", data = "<review>")]
+pub async fn update(
+  id: Uuid,
+  review: Json<UpdateReviewRequest>,
+  auth: Auth<Jwt>,
+  db_conn: DbConn,
+) -> MutationResponse<ReviewView> {
+  if let Err(errors) = review.validate() {
+    return Response::validation_error(errors);
+  }
+
+  let user = auth.into_user();
+  let reviewer_id = user.id;
+
+  let existing = db_conn
+    .run(move |conn| Review::find_for_reviewer(&reviewer_id, &id).first::<Review>(conn))
+    .await?;
+
+  let recording =
+    find_visible_recording(&db_conn, Some(reviewer_id), existing.recording_id).await?;
+
+  if !valid_scores(&db_conn, &recording.game_id, &review.scores).await? {
+    return Response::mutation_error(Status::UnprocessableEntity);
+  }
+
+  let (review, scores) = db_conn
+    .run::<_, QueryResult<_>>(move |conn| {
+      conn.transaction(|conn| {
+        let updated = diesel::update(&existing)
+          .set(
+            ReviewChangeset::default()
+              .body(ammonia::clean(&review.body))
+              .rating(Some(review.rating))
+              .strengths(ammonia::clean(&review.strengths))
+              .weaknesses(ammonia::clean(&review.weaknesses))
+              .focus_area(ammonia::clean(&review.focus_area)),
+          )
+          .get_result::<Review>(conn)?;
+
+        diesel::delete(review_scores::table.filter(review_scores::review_id.eq(updated.id)))
+          .execute(conn)?;
+
+        let scores = insert_scores(conn, updated.id, &review.scores)?;
+
+        Ok((updated, scores))
+      })
+    })
+    .await?;
+
+  Response::success(ReviewView::new(review, scores, Some(user)))
+}
+
+/// Lets the recording's owner mark a review as accepted or helpful. Only one
+/// review per recording can be accepted at a time.
+#[openapi(tag = "Ranklab")]
+#[put("/reviews/<id>/feedback", data = "<feedback>")]
+pub async fn update_feedback(
+  id: Uuid,
+  feedback: Json<UpdateReviewFeedbackRequest>,
+  auth: Auth<Jwt>,
+  db_conn: DbConn,
+) -> MutationResponse<ReviewView> {
+  let user_id = auth.into_user().id;
+
+  let review = db_conn
+    .run(move |conn| Review::find_by_id(&id).first::<Review>(conn))
+    .await?;
+
+  let recording = find_visible_recording(&db_conn, Some(user_id), review.recording_id).await?;
+
+  if recording.user_id != user_id {
+    return Response::mutation_error(Status::NotFound);
+  }
+
+  let now = Utc::now().naive_utc();
+  let accepted_at = feedback.accepted.then(|| review.accepted_at.unwrap_or(now));
+  let helpful_at = feedback.helpful.then(|| review.helpful_at.unwrap_or(now));
+
+  let (review, scores, reviewer) = db_conn
+    .run::<_, QueryResult<_>>(move |conn| {
+      conn.transaction(|conn| {
+        if accepted_at.is_some() {
+          diesel::update(Review::filter_accepted_for_recording(&review.recording_id))
+            .filter(reviews::id.ne(review.id))
+            .set(ReviewChangeset::default().accepted_at(None))
+            .execute(conn)?;
+        }
+
+        let review = diesel::update(&review)
+          .set(
+            ReviewChangeset::default()
+              .accepted_at(accepted_at)
+              .helpful_at(helpful_at),
+          )
+          .get_result::<Review>(conn)?;
+
+        let scores =
+          ReviewScore::filter_by_review_ids(vec![review.id]).load::<ReviewScore>(conn)?;
+        let reviewer = User::find_by_id(&review.reviewer_id).first::<User>(conn)?;
+
+        Ok((review, scores, reviewer))
+      })
+    })
+    .await?;
+
+  Response::success(ReviewView::new(review, scores, Some(reviewer)))
+}
+
+#[openapi(tag = "Ranklab")]
+#[delete("/reviews/<id>")]
+pub async fn delete(
+  id: Uuid,
+  auth: Auth<Jwt>,
+  db_conn: DbConn,
+) -> MutationResponse<StatusResponse> {
+  let reviewer_id = auth.into_user().id;
+
+  db_conn
+    .run(move |conn| {
+      let review = Review::find_for_reviewer(&reviewer_id, &id).first::<Review>(conn)?;
+      diesel::delete(&review).execute(conn)
+    })
+    .await?;
+
+  Response::status(Status::NoContent)
+}
@@ -1,8 +1,11 @@
+use crate::audit;
 use crate::auth::{
   decode_token_credentials, generate_token, Credentials, PasswordCredentials, TokenCredentials,
 };
 use crate::config::Config;
-use crate::guards::DbConn;
+use crate::data_types::{AuditAction, AuditTargetType};
+use crate::guards::auth::Suspension;
+use crate::guards::{ClientIp, DbConn};
 use crate::models::{Session, User};
 use crate::response::{MutationError, MutationResponse, Response};
 use bcrypt::verify;
@@ -13,17 +16,14 @@ use rocket::State;
 use rocket_okapi::openapi;
 use schemars::JsonSchema;
 use serde::Deserialize;
+use serde_json::json;
 
 #[derive(Deserialize, JsonSchema)]
 pub struct CreateSessionRequest {
   credentials: Credentials,
 }
 
-async fn create_with_password(
-  credentials: &PasswordCredentials,
-  config: &State<Config>,
-  db_conn: DbConn,
-) -> Option<String> {
+async fn create_with_password(credentials: &PasswordCredentials, db_conn: &DbConn) -> Option<User> {
   let session_password = credentials.password.clone();
   let email = credentials.email.clone();
 
@@ -45,23 +45,21 @@ async fn create_with_password(
     None => return None,
   }
 
-  Some(generate_token(&user, config))
+  Some(user)
 }
 
 async fn create_with_token(
   credentials: &TokenCredentials,
   config: &State<Config>,
-  db_conn: DbConn,
-) -> Option<String> {
+  db_conn: &DbConn,
+) -> Option<User> {
   let claims = decode_token_credentials(credentials, config)?;
   let email = claims.sub;
 
-  let user = db_conn
+  db_conn
     .run(move |conn| User::find_by_email(&email).get_result::<User>(conn))
     .await
-    .ok()?;
-
-  Some(generate_token(&user, config))
+    .ok()
 }
 
 #[openapi(tag = "Ranklab")]
@@ -70,12 +68,44 @@ pub async fn create(
   session: Json<CreateSessionRequest>,
   config: &State<Config>,
   db_conn: DbConn,
+  ip: ClientIp,
 ) -> MutationResponse<Session> {
-  let token = match &session.credentials {
-    Credentials::Password(password) => create_with_password(password, config, db_conn).await,
-    Credentials::Token(token) => create_with_token(token, config, db_conn).await,
+  let (user, payload) = match &session.credentials {
+    Credentials::Password(password) => (
+      create_with_password(password, &db_conn).await,
+      json!({ "method": "password", "email": password.email }),
+    ),
+    Credentials::Token(token) => (
+      create_with_token(token, config, &db_conn).await,
+      json!({ "method": "token" }),
+    ),
+  };
+
+  let user = match user {
+    Some(user) => user,
+    None => {
+      let event = audit::event(AuditAction::LoginFailed, None, &ip).payload(payload);
+
+      db_conn.run(move |conn| audit::record(conn, event)).await?;
+
+      return Response::mutation_error(Status::NotFound);
+    }
+  };
+
+  if user.is_suspended() {
+    return Err(MutationError::Suspended(Suspension {
+      until: user.suspended_until,
+      reason: user.suspension_reason,
+    }));
   }
-  .ok_or(MutationError::Status(Status::NotFound))?;
 
-  Response::success(Session { token })
+  let event = audit::event(AuditAction::Login, Some(user.id), &ip)
+    .target(AuditTargetType::User, user.id)
+    .payload(payload);
+
+  db_conn.run(move |conn| audit::record(conn, event)).await?;
+
+  Response::success(Session {
+    token: generate_token(&user, config),
+  })
 }
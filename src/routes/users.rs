@@ -27,7 +27,6 @@ const SALES_RECIPIENTS: [&str; 2] = ["eugeniodepalo@gmail.com", "matteodepalo@gm
 pub struct UpdateUserRequest {
   #[validate(length(min = 2))]
   name: String,
-  emails_enabled: bool,
 }
 
 #[derive(Deserialize, Validate, JsonSchema)]
@@ -167,11 +166,7 @@ pub async fn update(
   let user = db_conn
     .run(move |conn| {
       diesel::update(&existing_user)
-        .set(
-          UserChangeset::default()
-            .name(user.name.clone())
-            .emails_enabled(user.emails_enabled),
-        )
+        .set(UserChangeset::default().name(user.name.clone()))
         .get_result::<User>(conn)
     })
     .await
@@ -1,9 +1,66 @@
 // @generated automatically by Diesel CLI.
 
 pub mod sql_types {
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "audit_action"))]
+    pub struct AuditAction;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "audit_target_type"))]
+    pub struct AuditTargetType;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "clip_render_state"))]
+    pub struct ClipRenderState;
+
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "media_state"))]
     pub struct MediaState;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "notification_category"))]
+    pub struct NotificationCategory;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "notification_frequency"))]
+    pub struct NotificationFrequency;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "processing_job_kind"))]
+    pub struct ProcessingJobKind;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "report_reason"))]
+    pub struct ReportReason;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "report_status"))]
+    pub struct ReportStatus;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "taxonomy_kind"))]
+    pub struct TaxonomyKind;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "user_role"))]
+    pub struct UserRole;
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::AuditAction;
+    use super::sql_types::AuditTargetType;
+
+    audit_events (id) {
+        id -> Uuid,
+        action -> AuditAction,
+        actor_id -> Nullable<Uuid>,
+        target_type -> Nullable<AuditTargetType>,
+        target_id -> Nullable<Uuid>,
+        ip_address -> Nullable<Text>,
+        payload -> Jsonb,
+        created_at -> Timestamp,
+    }
 }
 
 diesel::table! {
@@ -18,6 +75,28 @@ diesel::table! {
         updated_at -> Timestamp,
         state -> MediaState,
         user_id -> Uuid,
+        state_reason -> Nullable<Text>,
+        state_changed_at -> Timestamp,
+        moderation_labels -> Nullable<Jsonb>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ClipRenderState;
+
+    clips (id) {
+        id -> Uuid,
+        recording_id -> Uuid,
+        user_id -> Uuid,
+        title -> Text,
+        start_timestamp -> Int8,
+        end_timestamp -> Int8,
+        render_state -> Nullable<ClipRenderState>,
+        render_job_id -> Nullable<Text>,
+        video_key -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
     }
 }
 
@@ -31,6 +110,8 @@ diesel::table! {
         user_id -> Uuid,
         recording_id -> Uuid,
         notified_at -> Nullable<Timestamp>,
+        hidden_at -> Nullable<Timestamp>,
+        deleted_at -> Nullable<Timestamp>,
     }
 }
 
@@ -50,6 +131,38 @@ diesel::table! {
         game_id -> Text,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        map -> Nullable<Text>,
+        role -> Nullable<Text>,
+        hero -> Nullable<Text>,
+        min_skill_level -> Nullable<Int2>,
+        max_skill_level -> Nullable<Int2>,
+    }
+}
+
+diesel::table! {
+    games (id) {
+        id -> Text,
+        name -> Text,
+        transcoding_profile -> Text,
+        review_categories -> Jsonb,
+        position -> Int2,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::NotificationCategory;
+    use super::sql_types::NotificationFrequency;
+
+    notification_preferences (user_id, category) {
+        user_id -> Uuid,
+        category -> NotificationCategory,
+        frequency -> NotificationFrequency,
+        last_sent_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
     }
 }
 
@@ -65,6 +178,33 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    processed_events (id) {
+        id -> Uuid,
+        event -> Text,
+        key -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ProcessingJobKind;
+
+    processing_jobs (id) {
+        id -> Uuid,
+        recording_id -> Uuid,
+        kind -> ProcessingJobKind,
+        external_id -> Text,
+        progress -> Nullable<Int4>,
+        started_at -> Timestamp,
+        finished_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::MediaState;
@@ -82,10 +222,96 @@ diesel::table! {
         processed_video_key -> Nullable<Text>,
         state -> MediaState,
         notes -> Text,
+        state_reason -> Nullable<Text>,
+        state_changed_at -> Timestamp,
+        playlist_key -> Nullable<Text>,
+        moderation_labels -> Nullable<Jsonb>,
+        hidden_at -> Nullable<Timestamp>,
+        deleted_at -> Nullable<Timestamp>,
+        upload_id -> Nullable<Text>,
+        map -> Nullable<Text>,
+        role -> Nullable<Text>,
+        hero -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ReportReason;
+    use super::sql_types::ReportStatus;
+
+    reports (id) {
+        id -> Uuid,
+        reporter_id -> Uuid,
+        recording_id -> Nullable<Uuid>,
+        comment_id -> Nullable<Uuid>,
+        reason -> ReportReason,
+        details -> Text,
+        status -> ReportStatus,
+        resolved_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    review_scores (review_id, category) {
+        review_id -> Uuid,
+        category -> Text,
+        score -> Int2,
+        comment -> Text,
     }
 }
 
 diesel::table! {
+    reviews (id) {
+        id -> Uuid,
+        recording_id -> Uuid,
+        reviewer_id -> Uuid,
+        body -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        rating -> Nullable<Int2>,
+        strengths -> Text,
+        weaknesses -> Text,
+        focus_area -> Text,
+        accepted_at -> Nullable<Timestamp>,
+        helpful_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    skill_levels (game_id, value) {
+        game_id -> Text,
+        value -> Int2,
+        name -> Text,
+        position -> Int2,
+        deprecated_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::TaxonomyKind;
+
+    taxonomy_entries (game_id, kind, key) {
+        game_id -> Text,
+        kind -> TaxonomyKind,
+        key -> Text,
+        name -> Text,
+        position -> Int2,
+        deprecated_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::UserRole;
+
     users (id) {
         created_at -> Timestamp,
         email -> Text,
@@ -93,23 +319,50 @@ diesel::table! {
         name -> Text,
         password -> Nullable<Text>,
         updated_at -> Timestamp,
-        emails_enabled -> Bool,
+        role -> UserRole,
+        suspended_at -> Nullable<Timestamp>,
+        suspended_until -> Nullable<Timestamp>,
+        suspension_reason -> Nullable<Text>,
+        shadow_banned_at -> Nullable<Timestamp>,
     }
 }
 
 diesel::joinable!(avatars -> users (user_id));
+diesel::joinable!(clips -> recordings (recording_id));
+diesel::joinable!(clips -> users (user_id));
 diesel::joinable!(comments -> recordings (recording_id));
 diesel::joinable!(comments -> users (user_id));
 diesel::joinable!(followings -> users (user_id));
+diesel::joinable!(notification_preferences -> users (user_id));
 diesel::joinable!(one_time_tokens -> users (user_id));
+diesel::joinable!(processing_jobs -> recordings (recording_id));
 diesel::joinable!(recordings -> users (user_id));
+diesel::joinable!(reports -> comments (comment_id));
+diesel::joinable!(reports -> recordings (recording_id));
+diesel::joinable!(reports -> users (reporter_id));
+diesel::joinable!(review_scores -> reviews (review_id));
+diesel::joinable!(reviews -> recordings (recording_id));
+diesel::joinable!(reviews -> users (reviewer_id));
+diesel::joinable!(skill_levels -> games (game_id));
+diesel::joinable!(taxonomy_entries -> games (game_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    audit_events,
     avatars,
+    clips,
     comments,
     digests,
     followings,
+    games,
+    notification_preferences,
     one_time_tokens,
+    processed_events,
+    processing_jobs,
     recordings,
+    reports,
+    review_scores,
+    reviews,
+    skill_levels,
+    taxonomy_entries,
     users,
 );
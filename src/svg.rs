@@ -0,0 +1,220 @@
+use ammonia::Builder;
+use lazy_static::lazy_static;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+const TAGS: [&str; 13] = [
+  "svg", "g", "defs", "marker", "path", "line", "polyline", "polygon", "circle", "ellipse", "rect",
+  "text", "tspan",
+];
+
+/// Presentation attributes allowed on every tag.
+const GENERIC_ATTRIBUTES: [&str; 11] = [
+  "stroke",
+  "stroke-width",
+  "stroke-linecap",
+  "stroke-linejoin",
+  "stroke-dasharray",
+  "stroke-opacity",
+  "fill",
+  "fill-opacity",
+  "opacity",
+  "transform",
+  "visibility",
+];
+
+const MARKER_ATTRIBUTES: [&str; 3] = ["marker-start", "marker-mid", "marker-end"];
+
+const TAG_ATTRIBUTES: [(&str, &[&str]); 12] = [
+  ("svg", &["viewBox", "width", "height"]),
+  (
+    "marker",
+    &[
+      "id",
+      "viewBox",
+      "markerWidth",
+      "markerHeight",
+      "markerUnits",
+      "refX",
+      "refY",
+      "orient",
+    ],
+  ),
+  ("path", &["d"]),
+  ("line", &["x1", "y1", "x2", "y2"]),
+  ("polyline", &["points"]),
+  ("polygon", &["points"]),
+  ("circle", &["cx", "cy", "r"]),
+  ("ellipse", &["cx", "cy", "rx", "ry"]),
+  ("rect", &["x", "y", "width", "height", "rx", "ry"]),
+  (
+    "text",
+    &[
+      "x",
+      "y",
+      "dx",
+      "dy",
+      "font-size",
+      "font-weight",
+      "text-anchor",
+      "dominant-baseline",
+    ],
+  ),
+  ("tspan", &["x", "y", "dx", "dy"]),
+  ("g", &[]),
+];
+
+lazy_static! {
+  static ref SANITIZER: Builder<'static> = {
+    let mut tag_attributes = HashMap::new();
+
+    for (tag, attributes) in TAG_ATTRIBUTES.iter() {
+      let mut attributes = attributes.iter().copied().collect::<HashSet<_>>();
+
+      if ["path", "line", "polyline", "polygon"].contains(tag) {
+        attributes.extend(MARKER_ATTRIBUTES.iter().copied());
+      }
+
+      tag_attributes.insert(*tag, attributes);
+    }
+
+    let mut builder = Builder::default();
+
+    builder
+      .tags(TAGS.iter().copied().collect())
+      .generic_attributes(GENERIC_ATTRIBUTES.iter().copied().collect())
+      .tag_attributes(tag_attributes)
+      .url_relative(ammonia::UrlRelative::Deny)
+      .link_rel(None)
+      .attribute_filter(filter_attribute);
+
+    builder
+  };
+}
+
+fn is_local_name(value: &str) -> bool {
+  !value.is_empty()
+    && value
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Paint and marker attributes may only reference elements of the drawing
+/// itself, so `url()` values pointing anywhere else are dropped.
+fn filter_attribute<'u>(_tag: &str, attribute: &str, value: &'u str) -> Option<Cow<'u, str>> {
+  if attribute == "id" {
+    return Some(value.into()).filter(|_| is_local_name(value));
+  }
+
+  let lowercase = value.to_ascii_lowercase();
+
+  if !lowercase.contains("url(") {
+    return Some(value.into());
+  }
+
+  value
+    .trim()
+    .strip_prefix("url(#")
+    .and_then(|reference| reference.strip_suffix(')'))
+    .filter(|reference| is_local_name(reference))
+    .map(|_| value.into())
+}
+
+/// Strips everything from a user supplied drawing except a small vocabulary of
+/// shapes, text and arrow markers.
+pub fn sanitize(drawing: &str) -> String {
+  SANITIZER.clean(drawing).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn keeps_clean_drawings_unchanged() {
+    let drawing = concat!(
+      r#"<svg viewBox="0 0 100 100">"#,
+      r#"<line x1="0" y1="0" x2="50" y2="50" stroke="red" stroke-width="2"></line>"#,
+      r#"<circle cx="50" cy="50" r="10" fill="none"></circle>"#,
+      r#"</svg>"#,
+    );
+
+    assert_eq!(sanitize(drawing), drawing);
+  }
+
+  #[test]
+  fn removes_scripts() {
+    let sanitized =
+      sanitize(r#"<svg><script>alert(1)</script><circle cx="1" cy="1" r="1"></circle></svg>"#);
+
+    assert!(!sanitized.contains("script"));
+    assert!(!sanitized.contains("alert"));
+    assert!(sanitized.contains("<circle"));
+  }
+
+  #[test]
+  fn removes_event_handlers() {
+    let sanitized = sanitize(
+      r#"<svg onload="alert(1)"><circle cx="1" cy="1" r="1" onclick="alert(2)"></circle></svg>"#,
+    );
+
+    assert!(!sanitized.contains("onload"));
+    assert!(!sanitized.contains("onclick"));
+    assert!(!sanitized.contains("alert"));
+    assert!(sanitized.contains("<circle"));
+  }
+
+  #[test]
+  fn removes_javascript_and_data_links() {
+    let sanitized = sanitize(concat!(
+      r#"<svg><a href="javascript:alert(1)"><path d="M0 0L1 1"></path></a>"#,
+      r#"<path href="javascript:alert(2)" d="M1 1L2 2"></path>"#,
+      r#"<image href="data:image/svg+xml;base64,PHN2Zz48L3N2Zz4="></image></svg>"#,
+    ));
+
+    assert!(!sanitized.contains("javascript:"));
+    assert!(!sanitized.contains("data:"));
+    assert!(!sanitized.contains("href"));
+    assert!(sanitized.contains(r#"d="M0 0L1 1""#));
+    assert!(sanitized.contains(r#"d="M1 1L2 2""#));
+  }
+
+  #[test]
+  fn removes_external_urls_from_attributes() {
+    let sanitized = sanitize(concat!(
+      r#"<svg><circle cx="1" cy="1" r="1" style="fill: url(https://example.com/a.svg#x)"></circle>"#,
+      r#"<circle cx="2" cy="2" r="1" fill="url(https://example.com/b.svg#x)"></circle>"#,
+      r#"<line x1="0" y1="0" x2="1" y2="1" marker-end="url(#arrow)"></line></svg>"#,
+    ));
+
+    assert!(!sanitized.contains("example.com"));
+    assert!(!sanitized.contains("style"));
+    assert!(sanitized.contains(r#"marker-end="url(#arrow)""#));
+  }
+
+  #[test]
+  fn removes_style_elements() {
+    let sanitized = sanitize(concat!(
+      r#"<svg><style>circle { fill: url(https://example.com/a.svg#x) }</style>"#,
+      r#"<circle cx="1" cy="1" r="1"></circle></svg>"#,
+    ));
+
+    assert!(!sanitized.contains("style"));
+    assert!(!sanitized.contains("example.com"));
+    assert!(sanitized.contains("<circle"));
+  }
+
+  #[test]
+  fn removes_foreign_objects() {
+    let sanitized = sanitize(concat!(
+      r#"<svg><foreignObject width="10" height="10">"#,
+      r#"<div><img src="x" onerror="alert(1)"></div>"#,
+      r#"</foreignObject></svg>"#,
+    ));
+
+    assert!(!sanitized.to_ascii_lowercase().contains("foreignobject"));
+    assert!(!sanitized.contains("<div"));
+    assert!(!sanitized.contains("<img"));
+    assert!(!sanitized.contains("onerror"));
+  }
+}
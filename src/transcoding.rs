@@ -0,0 +1,73 @@
+pub mod ffmpeg;
+pub mod media_convert;
+mod profile;
+use crate::config::Config;
+use crate::games;
+use crate::guards::DbConn;
+use anyhow::Result;
+use diesel::{OptionalExtension, QueryResult};
+pub use profile::{
+  default_profiles, deserialize_profiles, AudioProfile, Framerate, GopSizeUnits, HlsRendition,
+  ThumbnailProfile, TranscodingProfile, DEFAULT_PROFILE, HIGH_FRAMERATE_PROFILE,
+};
+use serde::{Deserialize, Serialize};
+
+pub const VIDEO_NAME_MODIFIER: &str = "_720p";
+pub const THUMBNAIL_NAME_MODIFIER: &str = "_thumbnail";
+pub const HLS_NAME_MODIFIER: &str = "_hls";
+pub const CLIP_NAME_MODIFIER: &str = "_clip";
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscoderKind {
+  #[default]
+  MediaConvert,
+  Ffmpeg,
+}
+
+/// Produces the processed outputs for an uploaded recording. Outputs are
+/// written next to the original under `recordings/processed/`, where the
+/// uploads queue picks them up, so callers only need the returned job id.
+#[async_trait]
+pub trait Transcoder: Send + Sync {
+  async fn transcode(&self, profile: &TranscodingProfile, input_key: &str) -> Result<String>;
+
+  /// Renders the section between `start` and `end` seconds as a single MP4
+  /// named with `name_modifier`, next to the other processed outputs.
+  async fn clip(
+    &self,
+    profile: &TranscodingProfile,
+    input_key: &str,
+    name_modifier: &str,
+    start: i64,
+    end: i64,
+  ) -> Result<String>;
+}
+
+pub fn transcoder(config: &Config) -> Box<dyn Transcoder> {
+  match config.transcoder {
+    TranscoderKind::MediaConvert => {
+      Box::new(media_convert::MediaConvertTranscoder::new(config.clone()))
+    }
+    TranscoderKind::Ffmpeg => Box::new(ffmpeg::FfmpegTranscoder::new(config.clone())),
+  }
+}
+
+pub async fn profile_for_game<'a>(
+  db_conn: &DbConn,
+  config: &'a Config,
+  game_id: &str,
+) -> QueryResult<Option<&'a TranscodingProfile>> {
+  let profile = games::find(db_conn, game_id)
+    .await
+    .optional()?
+    .map(|game| game.transcoding_profile)
+    .unwrap_or_else(|| DEFAULT_PROFILE.to_owned());
+
+  Ok(
+    config
+      .transcoding_profiles
+      .get(&profile)
+      .or_else(|| config.transcoding_profiles.get(DEFAULT_PROFILE)),
+  )
+}
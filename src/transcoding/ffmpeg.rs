@@ -0,0 +1,366 @@
+use super::{
+  GopSizeUnits, Transcoder, TranscoderKind, TranscodingProfile, THUMBNAIL_NAME_MODIFIER,
+  VIDEO_NAME_MODIFIER,
+};
+use crate::aws::s3::presign_download;
+use crate::aws::ConfigCredentialsProvider;
+use crate::config::Config;
+use crate::guards::DbConn;
+use crate::models::Recording;
+use crate::queue_handlers::fail_transcoding;
+use anyhow::{anyhow, Result};
+use diesel::prelude::*;
+use hyper_tls::HttpsConnector;
+use once_cell::sync::OnceCell;
+use rocket::tokio::process::Command;
+use rocket::{tokio, Orbit, Rocket};
+use rusoto_core::{ByteStream, HttpClient};
+use rusoto_s3::{GetObjectRequest, PutObjectRequest, S3Client, S3};
+use rusoto_signature::Region;
+use std::path::{Path, PathBuf};
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
+
+/// Transcodes outlive the request or queue message that started them, so
+/// their failures are recorded through a connection of their own.
+static DB_CONN: OnceCell<DbConn> = OnceCell::new();
+
+pub async fn init(rocket: &Rocket<Orbit>) {
+  let config = rocket.state::<Config>().unwrap();
+
+  if config.transcoder != TranscoderKind::Ffmpeg {
+    return;
+  }
+
+  if let Some(db_conn) = DbConn::get_one(rocket).await {
+    DB_CONN.set(db_conn).ok();
+  }
+}
+
+#[derive(Clone)]
+pub struct FfmpegTranscoder {
+  config: Config,
+  client: S3Client,
+}
+
+impl FfmpegTranscoder {
+  pub fn new(config: Config) -> Self {
+    let client = S3Client::new_with(
+      HttpClient::from_connector(HttpsConnector::new()),
+      ConfigCredentialsProvider::new(config.clone()),
+      Region::EuWest2,
+    );
+
+    Self { config, client }
+  }
+
+  fn ffmpeg_path(&self) -> String {
+    self
+      .config
+      .ffmpeg_path
+      .clone()
+      .unwrap_or_else(|| "ffmpeg".to_owned())
+  }
+
+  async fn download(&self, key: &str, path: &Path) -> Result<()> {
+    let object = self
+      .client
+      .get_object(GetObjectRequest {
+        bucket: self.config.uploads_bucket.clone(),
+        key: key.to_owned(),
+        ..Default::default()
+      })
+      .await?;
+
+    let body = object
+      .body
+      .ok_or_else(|| anyhow!("No body found for object {}", key))?;
+
+    let mut file = tokio::fs::File::create(path).await?;
+
+    tokio::io::copy(&mut body.into_async_read(), &mut file).await?;
+
+    Ok(())
+  }
+
+  async fn upload(&self, path: &Path, key: String, content_type: &str) -> Result<()> {
+    let file = tokio::fs::File::open(path).await?;
+    let content_length = file.metadata().await?.len();
+
+    self
+      .client
+      .put_object(PutObjectRequest {
+        bucket: self.config.uploads_bucket.clone(),
+        key,
+        content_type: Some(content_type.to_owned()),
+        content_length: Some(content_length as i64),
+        body: Some(ByteStream::new(ReaderStream::new(file))),
+        ..Default::default()
+      })
+      .await?;
+
+    Ok(())
+  }
+
+  async fn ffmpeg(&self, args: Vec<String>) -> Result<()> {
+    let ffmpeg_path = self.ffmpeg_path();
+
+    let output = Command::new(ffmpeg_path).args(args).output().await?;
+
+    if !output.status.success() {
+      return Err(anyhow!(
+        "ffmpeg exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+      ));
+    }
+
+    Ok(())
+  }
+}
+
+pub fn video_args(profile: &TranscodingProfile, input: &Path, output: &Path) -> Vec<String> {
+  let mut args = vec![
+    "-y".to_owned(),
+    "-i".to_owned(),
+    input.display().to_string(),
+    "-vf".to_owned(),
+    format!(
+      "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2",
+      w = profile.width,
+      h = profile.height
+    ),
+    "-c:v".to_owned(),
+    "libx264".to_owned(),
+    "-profile:v".to_owned(),
+    "high".to_owned(),
+    "-b:v".to_owned(),
+    profile.bitrate.to_string(),
+    "-maxrate".to_owned(),
+    profile.bitrate.to_string(),
+    "-bufsize".to_owned(),
+    (profile.bitrate * 2).to_string(),
+  ];
+
+  match profile.gop_size_units {
+    GopSizeUnits::Frames => {
+      args.push("-g".to_owned());
+      args.push((profile.gop_size as i64).to_string());
+    }
+    GopSizeUnits::Seconds => {
+      args.push("-force_key_frames".to_owned());
+      args.push(format!("expr:gte(t,n_forced*{})", profile.gop_size));
+    }
+  }
+
+  if let Some(framerate) = &profile.framerate {
+    args.push("-r".to_owned());
+    args.push(format!("{}/{}", framerate.numerator, framerate.denominator));
+  }
+
+  args.extend(vec![
+    "-c:a".to_owned(),
+    "aac".to_owned(),
+    "-b:a".to_owned(),
+    profile.audio.bitrate.to_string(),
+    "-ar".to_owned(),
+    profile.audio.sample_rate.to_string(),
+    "-ac".to_owned(),
+    "2".to_owned(),
+    "-movflags".to_owned(),
+    "+faststart".to_owned(),
+    output.display().to_string(),
+  ]);
+
+  args
+}
+
+pub fn thumbnail_args(profile: &TranscodingProfile, input: &Path, output: &Path) -> Vec<String> {
+  // ffmpeg's JPEG quality scale runs from 2 (best) to 31 (worst).
+  let quality = 2 + (100 - profile.thumbnails.quality.clamp(0, 100)) * 29 / 100;
+
+  vec![
+    "-y".to_owned(),
+    "-i".to_owned(),
+    input.display().to_string(),
+    "-vf".to_owned(),
+    format!(
+      "fps=1/{},scale=-2:{}",
+      profile.thumbnails.interval_seconds, profile.height
+    ),
+    "-frames:v".to_owned(),
+    profile.thumbnails.max_captures.to_string(),
+    "-q:v".to_owned(),
+    quality.to_string(),
+    "-start_number".to_owned(),
+    "0".to_owned(),
+    output.display().to_string(),
+  ]
+}
+
+impl FfmpegTranscoder {
+  async fn run_transcode(
+    &self,
+    job_id: &str,
+    profile: &TranscodingProfile,
+    input_key: &str,
+  ) -> Result<()> {
+    let work_dir: PathBuf = std::env::temp_dir().join(job_id);
+    let input = work_dir.join("input");
+    let output_key = input_key.replace("/originals/", "/processed/");
+    let video = work_dir.join("video.mp4");
+
+    tokio::fs::create_dir_all(&work_dir).await?;
+
+    let result = async {
+      self.download(input_key, &input).await?;
+      self.ffmpeg(video_args(profile, &input, &video)).await?;
+      self
+        .ffmpeg(thumbnail_args(
+          profile,
+          &input,
+          &work_dir.join("thumbnail.%07d.jpg"),
+        ))
+        .await?;
+
+      for index in 0..profile.thumbnails.max_captures {
+        let file_name = format!("thumbnail.{:07}.jpg", index);
+        let thumbnail = work_dir.join(&file_name);
+
+        if !tokio::fs::try_exists(&thumbnail).await? {
+          break;
+        }
+
+        self
+          .upload(
+            &thumbnail,
+            format!("{}{}.{:07}.jpg", output_key, THUMBNAIL_NAME_MODIFIER, index),
+            "image/jpeg",
+          )
+          .await?;
+      }
+
+      self
+        .upload(
+          &video,
+          format!("{}{}.mp4", output_key, VIDEO_NAME_MODIFIER),
+          "video/mp4",
+        )
+        .await
+    }
+    .await;
+
+    remove_work_dir(&work_dir).await;
+
+    result
+  }
+
+  async fn fail_transcode(&self, job_id: String, input_key: String) -> Result<()> {
+    let db_conn = DB_CONN
+      .get()
+      .ok_or_else(|| anyhow!("No database connection for ffmpeg jobs"))?;
+
+    let recording = db_conn
+      .run(move |conn| {
+        Recording::find_by_video_key(&input_key)
+          .first::<Recording>(conn)
+          .optional()
+      })
+      .await?;
+
+    match recording {
+      Some(recording) => fail_transcoding(db_conn, &self.config, recording, job_id).await,
+      None => Ok(()),
+    }
+  }
+}
+
+/// Cleanup failures are only logged so they don't hide the job's result.
+async fn remove_work_dir(work_dir: &Path) {
+  if let Err(e) = tokio::fs::remove_dir_all(work_dir).await {
+    error!("[ffmpeg] Failed to remove {}: {:?}", work_dir.display(), e);
+  }
+}
+
+#[async_trait]
+impl Transcoder for FfmpegTranscoder {
+  /// Transcoding takes about as long as the recording, so it runs in the
+  /// background. Like MediaConvert's, its outputs are picked up by the
+  /// uploads queue, and a failed run fails the recording.
+  async fn transcode(&self, profile: &TranscodingProfile, input_key: &str) -> Result<String> {
+    let job_id = format!("ffmpeg-{}", Uuid::new_v4());
+    let transcoder = self.clone();
+    let profile = profile.clone();
+    let input_key = input_key.to_owned();
+    let task_job_id = job_id.clone();
+
+    tokio::spawn(async move {
+      if let Err(e) = transcoder
+        .run_transcode(&task_job_id, &profile, &input_key)
+        .await
+      {
+        error!("[ffmpeg] Job {} failed: {:?}", task_job_id, e);
+        sentry::capture_error(e.root_cause());
+
+        if let Err(e) = transcoder
+          .fail_transcode(task_job_id.clone(), input_key)
+          .await
+        {
+          error!(
+            "[ffmpeg] Failed to mark job {} as failed: {:?}",
+            task_job_id, e
+          );
+          sentry::capture_error(e.root_cause());
+        }
+      }
+    });
+
+    Ok(job_id)
+  }
+
+  async fn clip(
+    &self,
+    profile: &TranscodingProfile,
+    input_key: &str,
+    name_modifier: &str,
+    start: i64,
+    end: i64,
+  ) -> Result<String> {
+    let job_id = format!("ffmpeg-{}", Uuid::new_v4());
+    let work_dir: PathBuf = std::env::temp_dir().join(&job_id);
+    let output_key = input_key.replace("/originals/", "/processed/");
+    let video = work_dir.join("clip.mp4");
+
+    // Seeking in a signed URL lets ffmpeg fetch only the ranges it needs
+    // instead of downloading the whole recording.
+    let input = presign_download(&self.config, input_key);
+
+    let mut args = vec![
+      "-ss".to_owned(),
+      start.to_string(),
+      "-t".to_owned(),
+      (end - start).to_string(),
+    ];
+
+    args.extend(video_args(profile, Path::new(&input), &video));
+
+    tokio::fs::create_dir_all(&work_dir).await?;
+
+    let result = async {
+      self.ffmpeg(args).await?;
+      self
+        .upload(
+          &video,
+          format!("{}{}.mp4", output_key, name_modifier),
+          "video/mp4",
+        )
+        .await
+    }
+    .await;
+
+    remove_work_dir(&work_dir).await;
+    result?;
+
+    Ok(job_id)
+  }
+}
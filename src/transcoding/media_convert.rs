@@ -0,0 +1,592 @@
+use super::{Transcoder, TranscodingProfile};
+use crate::aws::{self, ConfigCredentialsProvider};
+use crate::config::Config;
+use anyhow::{anyhow, Result};
+use hyper_tls::HttpsConnector;
+use rusoto_core::HttpClient;
+use rusoto_mediaconvert::{
+  AacSettings, AudioCodecSettings, AudioDescription, AudioSelector, ContainerSettings,
+  CreateJobRequest, DestinationSettings, FileGroupSettings, FrameCaptureSettings, H264QvbrSettings,
+  H264Settings, HlsGroupSettings, Input, InputClipping, JobSettings, M3u8Settings, MediaConvert,
+  MediaConvertClient, Mp4Settings, Output, OutputGroup, OutputGroupSettings, S3DestinationSettings,
+  VideoCodecSettings, VideoDescription, VideoSelector,
+};
+use rusoto_signature::Region;
+use std::collections::HashMap;
+
+pub struct MediaConvertTranscoder {
+  config: Config,
+}
+
+impl MediaConvertTranscoder {
+  pub fn new(config: Config) -> Self {
+    Self { config }
+  }
+}
+
+#[async_trait]
+impl Transcoder for MediaConvertTranscoder {
+  async fn transcode(&self, profile: &TranscodingProfile, input_key: &str) -> Result<String> {
+    self
+      .create_job(create_job_request(&self.config, profile, input_key))
+      .await
+  }
+
+  async fn clip(
+    &self,
+    profile: &TranscodingProfile,
+    input_key: &str,
+    name_modifier: &str,
+    start: i64,
+    end: i64,
+  ) -> Result<String> {
+    self
+      .create_job(clip_job_request(
+        &self.config,
+        profile,
+        input_key,
+        name_modifier,
+        start,
+        end,
+      ))
+      .await
+  }
+}
+
+impl MediaConvertTranscoder {
+  async fn create_job(&self, request: CreateJobRequest) -> Result<String> {
+    let endpoints_response = aws::media_convert::describe_endpoints(self.config.clone()).await?;
+    let endpoints = endpoints_response.endpoints;
+
+    let endpoint = endpoints
+      .first()
+      .ok_or_else(|| anyhow!("No endpoint found"))?;
+
+    let client = MediaConvertClient::new_with(
+      HttpClient::from_connector(HttpsConnector::new()),
+      ConfigCredentialsProvider::new(self.config.clone()),
+      Region::Custom {
+        name: Region::EuWest2.name().to_owned(),
+        endpoint: endpoint.url.clone(),
+      },
+    );
+
+    let job = client.create_job(request).await?;
+
+    job
+      .job
+      .and_then(|job| job.id)
+      .ok_or_else(|| anyhow!("No job id returned for transcoding"))
+  }
+}
+
+fn destination_settings() -> Option<DestinationSettings> {
+  Some(DestinationSettings {
+    s3_settings: Some(S3DestinationSettings {
+      ..Default::default()
+    }),
+  })
+}
+
+fn video_output(profile: &TranscodingProfile) -> Output {
+  let (
+    framerate_control,
+    framerate_conversion_algorithm,
+    framerate_numerator,
+    framerate_denominator,
+  ) = match &profile.framerate {
+    Some(framerate) => (
+      "SPECIFIED",
+      Some("DUPLICATE_DROP".to_owned()),
+      Some(framerate.numerator),
+      Some(framerate.denominator),
+    ),
+    None => ("INITIALIZE_FROM_SOURCE", None, None, None),
+  };
+
+  Output {
+    video_description: Some(VideoDescription {
+      scaling_behavior: Some("DEFAULT".to_owned()),
+      timecode_insertion: Some("DISABLED".to_owned()),
+      anti_alias: Some("ENABLED".to_owned()),
+      sharpness: Some(50),
+      codec_settings: Some(VideoCodecSettings {
+        codec: Some("H_264".to_owned()),
+        h264_settings: Some(H264Settings {
+          interlace_mode: Some("PROGRESSIVE".to_owned()),
+          number_reference_frames: Some(3),
+          syntax: Some("DEFAULT".to_owned()),
+          softness: Some(0),
+          gop_closed_cadence: Some(1),
+          gop_size: Some(profile.gop_size),
+          slices: Some(1),
+          gop_b_reference: Some("DISABLED".to_owned()),
+          slow_pal: Some("DISABLED".to_owned()),
+          spatial_adaptive_quantization: Some("ENABLED".to_owned()),
+          temporal_adaptive_quantization: Some("ENABLED".to_owned()),
+          flicker_adaptive_quantization: Some("DISABLED".to_owned()),
+          entropy_encoding: Some("CABAC".to_owned()),
+          bitrate: Some(profile.bitrate),
+          framerate_control: Some(framerate_control.to_owned()),
+          rate_control_mode: Some("CBR".to_owned()),
+          codec_profile: Some("HIGH".to_owned()),
+          telecine: Some("NONE".to_owned()),
+          min_i_interval: Some(0),
+          codec_level: Some(profile.codec_level.clone()),
+          adaptive_quantization: Some("HIGH".to_owned()),
+          field_encoding: Some("PAFF".to_owned()),
+          scene_change_detect: Some("ENABLED".to_owned()),
+          quality_tuning_level: Some("SINGLE_PASS_HQ".to_owned()),
+          framerate_conversion_algorithm,
+          unregistered_sei_timecode: Some("DISABLED".to_owned()),
+          gop_size_units: Some(profile.gop_size_units.as_str().to_owned()),
+          par_control: Some("INITIALIZE_FROM_SOURCE".to_owned()),
+          number_b_frames_between_reference_frames: Some(3),
+          repeat_pps: Some("DISABLED".to_owned()),
+          hrd_buffer_size: Some(profile.bitrate * 2),
+          hrd_buffer_initial_fill_percentage: Some(90),
+          framerate_numerator,
+          framerate_denominator,
+          ..Default::default()
+        }),
+        ..Default::default()
+      }),
+      afd_signaling: Some("NONE".to_owned()),
+      drop_frame_timecode: Some("ENABLED".to_owned()),
+      respond_to_afd: Some("NONE".to_owned()),
+      color_metadata: Some("INSERT".to_owned()),
+      width: Some(profile.width),
+      height: Some(profile.height),
+      ..Default::default()
+    }),
+    audio_descriptions: Some(vec![audio_description(profile)]),
+    container_settings: Some(ContainerSettings {
+      container: Some("MP4".to_owned()),
+      mp_4_settings: Some(Mp4Settings {
+        cslg_atom: Some("INCLUDE".to_owned()),
+        free_space_box: Some("EXCLUDE".to_owned()),
+        moov_placement: Some("PROGRESSIVE_DOWNLOAD".to_owned()),
+        ..Default::default()
+      }),
+      ..Default::default()
+    }),
+    name_modifier: Some(super::VIDEO_NAME_MODIFIER.to_owned()),
+    ..Default::default()
+  }
+}
+
+fn thumbnail_output(profile: &TranscodingProfile) -> Output {
+  Output {
+    container_settings: Some(ContainerSettings {
+      container: Some("RAW".to_owned()),
+      ..Default::default()
+    }),
+    extension: Some("jpg".to_owned()),
+    name_modifier: Some(super::THUMBNAIL_NAME_MODIFIER.to_owned()),
+    video_description: Some(VideoDescription {
+      height: Some(profile.height),
+      scaling_behavior: Some("DEFAULT".to_owned()),
+      timecode_insertion: Some("DISABLED".to_owned()),
+      anti_alias: Some("ENABLED".to_owned()),
+      sharpness: Some(50),
+      codec_settings: Some(VideoCodecSettings {
+        codec: Some("FRAME_CAPTURE".to_owned()),
+        frame_capture_settings: Some(FrameCaptureSettings {
+          framerate_numerator: Some(1),
+          framerate_denominator: Some(profile.thumbnails.interval_seconds),
+          max_captures: Some(profile.thumbnails.max_captures),
+          quality: Some(profile.thumbnails.quality),
+        }),
+        ..Default::default()
+      }),
+      afd_signaling: Some("NONE".to_owned()),
+      drop_frame_timecode: Some("ENABLED".to_owned()),
+      respond_to_afd: Some("NONE".to_owned()),
+      color_metadata: Some("INSERT".to_owned()),
+      ..Default::default()
+    }),
+    ..Default::default()
+  }
+}
+
+fn audio_description(profile: &TranscodingProfile) -> AudioDescription {
+  AudioDescription {
+    audio_type_control: Some("FOLLOW_INPUT".to_owned()),
+    codec_settings: Some(AudioCodecSettings {
+      codec: Some("AAC".to_owned()),
+      aac_settings: Some(AacSettings {
+        audio_description_broadcaster_mix: Some("NORMAL".to_owned()),
+        bitrate: Some(profile.audio.bitrate),
+        rate_control_mode: Some("CBR".to_owned()),
+        codec_profile: Some("LC".to_owned()),
+        coding_mode: Some("CODING_MODE_2_0".to_owned()),
+        raw_format: Some("NONE".to_owned()),
+        sample_rate: Some(profile.audio.sample_rate),
+        specification: Some("MPEG4".to_owned()),
+        ..Default::default()
+      }),
+      ..Default::default()
+    }),
+    language_code_control: Some("FOLLOW_INPUT".to_owned()),
+    ..Default::default()
+  }
+}
+
+fn hls_output_group(profile: &TranscodingProfile, destination: &str) -> Option<OutputGroup> {
+  if profile.hls_renditions.is_empty() {
+    return None;
+  }
+
+  let outputs = profile
+    .hls_renditions
+    .iter()
+    .map(|rendition| Output {
+      name_modifier: Some(format!("{}{}", super::HLS_NAME_MODIFIER, rendition.name)),
+      container_settings: Some(ContainerSettings {
+        container: Some("M3U8".to_owned()),
+        m_3u_8_settings: Some(M3u8Settings::default()),
+        ..Default::default()
+      }),
+      video_description: Some(VideoDescription {
+        scaling_behavior: Some("DEFAULT".to_owned()),
+        timecode_insertion: Some("DISABLED".to_owned()),
+        anti_alias: Some("ENABLED".to_owned()),
+        sharpness: Some(50),
+        height: rendition.height,
+        codec_settings: Some(VideoCodecSettings {
+          codec: Some("H_264".to_owned()),
+          h264_settings: Some(H264Settings {
+            rate_control_mode: Some("QVBR".to_owned()),
+            max_bitrate: Some(rendition.max_bitrate),
+            qvbr_settings: Some(H264QvbrSettings {
+              qvbr_quality_level: Some(8),
+              ..Default::default()
+            }),
+            framerate_control: Some("INITIALIZE_FROM_SOURCE".to_owned()),
+            gop_size: Some(2.0),
+            gop_size_units: Some("SECONDS".to_owned()),
+            codec_profile: Some("HIGH".to_owned()),
+            codec_level: Some("AUTO".to_owned()),
+            scene_change_detect: Some("TRANSITION_DETECTION".to_owned()),
+            quality_tuning_level: Some("SINGLE_PASS_HQ".to_owned()),
+            ..Default::default()
+          }),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }),
+      audio_descriptions: Some(vec![audio_description(profile)]),
+      ..Default::default()
+    })
+    .collect();
+
+  Some(OutputGroup {
+    name: Some("Apple HLS".to_owned()),
+    output_group_settings: Some(OutputGroupSettings {
+      type_: Some("HLS_GROUP_SETTINGS".to_owned()),
+      hls_group_settings: Some(HlsGroupSettings {
+        destination: Some(destination.to_owned()),
+        destination_settings: destination_settings(),
+        segment_length: Some(6),
+        min_segment_length: Some(0),
+        directory_structure: Some("SINGLE_DIRECTORY".to_owned()),
+        manifest_duration_format: Some("INTEGER".to_owned()),
+        output_selection: Some("MANIFESTS_AND_SEGMENTS".to_owned()),
+        segment_control: Some("SEGMENTED_FILES".to_owned()),
+        ..Default::default()
+      }),
+      ..Default::default()
+    }),
+    outputs: Some(outputs),
+    ..Default::default()
+  })
+}
+
+fn file_group(destination: &str, outputs: Vec<Output>) -> OutputGroup {
+  OutputGroup {
+    name: Some("File Group".to_owned()),
+    output_group_settings: Some(OutputGroupSettings {
+      type_: Some("FILE_GROUP_SETTINGS".to_owned()),
+      file_group_settings: Some(FileGroupSettings {
+        destination: Some(destination.to_owned()),
+        destination_settings: destination_settings(),
+      }),
+      ..Default::default()
+    }),
+    outputs: Some(outputs),
+    ..Default::default()
+  }
+}
+
+/// Formats whole seconds as a zero-based `HH:MM:SS:FF` timecode.
+fn timecode(seconds: i64) -> String {
+  format!(
+    "{:02}:{:02}:{:02}:00",
+    seconds / 3600,
+    seconds % 3600 / 60,
+    seconds % 60
+  )
+}
+
+pub fn clip_job_request(
+  config: &Config,
+  profile: &TranscodingProfile,
+  input_key: &str,
+  name_modifier: &str,
+  start: i64,
+  end: i64,
+) -> CreateJobRequest {
+  let mut request = create_job_request(config, profile, input_key);
+  let destination = format!("s3://{}/recordings/processed/", config.uploads_bucket);
+  let mut output = video_output(profile);
+
+  output.name_modifier = Some(name_modifier.to_owned());
+  request.settings.output_groups = Some(vec![file_group(&destination, vec![output])]);
+
+  if let Some(input) = request
+    .settings
+    .inputs
+    .as_mut()
+    .and_then(|inputs| inputs.first_mut())
+  {
+    // Clipping timecodes count from the start of the file rather than from
+    // whatever timecode the source has embedded.
+    input.timecode_source = Some("ZEROBASED".to_owned());
+    input.input_clippings = Some(vec![InputClipping {
+      start_timecode: Some(timecode(start)),
+      end_timecode: Some(timecode(end)),
+    }]);
+  }
+
+  request
+}
+
+pub fn create_job_request(
+  config: &Config,
+  profile: &TranscodingProfile,
+  input_key: &str,
+) -> CreateJobRequest {
+  let destination = format!("s3://{}/recordings/processed/", config.uploads_bucket);
+  let mut user_metadata = HashMap::new();
+  let mut audio_selectors = HashMap::new();
+
+  if let Some(instance_id) = config.instance_id.as_ref() {
+    user_metadata.insert("instance-id".to_owned(), instance_id.to_owned());
+  }
+
+  audio_selectors.insert(
+    "Audio Selector 1".to_owned(),
+    AudioSelector {
+      offset: Some(0),
+      default_selection: Some("DEFAULT".to_owned()),
+      selector_type: Some("TRACK".to_owned()),
+      program_selection: Some(1),
+      ..Default::default()
+    },
+  );
+
+  let file_group = file_group(
+    &destination,
+    vec![video_output(profile), thumbnail_output(profile)],
+  );
+
+  CreateJobRequest {
+    queue: Some(config.media_convert_queue_arn.clone()),
+    role: config.media_convert_role_arn.clone(),
+    status_update_interval: Some("SECONDS_10".to_owned()),
+    user_metadata: Some(user_metadata),
+    settings: JobSettings {
+      output_groups: Some(
+        vec![file_group]
+          .into_iter()
+          .chain(hls_output_group(profile, &destination))
+          .collect(),
+      ),
+      ad_avail_offset: Some(0),
+      inputs: Some(vec![Input {
+        audio_selectors: Some(audio_selectors),
+        video_selector: Some(VideoSelector {
+          color_space: Some("FOLLOW".to_owned()),
+          ..Default::default()
+        }),
+        filter_enable: Some("AUTO".to_owned()),
+        psi_control: Some("USE_PSI".to_owned()),
+        filter_strength: Some(0),
+        deblock_filter: Some("DISABLED".to_owned()),
+        denoise_filter: Some("DISABLED".to_owned()),
+        timecode_source: Some("EMBEDDED".to_owned()),
+        file_input: Some(format!("s3://{}/{}", config.uploads_bucket, input_key)),
+        ..Default::default()
+      }]),
+      ..Default::default()
+    },
+    ..Default::default()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::transcoding::{default_profiles, DEFAULT_PROFILE, HIGH_FRAMERATE_PROFILE};
+  use serde_json::json;
+
+  fn config_value() -> serde_json::Value {
+    json!({
+      "auth_client_secret": "secret",
+      "avatar_processor_lambda_arn": "arn:avatar-processor",
+      "aws_access_key_id": "key",
+      "aws_secret_key": "secret",
+      "host": "http://ranklab-api:8000",
+      "instance_id": "test",
+      "media_convert_events_queue_url": "https://sqs/media-convert-events",
+      "media_convert_queue_arn": "arn:queue",
+      "media_convert_role_arn": "arn:role",
+      "rekognition_queue_url": "https://sqs/rekognition",
+      "rekognition_role_arn": "arn:rekognition-role",
+      "rekognition_topic_arn": "arn:rekognition-topic",
+      "uploads_bucket": "uploads",
+      "uploads_queue_url": "https://sqs/uploads",
+      "web_host": "https://ranklab-test:3000",
+    })
+  }
+
+  fn config() -> Config {
+    serde_json::from_value(config_value()).unwrap()
+  }
+
+  fn h264_settings(request: &CreateJobRequest) -> &H264Settings {
+    request.settings.output_groups.as_ref().unwrap()[0]
+      .outputs
+      .as_ref()
+      .unwrap()[0]
+      .video_description
+      .as_ref()
+      .unwrap()
+      .codec_settings
+      .as_ref()
+      .unwrap()
+      .h264_settings
+      .as_ref()
+      .unwrap()
+  }
+
+  #[test]
+  fn default_profile_forces_framerate() {
+    let config = config();
+    let profiles = default_profiles();
+    let profile = &profiles[DEFAULT_PROFILE];
+    let request = create_job_request(&config, profile, "recordings/originals/vod");
+    let settings = h264_settings(&request);
+
+    assert_eq!(settings.framerate_control.as_deref(), Some("SPECIFIED"));
+    assert_eq!(settings.framerate_numerator, Some(24000));
+    assert_eq!(settings.framerate_denominator, Some(1001));
+    assert_eq!(settings.bitrate, Some(4_500_000));
+    assert_eq!(settings.codec_level.as_deref(), Some("LEVEL_3_1"));
+    assert_eq!(settings.gop_size, Some(24.0));
+    assert_eq!(settings.gop_size_units.as_deref(), Some("FRAMES"));
+  }
+
+  #[test]
+  fn configured_profiles_are_merged_with_defaults() {
+    let mut value = config_value();
+    let mut profile = serde_json::to_value(&default_profiles()[DEFAULT_PROFILE]).unwrap();
+
+    profile["bitrate"] = json!(3_000_000);
+    value["transcoding_profiles"] = json!({ DEFAULT_PROFILE: profile });
+
+    let config: Config = serde_json::from_value(value).unwrap();
+
+    assert_eq!(
+      config.transcoding_profiles[DEFAULT_PROFILE].bitrate,
+      3_000_000
+    );
+    assert!(config
+      .transcoding_profiles
+      .contains_key(HIGH_FRAMERATE_PROFILE));
+  }
+
+  #[test]
+  fn high_framerate_profile_passes_framerate_through() {
+    let config = config();
+    let profiles = default_profiles();
+    let profile = &profiles[HIGH_FRAMERATE_PROFILE];
+    let request = create_job_request(&config, profile, "recordings/originals/vod");
+    let settings = h264_settings(&request);
+
+    assert_eq!(
+      settings.framerate_control.as_deref(),
+      Some("INITIALIZE_FROM_SOURCE")
+    );
+    assert_eq!(settings.framerate_numerator, None);
+    assert_eq!(settings.framerate_denominator, None);
+    assert_eq!(settings.framerate_conversion_algorithm, None);
+  }
+
+  #[test]
+  fn request_targets_input_and_outputs() {
+    let config = config();
+    let profiles = default_profiles();
+    let profile = &profiles[DEFAULT_PROFILE];
+    let request = create_job_request(&config, profile, "recordings/originals/vod");
+    let output_groups = request.settings.output_groups.as_ref().unwrap();
+
+    assert_eq!(request.queue.as_deref(), Some("arn:queue"));
+    assert_eq!(request.role, "arn:role");
+    assert_eq!(
+      request.user_metadata.as_ref().unwrap().get("instance-id"),
+      Some(&"test".to_owned())
+    );
+    assert_eq!(
+      request.settings.inputs.as_ref().unwrap()[0]
+        .file_input
+        .as_deref(),
+      Some("s3://uploads/recordings/originals/vod")
+    );
+    assert_eq!(output_groups.len(), 2);
+    assert_eq!(
+      output_groups[1].outputs.as_ref().unwrap().len(),
+      profile.hls_renditions.len()
+    );
+  }
+
+  #[test]
+  fn request_skips_hls_without_renditions() {
+    let config = config();
+
+    let profile = TranscodingProfile {
+      hls_renditions: vec![],
+      ..default_profiles()[DEFAULT_PROFILE].clone()
+    };
+
+    let request = create_job_request(&config, &profile, "recordings/originals/vod");
+
+    assert_eq!(request.settings.output_groups.unwrap().len(), 1);
+  }
+
+  #[test]
+  fn clip_request_renders_a_single_clipped_video() {
+    let config = config();
+    let profiles = default_profiles();
+    let profile = &profiles[DEFAULT_PROFILE];
+    let request = clip_job_request(
+      &config,
+      profile,
+      "recordings/originals/vod",
+      "_clipid",
+      75,
+      3725,
+    );
+    let input = &request.settings.inputs.as_ref().unwrap()[0];
+    let clipping = &input.input_clippings.as_ref().unwrap()[0];
+    let output_groups = request.settings.output_groups.as_ref().unwrap();
+    let outputs = output_groups[0].outputs.as_ref().unwrap();
+
+    assert_eq!(input.timecode_source.as_deref(), Some("ZEROBASED"));
+    assert_eq!(clipping.start_timecode.as_deref(), Some("00:01:15:00"));
+    assert_eq!(clipping.end_timecode.as_deref(), Some("01:02:05:00"));
+    assert_eq!(output_groups.len(), 1);
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(outputs[0].name_modifier.as_deref(), Some("_clipid"));
+  }
+}
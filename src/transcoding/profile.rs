@@ -0,0 +1,157 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+
+pub const DEFAULT_PROFILE: &str = "default";
+pub const HIGH_FRAMERATE_PROFILE: &str = "high_framerate";
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Framerate {
+  pub numerator: i64,
+  pub denominator: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AudioProfile {
+  pub bitrate: i64,
+  pub sample_rate: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ThumbnailProfile {
+  pub interval_seconds: i64,
+  pub max_captures: i64,
+  pub quality: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct HlsRendition {
+  pub name: String,
+  pub height: Option<i64>,
+  pub max_bitrate: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GopSizeUnits {
+  Frames,
+  Seconds,
+}
+
+impl GopSizeUnits {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      GopSizeUnits::Frames => "FRAMES",
+      GopSizeUnits::Seconds => "SECONDS",
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TranscodingProfile {
+  pub width: i64,
+  pub height: i64,
+  pub bitrate: i64,
+  pub gop_size: f64,
+  pub gop_size_units: GopSizeUnits,
+  /// A MediaConvert H.264 level, such as `LEVEL_3_1` or `AUTO`.
+  pub codec_level: String,
+  /// When unset the source framerate is passed through untouched.
+  pub framerate: Option<Framerate>,
+  pub audio: AudioProfile,
+  pub thumbnails: ThumbnailProfile,
+  #[serde(default)]
+  pub hls_renditions: Vec<HlsRendition>,
+}
+
+fn hls_renditions(renditions: &[(&str, Option<i64>, i64)]) -> Vec<HlsRendition> {
+  renditions
+    .iter()
+    .map(|(name, height, max_bitrate)| HlsRendition {
+      name: name.to_string(),
+      height: *height,
+      max_bitrate: *max_bitrate,
+    })
+    .collect()
+}
+
+pub fn default_profiles() -> HashMap<String, TranscodingProfile> {
+  let mut profiles = HashMap::new();
+
+  profiles.insert(
+    DEFAULT_PROFILE.to_owned(),
+    TranscodingProfile {
+      width: 1280,
+      height: 720,
+      bitrate: 4_500_000,
+      gop_size: 24.0,
+      gop_size_units: GopSizeUnits::Frames,
+      codec_level: "LEVEL_3_1".to_owned(),
+      framerate: Some(Framerate {
+        numerator: 24000,
+        denominator: 1001,
+      }),
+      audio: AudioProfile {
+        bitrate: 96000,
+        sample_rate: 48000,
+      },
+      thumbnails: ThumbnailProfile {
+        interval_seconds: 10,
+        max_captures: 2,
+        quality: 80,
+      },
+      hls_renditions: hls_renditions(&[
+        ("360p", Some(360), 1_000_000),
+        ("720p", Some(720), 4_500_000),
+        ("1080p", Some(1080), 8_000_000),
+        ("source", None, 12_000_000),
+      ]),
+    },
+  );
+
+  profiles.insert(
+    HIGH_FRAMERATE_PROFILE.to_owned(),
+    TranscodingProfile {
+      width: 1280,
+      height: 720,
+      bitrate: 6_000_000,
+      gop_size: 2.0,
+      gop_size_units: GopSizeUnits::Seconds,
+      codec_level: "AUTO".to_owned(),
+      framerate: None,
+      audio: AudioProfile {
+        bitrate: 128000,
+        sample_rate: 48000,
+      },
+      thumbnails: ThumbnailProfile {
+        interval_seconds: 10,
+        max_captures: 2,
+        quality: 80,
+      },
+      hls_renditions: hls_renditions(&[
+        ("360p", Some(360), 1_500_000),
+        ("720p", Some(720), 6_000_000),
+        ("1080p", Some(1080), 10_000_000),
+        ("source", None, 16_000_000),
+      ]),
+    },
+  );
+
+  profiles
+}
+
+/// Configured profiles are added to the built-in ones, replacing those with
+/// the same name, so a config only needs to list what it changes.
+pub fn deserialize_profiles<'de, D>(
+  deserializer: D,
+) -> Result<HashMap<String, TranscodingProfile>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  let mut profiles = default_profiles();
+
+  profiles.extend(HashMap::<String, TranscodingProfile>::deserialize(
+    deserializer,
+  )?);
+
+  Ok(profiles)
+}
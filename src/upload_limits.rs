@@ -0,0 +1,284 @@
+use crate::aws::s3::upload_metadata;
+use crate::config::Config;
+use anyhow::Result;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
+use rocket::tokio;
+use rusoto_core::Region;
+use rusoto_credential::AwsCredentials;
+use rusoto_s3::util::PreSignedRequest;
+use rusoto_s3::{GetObjectRequest, HeadObjectRequest, PutObjectRequest, S3Client, S3};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::process::Command;
+use thiserror::Error;
+
+const REGION: &str = "eu-west-2";
+const UPLOAD_EXPIRY_MINUTES: i64 = 60;
+/// S3 refuses single PUT or POST uploads larger than this, so bigger
+/// recordings have to go through a multipart upload.
+const MAX_SINGLE_UPLOAD_SIZE: i64 = 5 * 1024 * 1024 * 1024;
+
+fn default_max_recording_size() -> i64 {
+  50 * 1024 * 1024 * 1024
+}
+
+fn default_recording_content_types() -> Vec<String> {
+  vec![
+    "video/mp4".to_owned(),
+    "video/quicktime".to_owned(),
+    "video/webm".to_owned(),
+    "video/x-matroska".to_owned(),
+  ]
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct UploadLimitsConfig {
+  /// Largest recording upload accepted, in bytes. Anything over 5 GB can
+  /// only be uploaded in parts.
+  #[serde(default = "default_max_recording_size")]
+  pub max_recording_size: i64,
+  #[serde(default = "default_recording_content_types")]
+  pub recording_content_types: Vec<String>,
+  /// Longest recording accepted, in seconds. Probing the duration requires
+  /// ffprobe, so it is skipped when unset.
+  pub max_recording_duration: Option<u64>,
+  pub ffprobe_path: Option<String>,
+}
+
+impl Default for UploadLimitsConfig {
+  fn default() -> Self {
+    Self {
+      max_recording_size: default_max_recording_size(),
+      recording_content_types: default_recording_content_types(),
+      max_recording_duration: None,
+      ffprobe_path: None,
+    }
+  }
+}
+
+pub struct PresignedPost {
+  pub url: String,
+  pub fields: HashMap<String, String>,
+}
+
+pub struct RecordingUpload {
+  /// Presigned PUT kept for existing clients. S3 can't enforce the limits on
+  /// it, so they're only checked once the upload lands.
+  pub put_url: String,
+  pub post: PresignedPost,
+}
+
+/// `ffprobe` couldn't be started at all, which is a problem with the
+/// deployment rather than with the uploaded file.
+#[derive(Error, Debug)]
+#[error("ffprobe could not be run: {0}")]
+pub struct FfprobeUnavailable(std::io::Error);
+
+pub fn presign_recording_upload(config: &Config, key: &str) -> RecordingUpload {
+  RecordingUpload {
+    put_url: presign_recording_put(config, key),
+    post: presign_recording_post(config, key),
+  }
+}
+
+fn presign_recording_put(config: &Config, key: &str) -> String {
+  let req = PutObjectRequest {
+    bucket: config.uploads_bucket.to_owned(),
+    key: key.to_owned(),
+    metadata: Some(upload_metadata(config)),
+    ..Default::default()
+  };
+
+  req.get_presigned_url(
+    &Region::EuWest2,
+    &AwsCredentials::new(
+      &config.aws_access_key_id,
+      &config.aws_secret_key,
+      None,
+      None,
+    ),
+    &Default::default(),
+  )
+}
+
+/// Builds a presigned POST whose policy makes S3 itself refuse uploads that
+/// are too large or are not videos.
+fn presign_recording_post(config: &Config, key: &str) -> PresignedPost {
+  let now = Utc::now();
+  let date = now.format("%Y%m%d").to_string();
+  let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+  let credential = format!(
+    "{}/{}/{}/s3/aws4_request",
+    config.aws_access_key_id, date, REGION
+  );
+
+  let mut fields = HashMap::new();
+
+  fields.insert("key".to_owned(), key.to_owned());
+  fields.insert("x-amz-algorithm".to_owned(), "AWS4-HMAC-SHA256".to_owned());
+  fields.insert("x-amz-credential".to_owned(), credential);
+  fields.insert("x-amz-date".to_owned(), amz_date);
+
+  if let Some(instance_id) = config.instance_id.as_ref() {
+    fields.insert("x-amz-meta-instance-id".to_owned(), instance_id.clone());
+  }
+
+  let mut conditions = vec![
+    json!({ "bucket": config.uploads_bucket }),
+    json!(["starts-with", "$Content-Type", "video/"]),
+    json!([
+      "content-length-range",
+      1,
+      config
+        .upload_limits
+        .max_recording_size
+        .min(MAX_SINGLE_UPLOAD_SIZE)
+    ]),
+  ];
+
+  conditions.extend(fields.iter().map(|(name, value)| {
+    let mut condition = serde_json::Map::new();
+    condition.insert(name.clone(), json!(value));
+    serde_json::Value::Object(condition)
+  }));
+
+  let policy = json!({
+    "expiration": (now + Duration::minutes(UPLOAD_EXPIRY_MINUTES))
+      .format("%Y-%m-%dT%H:%M:%SZ")
+      .to_string(),
+    "conditions": conditions,
+  });
+
+  let policy = BASE64.encode(policy.to_string());
+
+  let signing_key = [date.as_str(), REGION, "s3", "aws4_request"].iter().fold(
+    format!("AWS4{}", config.aws_secret_key).into_bytes(),
+    |key, part| hmac_sha256(&key, part.as_bytes()),
+  );
+
+  fields.insert(
+    "x-amz-signature".to_owned(),
+    hex::encode(hmac_sha256(&signing_key, policy.as_bytes())),
+  );
+
+  fields.insert("policy".to_owned(), policy);
+
+  PresignedPost {
+    url: format!(
+      "https://{}.s3.{}.amazonaws.com/",
+      config.uploads_bucket, REGION
+    ),
+    fields,
+  }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+  let mut mac = Hmac::<sha2::Sha256>::new_from_slice(key).unwrap();
+  mac.update(data);
+  mac.finalize().into_bytes().to_vec()
+}
+
+/// Checks an uploaded recording against the configured limits, returning the
+/// reason it should be rejected.
+pub async fn validate_recording(
+  config: &Config,
+  client: &S3Client,
+  key: &str,
+) -> Result<Option<String>> {
+  let limits = &config.upload_limits;
+
+  let object = client
+    .head_object(HeadObjectRequest {
+      bucket: config.uploads_bucket.clone(),
+      key: key.to_owned(),
+      ..Default::default()
+    })
+    .await?;
+
+  if object.content_length.unwrap_or_default() > limits.max_recording_size {
+    return Ok(Some(format!(
+      "The VOD must be smaller than {} GB.",
+      limits.max_recording_size / (1024 * 1024 * 1024)
+    )));
+  }
+
+  let content_type = object.content_type.unwrap_or_default();
+
+  if !limits.recording_content_types.contains(&content_type) {
+    return Ok(Some(
+      "The VOD must be an MP4, MOV, WebM or MKV file.".to_owned(),
+    ));
+  }
+
+  let max_duration = match limits.max_recording_duration {
+    Some(max_duration) => max_duration,
+    None => return Ok(None),
+  };
+
+  let duration = match probe_duration(config, key).await? {
+    Some(duration) => duration,
+    None => return Ok(Some("The VOD could not be read as a video.".to_owned())),
+  };
+
+  if duration > max_duration as f64 {
+    return Ok(Some(format!(
+      "The VOD must be shorter than {} minutes.",
+      max_duration / 60
+    )));
+  }
+
+  Ok(None)
+}
+
+/// Reads the duration straight from the bucket so the whole file doesn't
+/// need to be downloaded. Returns `None` when ffprobe can't parse the file
+/// and `FfprobeUnavailable` when it can't be run.
+async fn probe_duration(config: &Config, key: &str) -> Result<Option<f64>> {
+  let url = GetObjectRequest {
+    bucket: config.uploads_bucket.clone(),
+    key: key.to_owned(),
+    ..Default::default()
+  }
+  .get_presigned_url(
+    &Region::EuWest2,
+    &AwsCredentials::new(
+      &config.aws_access_key_id,
+      &config.aws_secret_key,
+      None,
+      None,
+    ),
+    &Default::default(),
+  );
+
+  let ffprobe_path = config
+    .upload_limits
+    .ffprobe_path
+    .clone()
+    .unwrap_or_else(|| "ffprobe".to_owned());
+
+  let output = tokio::task::spawn_blocking(move || {
+    Command::new(ffprobe_path)
+      .args([
+        "-v",
+        "error",
+        "-show_entries",
+        "format=duration",
+        "-of",
+        "default=noprint_wrappers=1:nokey=1",
+        url.as_str(),
+      ])
+      .output()
+  })
+  .await?
+  .map_err(FfprobeUnavailable)?;
+
+  if !output.status.success() {
+    return Ok(None);
+  }
+
+  Ok(String::from_utf8_lossy(&output.stdout).trim().parse().ok())
+}
@@ -1,10 +1,44 @@
+mod admin_user;
+mod audit_event;
 mod avatar;
+mod category_score;
+mod clip;
 mod comment;
+mod flagged_avatar;
+mod flagged_recording;
+mod following;
 mod game;
+mod notification_preference;
+mod processing_job;
+mod public_user;
 mod recording;
+mod recording_processing;
+mod report;
+mod review;
+mod skill_level;
+mod taxonomy_entry;
+mod upload_part;
+mod upload_session;
 mod user;
+pub use admin_user::AdminUserView;
+pub use audit_event::AuditEventView;
 pub use avatar::AvatarView;
+pub use category_score::CategoryScoreView;
+pub use clip::ClipView;
 pub use comment::CommentView;
+pub use flagged_avatar::FlaggedAvatarView;
+pub use flagged_recording::FlaggedRecordingView;
+pub use following::FollowingView;
 pub use game::GameView;
+pub use notification_preference::NotificationPreferenceView;
+pub use processing_job::ProcessingJobView;
+pub use public_user::PublicUserView;
 pub use recording::RecordingView;
+pub use recording_processing::RecordingProcessingView;
+pub use report::ReportView;
+pub use review::{ReviewScoreView, ReviewView};
+pub use skill_level::SkillLevelView;
+pub use taxonomy_entry::TaxonomyEntryView;
+pub use upload_part::UploadPartView;
+pub use upload_session::UploadSessionView;
 pub use user::UserView;
@@ -0,0 +1,35 @@
+use crate::data_types::UserRole;
+use crate::models::User;
+use schemars::JsonSchema;
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "AdminUser")]
+pub struct AdminUserView {
+  pub id: Uuid,
+  pub name: String,
+  pub email: String,
+  pub role: UserRole,
+  pub suspended_at: Option<chrono::NaiveDateTime>,
+  pub suspended_until: Option<chrono::NaiveDateTime>,
+  pub suspension_reason: Option<String>,
+  pub shadow_banned_at: Option<chrono::NaiveDateTime>,
+  pub created_at: chrono::NaiveDateTime,
+}
+
+impl From<User> for AdminUserView {
+  fn from(user: User) -> Self {
+    AdminUserView {
+      id: user.id,
+      name: user.name,
+      email: user.email,
+      role: user.role,
+      suspended_at: user.suspended_at,
+      suspended_until: user.suspended_until,
+      suspension_reason: user.suspension_reason,
+      shadow_banned_at: user.shadow_banned_at,
+      created_at: user.created_at,
+    }
+  }
+}
@@ -0,0 +1,33 @@
+use crate::data_types::{AuditAction, AuditTargetType};
+use crate::models::AuditEvent;
+use schemars::JsonSchema;
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "AuditEvent")]
+pub struct AuditEventView {
+  pub id: Uuid,
+  pub action: AuditAction,
+  pub actor_id: Option<Uuid>,
+  pub target_type: Option<AuditTargetType>,
+  pub target_id: Option<Uuid>,
+  pub ip_address: Option<String>,
+  pub payload: serde_json::Value,
+  pub created_at: chrono::NaiveDateTime,
+}
+
+impl From<AuditEvent> for AuditEventView {
+  fn from(event: AuditEvent) -> Self {
+    AuditEventView {
+      id: event.id,
+      action: event.action,
+      actor_id: event.actor_id,
+      target_type: event.target_type,
+      target_id: event.target_id,
+      ip_address: event.ip_address,
+      payload: event.payload,
+      created_at: event.created_at,
+    }
+  }
+}
@@ -1,3 +1,5 @@
+use crate::aws::s3::presign_download;
+use crate::config::Config;
 use crate::data_types::MediaState;
 use crate::models::Avatar;
 use schemars::JsonSchema;
@@ -9,28 +11,36 @@ use uuid::Uuid;
 pub struct AvatarView {
   pub id: Uuid,
   pub image_key: Option<String>,
+  pub image_url: Option<String>,
   pub upload_url: Option<String>,
   pub created_at: chrono::NaiveDateTime,
   pub updated_at: chrono::NaiveDateTime,
   pub state: MediaState,
+  pub state_reason: Option<String>,
+  pub state_changed_at: chrono::NaiveDateTime,
   pub instance_id: Option<String>,
 }
 
-impl From<Avatar> for AvatarView {
-  fn from(avatar: Avatar) -> Self {
-    AvatarView::new(avatar, None, None)
-  }
-}
-
 impl AvatarView {
-  pub fn new(avatar: Avatar, upload_url: Option<String>, instance_id: Option<String>) -> Self {
+  pub fn new(
+    avatar: Avatar,
+    config: &Config,
+    upload_url: Option<String>,
+    instance_id: Option<String>,
+  ) -> Self {
     AvatarView {
       id: avatar.id,
+      image_url: avatar
+        .processed_image_key
+        .as_ref()
+        .map(|key| presign_download(config, key)),
       image_key: avatar.processed_image_key,
       upload_url,
       created_at: avatar.created_at,
       updated_at: avatar.updated_at,
       state: avatar.state,
+      state_reason: avatar.state_reason,
+      state_changed_at: avatar.state_changed_at,
       instance_id,
     }
   }
@@ -0,0 +1,34 @@
+use crate::models::ReviewScore;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "CategoryScore")]
+pub struct CategoryScoreView {
+  pub category: String,
+  pub average: f64,
+  pub count: i64,
+}
+
+impl CategoryScoreView {
+  /// Averages the scores of every review per rubric category.
+  pub fn summarize(scores: Vec<ReviewScore>) -> Vec<Self> {
+    let mut totals = BTreeMap::new();
+
+    for score in scores {
+      let (sum, count) = totals.entry(score.category).or_insert((0i64, 0i64));
+      *sum += score.score as i64;
+      *count += 1;
+    }
+
+    totals
+      .into_iter()
+      .map(|(category, (sum, count))| CategoryScoreView {
+        category,
+        average: sum as f64 / count as f64,
+        count,
+      })
+      .collect()
+  }
+}
@@ -0,0 +1,41 @@
+use crate::aws::s3::presign_download;
+use crate::config::Config;
+use crate::data_types::ClipRenderState;
+use crate::models::Clip;
+use schemars::JsonSchema;
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "Clip")]
+pub struct ClipView {
+  pub id: Uuid,
+  pub recording_id: Uuid,
+  pub user_id: Uuid,
+  pub title: String,
+  pub start_timestamp: i64,
+  pub end_timestamp: i64,
+  pub render_state: Option<ClipRenderState>,
+  /// Signed URL of the rendered MP4, once rendering has finished.
+  pub video_url: Option<String>,
+  pub created_at: chrono::NaiveDateTime,
+}
+
+impl ClipView {
+  pub fn new(clip: Clip, config: &Config) -> Self {
+    ClipView {
+      id: clip.id,
+      recording_id: clip.recording_id,
+      user_id: clip.user_id,
+      title: clip.title,
+      start_timestamp: clip.start_timestamp,
+      end_timestamp: clip.end_timestamp,
+      render_state: clip.render_state,
+      video_url: clip
+        .video_key
+        .as_ref()
+        .map(|key| presign_download(config, key)),
+      created_at: clip.created_at,
+    }
+  }
+}
@@ -0,0 +1,38 @@
+use crate::aws::s3::presign_download;
+use crate::config::Config;
+use crate::data_types::MediaState;
+use crate::models::{Avatar, User};
+use crate::moderation::ModerationLabel;
+use schemars::JsonSchema;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::UserView;
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "FlaggedAvatar")]
+pub struct FlaggedAvatarView {
+  pub id: Uuid,
+  pub user_id: Uuid,
+  pub original_key: String,
+  pub original_url: String,
+  pub state: MediaState,
+  pub state_changed_at: chrono::NaiveDateTime,
+  pub moderation_labels: Vec<ModerationLabel>,
+  pub user: Option<UserView>,
+}
+
+impl FlaggedAvatarView {
+  pub fn new(avatar: Avatar, config: &Config, user: Option<User>) -> Self {
+    FlaggedAvatarView {
+      original_url: presign_download(config, &avatar.image_key),
+      id: avatar.id,
+      user_id: avatar.user_id,
+      original_key: avatar.image_key,
+      state: avatar.state,
+      state_changed_at: avatar.state_changed_at,
+      moderation_labels: ModerationLabel::from_json(avatar.moderation_labels),
+      user: user.map(UserView::from),
+    }
+  }
+}
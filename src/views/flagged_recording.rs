@@ -0,0 +1,45 @@
+use crate::aws::s3::presign_download;
+use crate::config::Config;
+use crate::data_types::MediaState;
+use crate::models::{Recording, User};
+use crate::moderation::ModerationLabel;
+use schemars::JsonSchema;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::UserView;
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "FlaggedRecording")]
+pub struct FlaggedRecordingView {
+  pub id: Uuid,
+  pub user_id: Uuid,
+  pub game_id: String,
+  pub title: String,
+  pub original_key: Option<String>,
+  pub original_url: Option<String>,
+  pub state: MediaState,
+  pub state_changed_at: chrono::NaiveDateTime,
+  pub moderation_labels: Vec<ModerationLabel>,
+  pub user: Option<UserView>,
+}
+
+impl FlaggedRecordingView {
+  pub fn new(recording: Recording, config: &Config, user: Option<User>) -> Self {
+    FlaggedRecordingView {
+      original_url: recording
+        .video_key
+        .as_ref()
+        .map(|key| presign_download(config, key)),
+      id: recording.id,
+      user_id: recording.user_id,
+      game_id: recording.game_id,
+      title: recording.title,
+      original_key: recording.video_key,
+      state: recording.state,
+      state_changed_at: recording.state_changed_at,
+      moderation_labels: ModerationLabel::from_json(recording.moderation_labels),
+      user: user.map(UserView::from),
+    }
+  }
+}
@@ -0,0 +1,26 @@
+use crate::models::Following;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// Narrows which of a followed game's recordings are included in digests.
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "Following")]
+pub struct FollowingView {
+  map: Option<String>,
+  role: Option<String>,
+  hero: Option<String>,
+  min_skill_level: Option<i16>,
+  max_skill_level: Option<i16>,
+}
+
+impl From<&Following> for FollowingView {
+  fn from(following: &Following) -> Self {
+    FollowingView {
+      map: following.map.to_owned(),
+      role: following.role.to_owned(),
+      hero: following.hero.to_owned(),
+      min_skill_level: following.min_skill_level,
+      max_skill_level: following.max_skill_level,
+    }
+  }
+}
@@ -1,5 +1,7 @@
-use crate::games::GameId;
-use crate::models::{Game, SkillLevel};
+use super::{FollowingView, SkillLevelView, TaxonomyEntryView};
+use crate::data_types::TaxonomyKind;
+use crate::games::CatalogueGame;
+use crate::models::{Following, ReviewCategory};
 use schemars::JsonSchema;
 use serde::Serialize;
 
@@ -7,24 +9,42 @@ use serde::Serialize;
 #[serde(rename = "Game")]
 pub struct GameView {
   name: String,
-  id: GameId,
-  skill_levels: Vec<SkillLevel>,
+  id: String,
+  skill_levels: Vec<SkillLevelView>,
+  review_categories: Vec<ReviewCategory>,
+  maps: Vec<TaxonomyEntryView>,
+  roles: Vec<TaxonomyEntryView>,
+  /// Heroes, agents, champions or legends, depending on the game.
+  heroes: Vec<TaxonomyEntryView>,
   followed: bool,
+  following: Option<FollowingView>,
 }
 
-impl From<&Game> for GameView {
-  fn from(game: &Game) -> Self {
-    GameView::new(game, false)
+impl From<&CatalogueGame> for GameView {
+  fn from(game: &CatalogueGame) -> Self {
+    GameView::new(game, None)
   }
 }
 
 impl GameView {
-  pub fn new(game: &Game, followed: bool) -> Self {
+  pub fn new(game: &CatalogueGame, following: Option<&Following>) -> Self {
+    let entries = |kind| -> Vec<TaxonomyEntryView> {
+      game
+        .taxonomy_entries(kind)
+        .map(TaxonomyEntryView::from)
+        .collect()
+    };
+
     GameView {
       name: game.name.to_owned(),
-      id: game.id,
-      skill_levels: game.skill_levels.to_owned(),
-      followed,
+      id: game.id.to_owned(),
+      skill_levels: game.skill_levels.iter().map(SkillLevelView::from).collect(),
+      review_categories: game.review_categories.to_owned(),
+      maps: entries(TaxonomyKind::Map),
+      roles: entries(TaxonomyKind::Role),
+      heroes: entries(TaxonomyKind::Hero),
+      followed: following.is_some(),
+      following: following.map(FollowingView::from),
     }
   }
 }
@@ -0,0 +1,19 @@
+use crate::data_types::{NotificationCategory, NotificationFrequency};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "NotificationPreference")]
+pub struct NotificationPreferenceView {
+  category: NotificationCategory,
+  frequency: NotificationFrequency,
+}
+
+impl NotificationPreferenceView {
+  pub fn new(category: NotificationCategory, frequency: NotificationFrequency) -> Self {
+    NotificationPreferenceView {
+      category,
+      frequency,
+    }
+  }
+}
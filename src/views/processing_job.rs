@@ -0,0 +1,24 @@
+use crate::data_types::ProcessingJobKind;
+use crate::models::ProcessingJob;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "ProcessingJob")]
+pub struct ProcessingJobView {
+  pub kind: ProcessingJobKind,
+  pub progress: Option<i32>,
+  pub started_at: chrono::NaiveDateTime,
+  pub finished_at: Option<chrono::NaiveDateTime>,
+}
+
+impl From<ProcessingJob> for ProcessingJobView {
+  fn from(job: ProcessingJob) -> Self {
+    ProcessingJobView {
+      kind: job.kind,
+      progress: job.progress,
+      started_at: job.started_at,
+      finished_at: job.finished_at,
+    }
+  }
+}
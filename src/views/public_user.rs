@@ -0,0 +1,22 @@
+use crate::models::User;
+use schemars::JsonSchema;
+use serde::Serialize;
+use uuid::Uuid;
+
+/// What other users get to see of a user, without their email or account
+/// details.
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "PublicUser")]
+pub struct PublicUserView {
+  pub id: Uuid,
+  pub name: String,
+}
+
+impl From<User> for PublicUserView {
+  fn from(user: User) -> Self {
+    PublicUserView {
+      id: user.id,
+      name: user.name,
+    }
+  }
+}
@@ -1,10 +1,14 @@
+use crate::aws::s3::presign_download;
+use crate::config::Config;
 use crate::data_types::MediaState;
-use crate::models::{Recording, User};
+use crate::models::{Recording, ReviewScore, User};
+use crate::upload_limits::RecordingUpload;
 use schemars::JsonSchema;
 use serde::Serialize;
+use std::collections::HashMap;
 use uuid::Uuid;
 
-use super::UserView;
+use super::{CategoryScoreView, UserView};
 
 #[derive(Serialize, JsonSchema)]
 #[serde(rename = "Recording")]
@@ -13,53 +17,95 @@ pub struct RecordingView {
   pub user_id: Uuid,
   pub video_key: Option<String>,
   pub thumbnail_key: Option<String>,
+  pub playlist_key: Option<String>,
+  pub video_url: Option<String>,
+  pub thumbnail_url: Option<String>,
+  /// HLS playlist served through the API so every segment URL is signed.
+  pub playlist_url: Option<String>,
+  /// Presigned PUT for the original upload.
   pub upload_url: Option<String>,
+  /// Presigned POST for the original upload. Preferred over `upload_url`
+  /// since S3 enforces the size and type limits up front.
+  pub upload_form_url: Option<String>,
+  /// Form fields to send with a POST to `upload_form_url`.
+  pub upload_form_fields: Option<HashMap<String, String>>,
   pub created_at: chrono::NaiveDateTime,
   pub updated_at: chrono::NaiveDateTime,
   pub game_id: String,
   pub title: String,
   pub skill_level: i16,
+  pub map: Option<String>,
+  pub role: Option<String>,
+  pub hero: Option<String>,
   pub state: MediaState,
+  pub state_reason: Option<String>,
+  pub state_changed_at: chrono::NaiveDateTime,
   pub instance_id: Option<String>,
   pub notes: String,
   pub user: Option<UserView>,
   pub notes_text: String,
   pub comment_count: i64,
-}
-
-impl From<Recording> for RecordingView {
-  fn from(recording: Recording) -> Self {
-    RecordingView::new(recording, None, None, None, None)
-  }
+  /// Average review score per rubric category. Left out of the responses to
+  /// creating and restoring a recording.
+  pub review_scores: Option<Vec<CategoryScoreView>>,
 }
 
 impl RecordingView {
   pub fn new(
     recording: Recording,
-    upload_url: Option<String>,
+    config: &Config,
+    upload: Option<RecordingUpload>,
     instance_id: Option<String>,
     user: Option<User>,
     comment_count: Option<i64>,
+    review_scores: Option<Vec<ReviewScore>>,
   ) -> Self {
     let notes_text = html2text::from_read(recording.notes.as_bytes(), 100);
 
+    let playlist_url = recording.playlist_key.as_ref().map(|playlist_key| {
+      format!(
+        "{}/recordings/{}/playlists/{}",
+        config.host,
+        recording.id,
+        playlist_key.rsplit('/').next().unwrap_or_default()
+      )
+    });
+
     RecordingView {
+      video_url: recording
+        .processed_video_key
+        .as_ref()
+        .map(|key| presign_download(config, key)),
+      thumbnail_url: recording
+        .thumbnail_key
+        .as_ref()
+        .map(|key| presign_download(config, key)),
+      playlist_url,
       id: recording.id,
       user_id: recording.user_id,
       video_key: recording.processed_video_key,
-      upload_url,
+      upload_url: upload.as_ref().map(|upload| upload.put_url.clone()),
+      upload_form_url: upload.as_ref().map(|upload| upload.post.url.clone()),
+      upload_form_fields: upload.map(|upload| upload.post.fields),
       created_at: recording.created_at,
       updated_at: recording.updated_at,
       game_id: recording.game_id,
       title: recording.title,
       skill_level: recording.skill_level,
+      map: recording.map,
+      role: recording.role,
+      hero: recording.hero,
       state: recording.state,
+      state_reason: recording.state_reason,
+      state_changed_at: recording.state_changed_at,
       thumbnail_key: recording.thumbnail_key,
+      playlist_key: recording.playlist_key,
       instance_id,
       notes: recording.notes,
       notes_text,
       user: user.map(UserView::from),
       comment_count: comment_count.unwrap_or(0),
+      review_scores: review_scores.map(CategoryScoreView::summarize),
     }
   }
 }
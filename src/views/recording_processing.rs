@@ -0,0 +1,28 @@
+use super::ProcessingJobView;
+use crate::data_types::MediaState;
+use crate::models::{ProcessingJob, Recording};
+use schemars::JsonSchema;
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "RecordingProcessing")]
+pub struct RecordingProcessingView {
+  pub recording_id: Uuid,
+  pub state: MediaState,
+  pub state_reason: Option<String>,
+  pub state_changed_at: chrono::NaiveDateTime,
+  pub jobs: Vec<ProcessingJobView>,
+}
+
+impl RecordingProcessingView {
+  pub fn new(recording: Recording, jobs: Vec<ProcessingJob>) -> Self {
+    RecordingProcessingView {
+      recording_id: recording.id,
+      state: recording.state,
+      state_reason: recording.state_reason,
+      state_changed_at: recording.state_changed_at,
+      jobs: jobs.into_iter().map(ProcessingJobView::from).collect(),
+    }
+  }
+}
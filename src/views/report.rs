@@ -0,0 +1,35 @@
+use crate::data_types::{ReportReason, ReportStatus};
+use crate::models::Report;
+use schemars::JsonSchema;
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "Report")]
+pub struct ReportView {
+  pub id: Uuid,
+  pub reporter_id: Uuid,
+  pub recording_id: Option<Uuid>,
+  pub comment_id: Option<Uuid>,
+  pub reason: ReportReason,
+  pub details: String,
+  pub status: ReportStatus,
+  pub resolved_at: Option<chrono::NaiveDateTime>,
+  pub created_at: chrono::NaiveDateTime,
+}
+
+impl From<Report> for ReportView {
+  fn from(report: Report) -> Self {
+    ReportView {
+      id: report.id,
+      reporter_id: report.reporter_id,
+      recording_id: report.recording_id,
+      comment_id: report.comment_id,
+      reason: report.reason,
+      details: report.details,
+      status: report.status,
+      resolved_at: report.resolved_at,
+      created_at: report.created_at,
+    }
+  }
+}
@@ -0,0 +1,64 @@
+use super::PublicUserView;
+use crate::models::{Review, ReviewScore, User};
+use schemars::JsonSchema;
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "ReviewScore")]
+pub struct ReviewScoreView {
+  pub category: String,
+  pub score: i16,
+  pub comment: String,
+}
+
+impl From<ReviewScore> for ReviewScoreView {
+  fn from(score: ReviewScore) -> Self {
+    ReviewScoreView {
+      category: score.category,
+      score: score.score,
+      comment: score.comment,
+    }
+  }
+}
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "Review")]
+pub struct ReviewView {
+  pub id: Uuid,
+  pub recording_id: Uuid,
+  pub reviewer_id: Uuid,
+  /// Overall summary of the recording.
+  pub body: String,
+  pub rating: Option<i16>,
+  pub strengths: String,
+  pub weaknesses: String,
+  pub focus_area: String,
+  pub accepted: bool,
+  pub helpful: bool,
+  pub scores: Vec<ReviewScoreView>,
+  pub reviewer: Option<PublicUserView>,
+  pub created_at: chrono::NaiveDateTime,
+  pub updated_at: chrono::NaiveDateTime,
+}
+
+impl ReviewView {
+  pub fn new(review: Review, scores: Vec<ReviewScore>, reviewer: Option<User>) -> Self {
+    ReviewView {
+      id: review.id,
+      recording_id: review.recording_id,
+      reviewer_id: review.reviewer_id,
+      body: review.body,
+      rating: review.rating,
+      strengths: review.strengths,
+      weaknesses: review.weaknesses,
+      focus_area: review.focus_area,
+      accepted: review.accepted_at.is_some(),
+      helpful: review.helpful_at.is_some(),
+      scores: scores.into_iter().map(ReviewScoreView::from).collect(),
+      reviewer: reviewer.map(PublicUserView::from),
+      created_at: review.created_at,
+      updated_at: review.updated_at,
+    }
+  }
+}
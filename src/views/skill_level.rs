@@ -0,0 +1,23 @@
+use crate::models::SkillLevel;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "SkillLevel")]
+pub struct SkillLevelView {
+  name: String,
+  value: i16,
+  /// Kept so existing recordings can still be labelled, but not offered for
+  /// new ones.
+  deprecated: bool,
+}
+
+impl From<&SkillLevel> for SkillLevelView {
+  fn from(skill_level: &SkillLevel) -> Self {
+    SkillLevelView {
+      name: skill_level.name.to_owned(),
+      value: skill_level.value,
+      deprecated: skill_level.is_deprecated(),
+    }
+  }
+}
@@ -0,0 +1,21 @@
+use crate::models::TaxonomyEntry;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "TaxonomyEntry")]
+pub struct TaxonomyEntryView {
+  key: String,
+  name: String,
+  deprecated: bool,
+}
+
+impl From<&TaxonomyEntry> for TaxonomyEntryView {
+  fn from(entry: &TaxonomyEntry) -> Self {
+    TaxonomyEntryView {
+      key: entry.key.to_owned(),
+      name: entry.name.to_owned(),
+      deprecated: entry.is_deprecated(),
+    }
+  }
+}
@@ -0,0 +1,34 @@
+use rusoto_s3::Part;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "UploadPart")]
+pub struct UploadPartView {
+  pub part_number: i64,
+  pub e_tag: Option<String>,
+  pub size: Option<i64>,
+  pub upload_url: Option<String>,
+}
+
+impl UploadPartView {
+  pub fn presigned(part_number: i64, upload_url: String) -> Self {
+    UploadPartView {
+      part_number,
+      e_tag: None,
+      size: None,
+      upload_url: Some(upload_url),
+    }
+  }
+}
+
+impl From<Part> for UploadPartView {
+  fn from(part: Part) -> Self {
+    UploadPartView {
+      part_number: part.part_number.unwrap_or_default(),
+      e_tag: part.e_tag,
+      size: part.size,
+      upload_url: None,
+    }
+  }
+}
@@ -0,0 +1,16 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::UploadPartView;
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "UploadSession")]
+pub struct UploadSessionView {
+  pub recording_id: Uuid,
+  pub upload_id: String,
+  pub part_size: i64,
+  pub max_parts: i64,
+  /// Parts S3 has already received. Clients resume by uploading the rest.
+  pub parts: Vec<UploadPartView>,
+}
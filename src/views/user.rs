@@ -1,4 +1,6 @@
+use crate::aws::s3::presign_download;
 use crate::config::Config;
+use crate::data_types::UserRole;
 use crate::intercom;
 use crate::models::{Avatar, User};
 use schemars::JsonSchema;
@@ -11,9 +13,10 @@ pub struct UserView {
   pub id: Uuid,
   pub name: String,
   pub email: String,
-  pub emails_enabled: bool,
   pub intercom_hash: Option<String>,
   pub avatar_image_key: Option<String>,
+  pub avatar_image_url: Option<String>,
+  pub role: UserRole,
 }
 
 impl From<User> for UserView {
@@ -25,14 +28,20 @@ impl From<User> for UserView {
 impl UserView {
   pub fn new(user: User, config: Option<&Config>, avatar: Option<Avatar>) -> Self {
     let intercom_hash = config.and_then(|config| intercom::generate_user_hash(&user.email, config));
+    let avatar_image_key = avatar.and_then(|avatar| avatar.processed_image_key);
+
+    let avatar_image_url = config
+      .zip(avatar_image_key.as_ref())
+      .map(|(config, key)| presign_download(config, key));
 
     UserView {
       id: user.id,
       name: user.name,
       email: user.email,
-      emails_enabled: user.emails_enabled,
       intercom_hash,
-      avatar_image_key: avatar.and_then(|avatar| avatar.processed_image_key),
+      avatar_image_key,
+      avatar_image_url,
+      role: user.role,
     }
   }
 }